@@ -1,6 +1,6 @@
 use argmin::core::{CostFunction, Error, Executor, observers::ObserverMode};
 use argmin_observer_slog::SlogLogger;
-use cobyla_argmin::CobylaSolver;
+use cobyla_argmin::{CobylaSolver, Verbosity};
 
 /// Problem cost function
 fn paraboloid(x: &[f64], _data: &mut ()) -> f64 {
@@ -29,7 +29,7 @@ fn main() {
 
     let res = Executor::new(problem, solver)
         .timer(true)
-        .configure(|state| state.max_iters(100).iprint(0))
+        .configure(|state| state.max_iters(100).verbosity(Verbosity::Silent))
         .add_observer(SlogLogger::term(), ObserverMode::Always)
         .run()
         .unwrap();
@@ -37,5 +37,5 @@ fn main() {
     // Wait a second (lets the logger flush everything before printing again)
     std::thread::sleep(std::time::Duration::from_secs(1));
     println!("*** Result argmin solver impl ***");
-    println!("Result:\n{}", res);
+    print!("{}", res.state().summary());
 }
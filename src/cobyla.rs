@@ -15,10 +15,45 @@
     clippy::collapsible_if,
     clippy::neg_cmp_op_on_partial_ord,
     clippy::single_match,
+    clippy::collapsible_match,
     clippy::unnecessary_cast
 )]
 
+use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Writer receiving the COBYLA printouts
+pub(crate) type CobylaWriter = Arc<Mutex<dyn Write + Send>>;
+
+thread_local! {
+    /// Writer of the printouts of the COBYLA iterations run in the current thread,
+    /// stdout when None
+    static OUTPUT: RefCell<Option<CobylaWriter>> = const { RefCell::new(None) };
+}
+
+/// Prints a line to the current COBYLA output (see [with_output])
+macro_rules! cobyla_println {
+    ($($arg:tt)*) => {
+        OUTPUT.with_borrow(|output| match output {
+            Some(output) => {
+                let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+                // Printouts are diagnostics only: a failing writer does not stop iterations
+                let _ = writeln!(output, $($arg)*);
+            }
+            None => println!($($arg)*),
+        })
+    };
+}
+
+/// Runs `f` with COBYLA printouts written to the given output (stdout when None)
+pub(crate) fn with_output<R>(output: Option<&CobylaWriter>, f: impl FnOnce() -> R) -> R {
+    let previous = OUTPUT.replace(output.cloned());
+    let result = f();
+    OUTPUT.set(previous);
+    result
+}
 
 #[repr(C)]
 pub(crate) enum CobylaStatus {
@@ -122,6 +157,27 @@ pub struct _cobyla_context {
     pub ifull: libc::c_long,
     pub jdrop: libc::c_long,
     pub status: libc::c_int,
+    /// Whether the initial simplex is given in `sim` and `simi` before the first
+    /// iteration (otherwise it is built along the axes with steps of `rhobeg`)
+    pub custom_simplex: libc::c_int,
+    /// Factor applied to the trust region radius when it is reduced (0.5 in Powell's code)
+    pub rho_reduction: libc::c_double,
+    /// Initial value of the penalty parameter of the merit function (0 in Powell's code)
+    pub parmu_init: libc::c_double,
+    /// Number of trust region steps evaluated, the `trial_*` fields being set from the
+    /// last one when not zero (diagnostics only, not used by the algorithm)
+    pub trial_step: libc::c_int,
+    /// Predicted reduction of the merit function by the last trust region step
+    pub trial_prerem: libc::c_double,
+    /// Actual reduction of the merit function by the last trust region step
+    pub trial_trured: libc::c_double,
+    /// Whether the last trust region step reduced the merit function and entered the simplex
+    pub trial_accepted: libc::c_int,
+    /// Whether the last trust region step was rejected by the caller (see `reject_trial`)
+    pub trial_rejected: libc::c_int,
+    /// Set by the caller to reject the pending trust region step without evaluating it,
+    /// the values then given are ignored (reset once the step is handled)
+    pub reject_trial: libc::c_int,
 }
 
 pub type cobyla_context_t = _cobyla_context;
@@ -158,6 +214,15 @@ impl Default for cobyla_context_t {
             ifull: 0,
             jdrop: 0,
             status: 0,
+            custom_simplex: 0,
+            rho_reduction: 0.5,
+            parmu_init: 0.,
+            trial_step: 0,
+            trial_prerem: 0.,
+            trial_trured: 0.,
+            trial_accepted: 0,
+            trial_rejected: 0,
+            reject_trial: 0,
         }
     }
 }
@@ -355,6 +420,79 @@ pub(crate) unsafe fn cobyla_get_last_f(mut ctx: *const cobyla_context_t) -> libc
     return (*ctx).f;
 }
 
+/// Replaces the pending geometry step from the best vertex by the step to `x`, which is
+/// then the point to be evaluated: the displacement of the replaced vertex is updated in
+/// SIM and its inverse SIMI as in the geometry step of COBYLA
+pub(crate) unsafe fn cobyla_set_geometry_step(
+    ctx: *mut cobyla_context_t,
+    x: *const libc::c_double,
+) {
+    let n = (*ctx).n;
+    let jdrop = (*ctx).jdrop - 1 as libc::c_int as libc::c_long;
+    let sim = (*ctx).sim;
+    let simi = (*ctx).simi;
+    let dx = (*ctx).dx;
+    let mut temp: libc::c_double = 0.0f64;
+    let mut i: libc::c_long = 0;
+    while i < n {
+        *dx.offset(i as isize) = *x.offset(i as isize) - *sim.offset((i + n * n) as isize);
+        *sim.offset((i + n * jdrop) as isize) = *dx.offset(i as isize);
+        temp += *simi.offset((jdrop + n * i) as isize) * *dx.offset(i as isize);
+        i += 1;
+    }
+    i = 0;
+    while i < n {
+        *simi.offset((jdrop + n * i) as isize) /= temp;
+        i += 1;
+    }
+    let mut j: libc::c_long = 0;
+    while j < n {
+        if j != jdrop {
+            temp = 0.0f64;
+            i = 0;
+            while i < n {
+                temp += *simi.offset((j + n * i) as isize) * *dx.offset(i as isize);
+                i += 1;
+            }
+            i = 0;
+            while i < n {
+                *simi.offset((j + n * i) as isize) -= temp * *simi.offset((jdrop + n * i) as isize);
+                i += 1;
+            }
+        }
+        j += 1;
+    }
+}
+
+/// Copies the `j`-th vertex of the simplex in `x` and its objective followed by its
+/// constraint values in `fc`: the best vertex is the `n`-th one, kept in the last column
+/// of SIM (x) and DATMAT (constraints, f, resmax), the others being given by their
+/// displacements from it in the first columns of SIM
+pub(crate) unsafe fn cobyla_get_vertex(
+    ctx: *const cobyla_context_t,
+    j: libc::c_long,
+    x: *mut libc::c_double,
+    fc: *mut libc::c_double,
+) {
+    let n = (*ctx).n;
+    let m = (*ctx).m;
+    let mpp = m + 2 as libc::c_int as libc::c_long;
+    let mut i: libc::c_long = 0;
+    while i < n {
+        *x.offset(i as isize) = *((*ctx).sim).offset((i + n * n) as isize);
+        if j < n {
+            *x.offset(i as isize) += *((*ctx).sim).offset((i + n * j) as isize);
+        }
+        i += 1;
+    }
+    *fc = *((*ctx).datmat).offset((m + mpp * j) as isize);
+    let mut k: libc::c_long = 0;
+    while k < m {
+        *fc.offset((k + 1) as isize) = *((*ctx).datmat).offset((k + mpp * j) as isize);
+        k += 1;
+    }
+}
+
 pub(crate) unsafe fn cobyla_iterate(
     mut ctx: *mut cobyla_context_t,
     mut f: libc::c_double,
@@ -422,6 +560,8 @@ pub(crate) unsafe fn cobyla_iterate(
     let mut w: *mut libc::c_double = 0 as *mut libc::c_double;
     let mut iact: *mut libc::c_long = 0 as *mut libc::c_long;
     let mut status: libc::c_int = 0;
+    let mut custom_simplex: bool = false;
+    let mut rho_reduction: libc::c_double = 0.;
     if ctx.is_null() {
         // *__errno_location() = 14 as libc::c_int;
         return -(3 as libc::c_int);
@@ -445,6 +585,8 @@ pub(crate) unsafe fn cobyla_iterate(
     dx = (*ctx).dx;
     w = (*ctx).w;
     status = (*ctx).status;
+    custom_simplex = (*ctx).custom_simplex != 0;
+    rho_reduction = (*ctx).rho_reduction;
     if x.is_null() || c.is_null() && m > 0 as libc::c_int as libc::c_long {
         // *__errno_location() = 14 as libc::c_int;
         (*ctx).status = -(3 as libc::c_int);
@@ -465,7 +607,7 @@ pub(crate) unsafe fn cobyla_iterate(
         parsig = zero;
         iflag = 0 as libc::c_int as libc::c_long;
         rho = rhobeg;
-        parmu = zero;
+        parmu = (*ctx).parmu_init;
         jdrop = np;
         ibrnch = 0 as libc::c_int as libc::c_long;
         temp = one / rho;
@@ -475,6 +617,11 @@ pub(crate) unsafe fn cobyla_iterate(
                 (i - 1 as libc::c_int as libc::c_long + n * (np - 1 as libc::c_int as libc::c_long))
                     as isize,
             ) = *x.offset((i - 1 as libc::c_int as libc::c_long) as isize);
+            if custom_simplex {
+                // Displacements of the vertices and their inverse are already set
+                i += 1;
+                continue;
+            }
             j = 1 as libc::c_int as libc::c_long;
             while j <= n {
                 *sim.offset(
@@ -498,9 +645,10 @@ pub(crate) unsafe fn cobyla_iterate(
             i += 1;
         }
         if iprint >= 2 as libc::c_int as libc::c_long {
-            println!(
-                "\n   The initial value of RHO is {}  and PARMU is set to zero.\n",
+            cobyla_println!(
+                "\n   The initial value of RHO is {}  and PARMU is set to {}.\n",
                 rho,
+                parmu,
             );
         }
         current_block = 14453151562619017203;
@@ -522,7 +670,7 @@ pub(crate) unsafe fn cobyla_iterate(
                 if nfvals >= maxfun && nfvals > 0 as libc::c_int as libc::c_long {
                     status = -(2 as libc::c_int);
                     if iprint > 0 as libc::c_int as libc::c_long {
-                        println!(
+                        cobyla_println!(
                             "Return from subroutine COBYLA because {:?}.\n",
                             cobyla_reason(status),
                         );
@@ -550,8 +698,9 @@ pub(crate) unsafe fn cobyla_iterate(
                     }
                     k += 1;
                 }
-                if nfvals == iprint - 1 as libc::c_int as libc::c_long
-                    || iprint == 3 as libc::c_int as libc::c_long
+                if (*ctx).reject_trial == 0
+                    && (nfvals == iprint - 1 as libc::c_int as libc::c_long
+                        || iprint == 3 as libc::c_int as libc::c_long)
                 {
                     print_calcfc(
                         n,
@@ -563,7 +712,20 @@ pub(crate) unsafe fn cobyla_iterate(
                 }
                 *con.offset((mp - 1 as libc::c_int as libc::c_long) as isize) = f;
                 *con.offset((mpp - 1 as libc::c_int as libc::c_long) as isize) = resmax;
-                if ibrnch == 1 as libc::c_int as libc::c_long {
+                if ibrnch == 1 as libc::c_int as libc::c_long && (*ctx).reject_trial != 0 {
+                    // Trial point is rejected by the caller without evaluation, the values
+                    // given for it are ignored: the step is unsuccessful, the simplex is
+                    // kept and the best vertex is returned if the iterations end
+                    (*ctx).reject_trial = 0;
+                    (*ctx).trial_step += 1;
+                    (*ctx).trial_prerem = prerem;
+                    (*ctx).trial_trured = libc::c_double::NAN;
+                    (*ctx).trial_accepted = 0;
+                    (*ctx).trial_rejected = 1;
+                    ifull = 0 as libc::c_int as libc::c_long;
+                    jdrop = 0 as libc::c_int as libc::c_long;
+                    current_block = 12414752556692412193;
+                } else if ibrnch == 1 as libc::c_int as libc::c_long {
                     vmold = *datmat.offset(
                         (mp - 1 as libc::c_int as libc::c_long
                             + mpp * (np - 1 as libc::c_int as libc::c_long))
@@ -648,6 +810,11 @@ pub(crate) unsafe fn cobyla_iterate(
                     if l > 0 as libc::c_int as libc::c_long {
                         jdrop = l;
                     }
+                    (*ctx).trial_step += 1;
+                    (*ctx).trial_prerem = prerem;
+                    (*ctx).trial_trured = trured;
+                    (*ctx).trial_accepted = (jdrop != 0 && trured > zero) as libc::c_int;
+                    (*ctx).trial_rejected = 0;
                     if jdrop == 0 as libc::c_int as libc::c_long {
                         current_block = 12414752556692412193;
                     } else {
@@ -736,7 +903,20 @@ pub(crate) unsafe fn cobyla_iterate(
                         k += 1;
                     }
                     if !(nfvals > np) {
-                        if jdrop <= n {
+                        if jdrop <= n && custom_simplex {
+                            // Vertices of a custom simplex are all evaluated from the
+                            // initial point, the best one being identified afterwards
+                            i = 1 as libc::c_int as libc::c_long;
+                            while i <= n {
+                                *x.offset((i - 1 as libc::c_int as libc::c_long) as isize) = *sim
+                                    .offset(
+                                        (i - 1 as libc::c_int as libc::c_long
+                                            + n * (np - 1 as libc::c_int as libc::c_long))
+                                            as isize,
+                                    );
+                                i += 1;
+                            }
+                        } else if jdrop <= n {
                             if *datmat.offset(
                                 (mp - 1 as libc::c_int as libc::c_long
                                     + mpp * (np - 1 as libc::c_int as libc::c_long))
@@ -802,7 +982,21 @@ pub(crate) unsafe fn cobyla_iterate(
                         }
                         if nfvals <= n {
                             jdrop = nfvals;
-                            *x.offset((jdrop - 1 as libc::c_int as libc::c_long) as isize) += rho;
+                            if custom_simplex {
+                                i = 1 as libc::c_int as libc::c_long;
+                                while i <= n {
+                                    *x.offset((i - 1 as libc::c_int as libc::c_long) as isize) +=
+                                        *sim.offset(
+                                            (i - 1 as libc::c_int as libc::c_long
+                                                + n * (jdrop - 1 as libc::c_int as libc::c_long))
+                                                as isize,
+                                        );
+                                    i += 1;
+                                }
+                            } else {
+                                *x.offset((jdrop - 1 as libc::c_int as libc::c_long) as isize) +=
+                                    rho;
+                            }
                             current_block = 14453151562619017203;
                             continue;
                         }
@@ -817,7 +1011,7 @@ pub(crate) unsafe fn cobyla_iterate(
                                 ibrnch = 0 as libc::c_int as libc::c_long;
                                 current_block = 10213206415157612706;
                             } else if rho > rhoend {
-                                rho = 0.5f64 * rho;
+                                rho = rho_reduction * rho;
                                 if rho <= 1.5f64 * rhoend {
                                     rho = rhoend;
                                 }
@@ -862,9 +1056,10 @@ pub(crate) unsafe fn cobyla_iterate(
                                     }
                                 }
                                 if iprint >= 2 as libc::c_int as libc::c_long {
-                                    println!(
+                                    cobyla_println!(
                                         "\n   Reduction in RHO to {}  and PARMU ={}\n",
-                                        rho, parmu,
+                                        rho,
+                                        parmu,
                                     );
                                     if iprint == 2 as libc::c_int as libc::c_long {
                                         print_calcfc(
@@ -892,7 +1087,7 @@ pub(crate) unsafe fn cobyla_iterate(
                                 current_block = 10213206415157612706;
                             } else {
                                 if iprint >= 1 as libc::c_int as libc::c_long {
-                                    println!("\n   Normal return from subroutine COBYLA\n");
+                                    cobyla_println!("\n   Normal return from subroutine COBYLA\n");
                                 }
                                 status = 0 as libc::c_int;
                                 if ifull == 1 as libc::c_int as libc::c_long {
@@ -1038,7 +1233,7 @@ pub(crate) unsafe fn cobyla_iterate(
                             if error > 0.1f64 {
                                 status = -(1 as libc::c_int);
                                 if iprint >= 1 as libc::c_int as libc::c_long {
-                                    println!(
+                                    cobyla_println!(
                                         "Return from subroutine COBYLA because {:?}.\n",
                                         cobyla_reason(status),
                                     );
@@ -1211,7 +1406,7 @@ pub(crate) unsafe fn cobyla_iterate(
                                     }
                                     parmu = 2.0f64 * barmu;
                                     if iprint >= 2 as libc::c_int as libc::c_long {
-                                        println!("\n   Increase in PARMU to {}\n", parmu);
+                                        cobyla_println!("\n   Increase in PARMU to {}\n", parmu);
                                     }
                                     phi = *datmat.offset(
                                         (mp - 1 as libc::c_int as libc::c_long
@@ -1606,7 +1801,7 @@ unsafe fn cobylb(
         i += 1;
     }
     if iprint >= 2 as libc::c_int as libc::c_long {
-        println!(
+        cobyla_println!(
             "\n   The initial value of RHO is {}  and PARMU is set to zero.\n",
             rho,
         );
@@ -1615,7 +1810,7 @@ unsafe fn cobylb(
         if nfvals >= maxfun && nfvals > 0 as libc::c_int as libc::c_long {
             status = -(2 as libc::c_int);
             if iprint > 0 as libc::c_int as libc::c_long {
-                println!(
+                cobyla_println!(
                     "Return from subroutine COBYLA because {:?}.\n",
                     cobyla_reason(status),
                 );
@@ -1950,9 +2145,10 @@ unsafe fn cobylb(
                                 }
                             }
                             if iprint >= 2 as libc::c_int as libc::c_long {
-                                println!(
+                                cobyla_println!(
                                     "\n   Reduction in RHO to {}  and PARMU = {}\n",
-                                    rho, parmu,
+                                    rho,
+                                    parmu,
                                 );
                                 if iprint == 2 as libc::c_int as libc::c_long {
                                     print_calcfc(
@@ -1979,7 +2175,7 @@ unsafe fn cobylb(
                             current_block = 1854612888966593411;
                         } else {
                             if iprint >= 1 as libc::c_int as libc::c_long {
-                                println!("\n   Normal return from subroutine COBYLA\n");
+                                cobyla_println!("\n   Normal return from subroutine COBYLA\n");
                             }
                             status = 0 as libc::c_int;
                             if ifull == 1 as libc::c_int as libc::c_long {
@@ -2125,7 +2321,7 @@ unsafe fn cobylb(
                         if error > 0.1f64 {
                             status = -(1 as libc::c_int);
                             if iprint >= 1 as libc::c_int as libc::c_long {
-                                println!(
+                                cobyla_println!(
                                     "Return from subroutine COBYLA because {:?}.\n",
                                     cobyla_reason(status),
                                 );
@@ -2295,7 +2491,7 @@ unsafe fn cobylb(
                                 }
                                 parmu = 2.0f64 * barmu;
                                 if iprint >= 2 as libc::c_int as libc::c_long {
-                                    println!("\n   Increase in PARMU to {}\n", parmu);
+                                    cobyla_println!("\n   Increase in PARMU to {}\n", parmu);
                                 }
                                 phi = *datmat.offset(
                                     (mp - 1 as libc::c_int as libc::c_long
@@ -3484,7 +3680,7 @@ unsafe fn print_calcfc(
     mut x: *const libc::c_double,
 ) {
     let mut i: libc::c_long = 0;
-    println!(
+    cobyla_println!(
         "\n   NFVALS ={}   F ={}    MAXCV ={}\n   X ={}",
         nfvals as libc::c_int,
         f,
@@ -3498,8 +3694,8 @@ unsafe fn print_calcfc(
         // } else {
         //     "{}"
         // };
-        println!("{}", *x.offset(i as isize));
+        cobyla_println!("{}", *x.offset(i as isize));
         i += 1;
     }
-    println!("\n");
+    cobyla_println!("\n");
 }
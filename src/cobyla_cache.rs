@@ -0,0 +1,93 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Bounded (least recently used eviction) cache of cost function evaluations.
+///
+/// Parameter vectors are quantized with the given tolerance: points whose components
+/// round to the same multiples of `tol` share the same entry (exact match when `tol`
+/// is not strictly positive).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub(crate) struct EvalCache {
+    /// Maximum number of entries
+    capacity: usize,
+    /// Quantization tolerance of parameter components
+    tol: f64,
+    /// Cost function outputs and their last use stamp by quantized parameter vector
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    entries: HashMap<Vec<u64>, (u64, Vec<f64>)>,
+    /// Quantized parameter vectors by last use stamp
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    usage: BTreeMap<u64, Vec<u64>>,
+    /// Use stamp counter
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    stamp: u64,
+}
+
+impl EvalCache {
+    pub(crate) fn new(capacity: usize, tol: f64) -> Self {
+        EvalCache {
+            capacity,
+            tol,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            stamp: 0,
+        }
+    }
+
+    /// Returns the maximum number of entries and the quantization tolerance
+    pub(crate) fn settings(&self) -> (usize, f64) {
+        (self.capacity, self.tol)
+    }
+
+    /// Removes all entries
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.usage.clear();
+        self.stamp = 0;
+    }
+
+    /// Returns the cost function output cached for x if any
+    pub(crate) fn get(&mut self, x: &[f64]) -> Option<Vec<f64>> {
+        let key = self.key(x);
+        self.stamp += 1;
+        let (stamp, fx) = self.entries.get_mut(&key)?;
+        self.usage.remove(stamp);
+        *stamp = self.stamp;
+        self.usage.insert(self.stamp, key);
+        Some(fx.clone())
+    }
+
+    /// Stores the cost function output for x, evicting the least recently used entry
+    /// when the cache is full
+    pub(crate) fn insert(&mut self, x: &[f64], fx: Vec<f64>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = self.key(x);
+        self.stamp += 1;
+        if let Some((stamp, _)) = self.entries.remove(&key) {
+            self.usage.remove(&stamp);
+        } else if self.entries.len() >= self.capacity
+            && let Some((_, lru)) = self.usage.pop_first()
+        {
+            self.entries.remove(&lru);
+        }
+        self.usage.insert(self.stamp, key.clone());
+        self.entries.insert(key, (self.stamp, fx));
+    }
+
+    /// Returns the quantized parameter vector used as key
+    fn key(&self, x: &[f64]) -> Vec<u64> {
+        x.iter()
+            .map(|&xi| {
+                if self.tol > 0. {
+                    (xi / self.tol).round() as i64 as u64
+                } else {
+                    xi.to_bits()
+                }
+            })
+            .collect()
+    }
+}
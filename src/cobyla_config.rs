@@ -0,0 +1,122 @@
+use crate::cobyla_solver::CobylaSolver;
+use crate::{
+    ConstraintSense, DegenerateSimplex, EqualityConstraint, NanConstraintPolicy, NonFinitePolicy,
+    RhoBeg, Sense, SimplexInit, StopTols, VariableTransform,
+};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+/// Snapshot of the inputs of a [CobylaSolver] run: initial guess and solver tunables
+/// affecting the result, to be archived along with the optimization results and
+/// reproduced later with [CobylaSolver::from_config].
+///
+/// Contrary to argmin checkpointing which saves the in-flight solver and state, the
+/// configuration only captures how the solver was set up. Settings which do not affect the
+/// result (printouts, observers, callbacks, recording of history) and constraints added
+/// with closures (see [crate::CobylaSolverBuilder::add_constraint]) are not part of it.
+/// Neither is the iteration budget set on the state with argmin `max_iters`, which has
+/// to be archived along with the configuration.
+///
+/// ```
+/// use cobyla_argmin::{CobylaConfig, CobylaSolver, RhoBeg};
+///
+/// let config = CobylaConfig {
+///     x0: vec![1., 1.],
+///     rhobeg: Some(RhoBeg::All(0.1)),
+///     seed: Some(42),
+///     ..CobylaConfig::default()
+/// };
+/// let solver = CobylaSolver::from_config(config.clone());
+/// assert_eq!(solver.config().rhoend, config.rhoend);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct CobylaConfig {
+    /// Initial guess for x value
+    pub x0: Vec<f64>,
+    /// Initial change of x (see [crate::CobylaSolverBuilder::rhobeg])
+    pub rhobeg: Option<RhoBeg>,
+    /// Minimum initial change of x components used with [RhoBeg::Relative]
+    pub rhobeg_floor: f64,
+    /// Final value of the trust region radius (see [crate::CobylaSolverBuilder::rhoend])
+    pub rhoend: Option<f64>,
+    /// Factor applied to the trust region radius when it is reduced
+    pub rho_reduction: f64,
+    /// Initial value of the penalty parameter, Powell's one when not set
+    pub initial_penalty: Option<f64>,
+    /// Construction of the initial simplex
+    pub simplex_init: SimplexInit,
+    /// Handling of a degenerate custom initial simplex
+    pub degenerate_simplex: DegenerateSimplex,
+    /// Termination criteria
+    pub stop_tols: StopTols,
+    /// Lower and upper bounds of x components
+    pub bounds: Option<(Vec<f64>, Vec<f64>)>,
+    /// Scaling of x components
+    pub scaling: Option<Vec<f64>>,
+    /// Transforms of x components
+    pub transforms: Option<Vec<VariableTransform>>,
+    /// Scaling of the constraints returned by the cost function
+    pub constraint_scaling: Option<Vec<f64>>,
+    /// Equality constraints among cost function output
+    pub eq_constraints: Vec<EqualityConstraint>,
+    /// Convention of the constraints without a sense of their own
+    pub constraint_sense: ConstraintSense,
+    /// Convention of each constraint returned by the cost function
+    pub constraint_senses: Option<Vec<ConstraintSense>>,
+    /// Policy applied to non-finite cost function values
+    pub non_finite_policy: NonFinitePolicy,
+    /// Policy applied to NaN constraint values
+    pub nan_constraint_policy: NanConstraintPolicy,
+    /// Wall-clock time budget
+    pub max_time: Option<Duration>,
+    /// Budget of cost function evaluations
+    pub max_fun_evals: Option<u64>,
+    /// Objective value target of feasible points
+    pub stopval: Option<f64>,
+    /// Optimization sense of the objective
+    pub sense: Sense,
+    /// Number of iterations and minimum improvement of the stagnation criterion
+    pub stagnation_window: Option<(usize, f64)>,
+    /// Constraint margin and objective gradient tolerance of the interior criterion
+    pub interior_stop: Option<(f64, f64)>,
+    /// Number of repeated cost function evaluations averaged at each point
+    pub resample: usize,
+    /// Whether the initial simplex vertices are evaluated in a single batch
+    pub batch_initial_simplex: bool,
+    /// Whether points are clamped within the bounds before calling the cost function
+    pub clamp_to_bounds: bool,
+    /// Whether trust region steps out of the bounds are rejected without evaluation
+    pub reject_out_of_bounds: bool,
+    /// Whether a feasibility restoration phase is run from an infeasible initial guess
+    pub restore_feasibility: bool,
+    /// Whether only a feasible point is searched, the objective being ignored
+    pub feasibility_only: bool,
+    /// Maximum number of restarts after rounding errors
+    pub max_restarts: usize,
+    /// Whether the best feasible point is returned instead of an infeasible best point
+    pub prefer_feasible: bool,
+    /// Whether convergence on x is only accepted at feasible points
+    pub feasible_stop: bool,
+    /// Whether an infeasible result is polished by a final feasibility restoration
+    pub polish_feasibility: bool,
+    /// Maximum constraint violation ending the feasibility restoration phase
+    pub feasibility_tol: f64,
+    /// Capacity and tolerance of the evaluation cache (see
+    /// [crate::CobylaSolverBuilder::with_cache])
+    pub cache: Option<(usize, f64)>,
+    /// Whether panics of the cost function are caught
+    pub catch_panics: bool,
+    /// Seed of the random number generator of the user workflow (eg used to draw the
+    /// start points of [crate::MultiStartCobyla]). The solver itself is deterministic and
+    /// does not use it: it is only archived along with the other settings.
+    pub seed: Option<u64>,
+}
+
+impl Default for CobylaConfig {
+    fn default() -> Self {
+        CobylaSolver::builder().build().config()
+    }
+}
@@ -0,0 +1,327 @@
+use crate::FailStatus;
+use crate::cobyla::cobyla_context_t;
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Owner of the COBYLA context used by the reverse communication iterations.
+///
+/// The context holds pointers to the working arrays (simplex, constraint values, ...)
+/// which are allocated and owned along with it, hence the context can be cloned,
+/// serialized and deserialized preserving the whole state of the algorithm.
+#[derive(Debug)]
+pub(crate) struct CobylaContext {
+    ctx: Box<cobyla_context_t>,
+    iact: Vec<libc::c_long>,
+    work: Vec<libc::c_double>,
+    /// Scaling of x components of the space where COBYLA is run
+    pub(crate) scale: Option<Vec<f64>>,
+    /// Next point to be evaluated (in COBYLA space) when iterations are suspended
+    pub(crate) pending: Option<Vec<f64>>,
+}
+
+// SAFETY: context pointers only refer to the working arrays owned by the context
+// which are moved along with it and only mutated through `&mut self`
+unsafe impl Send for CobylaContext {}
+unsafe impl Sync for CobylaContext {}
+
+impl CobylaContext {
+    /// Creates a new context, returns [FailStatus::InvalidArgs] when arguments are invalid
+    /// and [FailStatus::OutOfMemory] when the working arrays cannot be allocated.
+    /// See `cobyla_create()` for the meaning of the arguments.
+    pub(crate) fn new(
+        n: libc::c_long,
+        m: libc::c_long,
+        rhobeg: libc::c_double,
+        rhoend: libc::c_double,
+        iprint: libc::c_long,
+        maxfun: libc::c_long,
+    ) -> Result<Self, FailStatus> {
+        if n < 1 || m < 0 || rhobeg < rhoend || rhoend <= 0. || maxfun < 1 {
+            return Err(FailStatus::InvalidArgs);
+        }
+        let (iact_len, work_len) =
+            work_sizes(n as usize, m as usize).ok_or(FailStatus::OutOfMemory)?;
+        let iact = try_zeroed(iact_len)?;
+        let work = try_zeroed(work_len)?;
+        let ctx = cobyla_context_t {
+            n,
+            m,
+            iprint,
+            maxfun,
+            nfvals: 0,
+            rhobeg,
+            rhoend,
+            status: 1,
+            ..cobyla_context_t::default()
+        };
+        let mut context = CobylaContext {
+            ctx: Box::new(ctx),
+            iact,
+            work,
+            scale: None,
+            pending: None,
+        };
+        context.link();
+        Ok(context)
+    }
+
+    /// Returns the numbers of variables and constraints
+    pub(crate) fn dims(&self) -> (usize, usize) {
+        (self.ctx.n as usize, self.ctx.m as usize)
+    }
+
+    /// Returns the current trust region radius
+    pub(crate) fn rho(&self) -> f64 {
+        self.ctx.rho
+    }
+
+    /// Sets the budget of evaluations to `maxfun` evaluations in addition to the ones
+    /// already done, returns false when the budget is invalid
+    pub(crate) fn extend_maxfun(&mut self, maxfun: libc::c_long) -> bool {
+        if maxfun < 1 {
+            return false;
+        }
+        self.ctx.maxfun = self.ctx.nfvals.saturating_add(maxfun);
+        true
+    }
+
+    /// Sets the initial simplex given the displacements of its vertices from the initial
+    /// point and their inverse (`n x n` column-major matrices), to be called before the
+    /// first iteration
+    pub(crate) fn set_simplex(&mut self, sim: &[f64], simi: &[f64]) {
+        let n = self.ctx.n as usize;
+        let mpp = self.ctx.m as usize + 2;
+        // Same offsets as the ones of `link()`
+        self.work[mpp..mpp + n * n].copy_from_slice(sim);
+        let offset = mpp + n * n + n;
+        self.work[offset..offset + n * n].copy_from_slice(simi);
+        self.ctx.custom_simplex = 1;
+    }
+
+    /// Sets the factor applied to the trust region radius when it is reduced
+    pub(crate) fn set_rho_reduction(&mut self, rho_reduction: f64) {
+        self.ctx.rho_reduction = rho_reduction;
+    }
+
+    /// Sets the initial value of the penalty parameter of the merit function
+    pub(crate) fn set_initial_penalty(&mut self, penalty: f64) {
+        self.ctx.parmu_init = penalty;
+    }
+
+    /// Sets context pointers to the owned working arrays
+    /// (same layout as the one used in `cobyla_create()`)
+    fn link(&mut self) {
+        let n = self.ctx.n as usize;
+        let m = self.ctx.m as usize;
+        let mpp = m + 2;
+        let ctx = &mut self.ctx;
+        ctx.iact = self.iact.as_mut_ptr();
+        let work = self.work.as_mut_ptr();
+        unsafe {
+            ctx.con = work;
+            ctx.sim = ctx.con.add(mpp);
+            ctx.simi = ctx.sim.add(n * n + n);
+            ctx.datmat = ctx.simi.add(n * n);
+            ctx.a = ctx.datmat.add(n * mpp + mpp);
+            ctx.vsig = ctx.a.add(m * n + n);
+            ctx.veta = ctx.vsig.add(n);
+            ctx.sigbar = ctx.veta.add(n);
+            ctx.dx = ctx.sigbar.add(n);
+            ctx.w = ctx.dx.add(n);
+        }
+    }
+
+    /// Returns a pointer to the context for use with `cobyla_*()` functions
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut cobyla_context_t {
+        &mut *self.ctx
+    }
+}
+
+/// Returns the lengths of the integer and floating point working arrays of a context
+/// with `n` variables and `m` constraints, None when they overflow
+pub(crate) fn work_sizes(n: usize, m: usize) -> Option<(usize, usize)> {
+    let iact = m.checked_add(1)?;
+    let work = n
+        .checked_mul(3)?
+        .checked_add(m.checked_mul(2)?)?
+        .checked_add(11)?
+        .checked_mul(n)?
+        .checked_add(m.checked_mul(4)?)?
+        .checked_add(6)?;
+    Some((iact, work))
+}
+
+/// Returns a zeroed working array of the given length, [FailStatus::OutOfMemory] when
+/// it cannot be allocated
+fn try_zeroed<T: Clone + Default>(len: usize) -> Result<Vec<T>, FailStatus> {
+    let mut v = Vec::new();
+    v.try_reserve_exact(len)
+        .map_err(|_| FailStatus::OutOfMemory)?;
+    v.resize(len, T::default());
+    Ok(v)
+}
+
+impl Clone for CobylaContext {
+    fn clone(&self) -> Self {
+        let mut context = CobylaContext {
+            ctx: self.ctx.clone(),
+            iact: self.iact.clone(),
+            work: self.work.clone(),
+            scale: self.scale.clone(),
+            pending: self.pending.clone(),
+        };
+        context.link();
+        context
+    }
+}
+
+/// Reduction factor of the trust region radius of contexts serialized without it
+#[cfg(feature = "serde1")]
+fn default_rho_reduction() -> libc::c_double {
+    cobyla_context_t::default().rho_reduction
+}
+
+/// Serializable content of the context: pointers are not serialized
+/// and are restored at deserialization from the working arrays.
+#[cfg(feature = "serde1")]
+#[derive(Serialize, Deserialize)]
+struct CobylaContextData {
+    n: libc::c_long,
+    m: libc::c_long,
+    iprint: libc::c_long,
+    maxfun: libc::c_long,
+    nfvals: libc::c_long,
+    rhobeg: libc::c_double,
+    rhoend: libc::c_double,
+    parmu: libc::c_double,
+    parsig: libc::c_double,
+    prerec: libc::c_double,
+    prerem: libc::c_double,
+    rho: libc::c_double,
+    f: libc::c_double,
+    ibrnch: libc::c_long,
+    iflag: libc::c_long,
+    ifull: libc::c_long,
+    jdrop: libc::c_long,
+    status: libc::c_int,
+    #[serde(default)]
+    custom_simplex: libc::c_int,
+    #[serde(default = "default_rho_reduction")]
+    rho_reduction: libc::c_double,
+    #[serde(default)]
+    parmu_init: libc::c_double,
+    #[serde(default)]
+    reject_trial: libc::c_int,
+    #[serde(default)]
+    trial_step: libc::c_int,
+    #[serde(default)]
+    trial_prerem: libc::c_double,
+    #[serde(default)]
+    trial_trured: libc::c_double,
+    #[serde(default)]
+    trial_accepted: libc::c_int,
+    #[serde(default)]
+    trial_rejected: libc::c_int,
+    iact: Vec<libc::c_long>,
+    work: Vec<libc::c_double>,
+    scale: Option<Vec<f64>>,
+    pending: Option<Vec<f64>>,
+}
+
+#[cfg(feature = "serde1")]
+impl Serialize for CobylaContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ctx = &self.ctx;
+        CobylaContextData {
+            n: ctx.n,
+            m: ctx.m,
+            iprint: ctx.iprint,
+            maxfun: ctx.maxfun,
+            nfvals: ctx.nfvals,
+            rhobeg: ctx.rhobeg,
+            rhoend: ctx.rhoend,
+            parmu: ctx.parmu,
+            parsig: ctx.parsig,
+            prerec: ctx.prerec,
+            prerem: ctx.prerem,
+            rho: ctx.rho,
+            f: ctx.f,
+            ibrnch: ctx.ibrnch,
+            iflag: ctx.iflag,
+            ifull: ctx.ifull,
+            jdrop: ctx.jdrop,
+            status: ctx.status,
+            custom_simplex: ctx.custom_simplex,
+            rho_reduction: ctx.rho_reduction,
+            parmu_init: ctx.parmu_init,
+            reject_trial: ctx.reject_trial,
+            trial_step: ctx.trial_step,
+            trial_prerem: ctx.trial_prerem,
+            trial_trured: ctx.trial_trured,
+            trial_accepted: ctx.trial_accepted,
+            trial_rejected: ctx.trial_rejected,
+            iact: self.iact.clone(),
+            work: self.work.clone(),
+            scale: self.scale.clone(),
+            pending: self.pending.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde1")]
+impl<'de> Deserialize<'de> for CobylaContext {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = CobylaContextData::deserialize(deserializer)?;
+        let (n, m) = (data.n, data.m);
+        if n < 1
+            || m < 0
+            || work_sizes(n as usize, m as usize) != Some((data.iact.len(), data.work.len()))
+            || data.scale.as_ref().is_some_and(|s| s.len() != n as usize)
+            || data.pending.as_ref().is_some_and(|x| x.len() != n as usize)
+        {
+            return Err(serde::de::Error::custom(
+                "inconsistent COBYLA context working arrays sizes",
+            ));
+        }
+        let ctx = cobyla_context_t {
+            n,
+            m,
+            iprint: data.iprint,
+            maxfun: data.maxfun,
+            nfvals: data.nfvals,
+            rhobeg: data.rhobeg,
+            rhoend: data.rhoend,
+            parmu: data.parmu,
+            parsig: data.parsig,
+            prerec: data.prerec,
+            prerem: data.prerem,
+            rho: data.rho,
+            f: data.f,
+            ibrnch: data.ibrnch,
+            iflag: data.iflag,
+            ifull: data.ifull,
+            jdrop: data.jdrop,
+            status: data.status,
+            custom_simplex: data.custom_simplex,
+            rho_reduction: data.rho_reduction,
+            parmu_init: data.parmu_init,
+            reject_trial: data.reject_trial,
+            trial_step: data.trial_step,
+            trial_prerem: data.trial_prerem,
+            trial_trured: data.trial_trured,
+            trial_accepted: data.trial_accepted,
+            trial_rejected: data.trial_rejected,
+            ..cobyla_context_t::default()
+        };
+        let mut context = CobylaContext {
+            ctx: Box::new(ctx),
+            iact: data.iact,
+            work: data.work,
+            scale: data.scale,
+            pending: data.pending,
+        };
+        context.link();
+        Ok(context)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::FailStatus;
+use crate::cobyla_problem::CostEvaluationError;
+
+/// Error of the COBYLA solver.
+///
+/// The solver returns its errors (invalid settings, cost function failures) as
+/// `CobylaError` values wrapped in argmin `Error`, they can be retrieved with
+/// `downcast_ref` to handle specific failure modes. Failed terminations
+/// (see [crate::CobylaState::termination_status]) are converted with
+/// [crate::CobylaState::termination_error].
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, Executor};
+/// use cobyla_argmin::{CobylaError, CobylaSolver};
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder()
+///     .initial_param(vec![1., 1.])
+///     .rho_reduction(1.5)
+///     .build();
+/// let err = Executor::new(ParaboloidProblem, solver).run().err().unwrap();
+/// assert!(matches!(
+///     err.downcast_ref::<CobylaError>(),
+///     Some(CobylaError::InvalidParameter(_))
+/// ));
+/// ```
+#[derive(Debug)]
+pub enum CobylaError {
+    /// Generic failure (see [FailStatus::Failure])
+    Failure,
+    /// Invalid arguments given to COBYLA (see [FailStatus::InvalidArgs])
+    InvalidArgs,
+    /// Memory allocation failure (see [FailStatus::OutOfMemory])
+    OutOfMemory,
+    /// Optimization stopped by rounding errors (see [FailStatus::RoundoffLimited])
+    RoundoffLimited,
+    /// Optimization interrupted (see [FailStatus::ForcedStop])
+    ForcedStop,
+    /// Unexpected error such as a cost function panic (see [FailStatus::UnexpectedError])
+    UnexpectedError,
+    /// Non finite objective value (see [FailStatus::NonFiniteCost])
+    NonFiniteCost,
+    /// Invalid solver setting or inconsistent cost function output
+    InvalidParameter(String),
+    /// Cost function evaluation failure
+    CostEvaluation(CostEvaluationError),
+}
+
+impl std::fmt::Display for CobylaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CobylaError::Failure => write!(f, "COBYLA failed"),
+            CobylaError::InvalidArgs => write!(f, "COBYLA invalid arguments"),
+            CobylaError::OutOfMemory => write!(f, "COBYLA out of memory"),
+            CobylaError::RoundoffLimited => write!(f, "COBYLA stopped by rounding errors"),
+            CobylaError::ForcedStop => write!(f, "COBYLA stopped by user"),
+            CobylaError::UnexpectedError => write!(f, "COBYLA unexpected error"),
+            CobylaError::NonFiniteCost => write!(f, "COBYLA non finite objective value"),
+            CobylaError::InvalidParameter(text) => write!(f, "invalid parameter: {text}"),
+            CobylaError::CostEvaluation(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CobylaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CobylaError::CostEvaluation(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FailStatus> for CobylaError {
+    fn from(status: FailStatus) -> Self {
+        match status {
+            FailStatus::Failure => CobylaError::Failure,
+            FailStatus::InvalidArgs => CobylaError::InvalidArgs,
+            FailStatus::OutOfMemory => CobylaError::OutOfMemory,
+            FailStatus::RoundoffLimited => CobylaError::RoundoffLimited,
+            FailStatus::ForcedStop => CobylaError::ForcedStop,
+            FailStatus::UnexpectedError => CobylaError::UnexpectedError,
+            FailStatus::NonFiniteCost => CobylaError::NonFiniteCost,
+        }
+    }
+}
+
+impl From<CostEvaluationError> for CobylaError {
+    fn from(err: CostEvaluationError) -> Self {
+        CobylaError::CostEvaluation(err)
+    }
+}
+
+/// Invalid setting reported by [crate::CobylaSolverBuilder::validate]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Description of the problem, the one of [CobylaError::InvalidParameter] when running
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid parameter: {}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<argmin::core::Error> for ConfigError {
+    fn from(err: argmin::core::Error) -> Self {
+        let message = match err.downcast_ref::<CobylaError>() {
+            Some(CobylaError::InvalidParameter(text)) => text.clone(),
+            _ => err.to_string(),
+        };
+        ConfigError { message }
+    }
+}
+
+/// Returns an argmin error wrapping [CobylaError::InvalidParameter]
+pub(crate) fn invalid_parameter(text: impl Into<String>) -> argmin::core::Error {
+    argmin::core::Error::new(CobylaError::InvalidParameter(text.into()))
+}
@@ -0,0 +1,377 @@
+use crate::cobyla_error::invalid_parameter;
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_problem::CobylaOutput;
+use crate::cobyla_state::CobylaState;
+
+use argmin::argmin_error;
+use argmin::core::{CostFunction, Error};
+
+/// Finite difference scheme used to estimate derivatives at the best point
+/// (see [CobylaState::estimate_gradient])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FiniteDifference {
+    /// `(f(x + h e_i) - f(x)) / h`: one evaluation per component, the best cost function
+    /// value of the state being reused for `f(x)`
+    Forward,
+    /// `(f(x + h e_i) - f(x - h e_i)) / 2h`: two evaluations per component, more accurate
+    #[default]
+    Central,
+    /// Richardson extrapolation to `h -> 0` of central differences computed with
+    /// decreasing steps (see [GradientEstimator]): two evaluations per component and step,
+    /// the most accurate for smooth functions
+    Richardson,
+}
+
+/// Finite difference estimator of derivatives at the best point
+/// (see [GradientEstimator::gradient] and [GradientEstimator::jacobian]).
+///
+/// With [FiniteDifference::Richardson], central differences are computed with the steps
+/// `h, h/r, ..., h/r^(levels-1)` and extrapolated to `h -> 0`, each level cancelling
+/// the next even power of `h` of the truncation error. The largest step `h` should then
+/// be chosen much larger than with plain differences (eg `1e-2` relative to the variable
+/// magnitudes) so that rounding errors stay negligible.
+///
+/// ```rust
+/// # use cobyla_argmin::{FiniteDifference, GradientEstimator};
+/// let estimator = GradientEstimator {
+///     scheme: FiniteDifference::Richardson,
+///     step: 1e-2,
+///     ..GradientEstimator::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientEstimator {
+    /// Finite difference scheme
+    pub scheme: FiniteDifference,
+    /// Step `h`, the largest one with [FiniteDifference::Richardson]
+    pub step: f64,
+    /// Number of steps extrapolated with [FiniteDifference::Richardson]
+    pub levels: usize,
+    /// Ratio `r` between successive steps of [FiniteDifference::Richardson]
+    pub step_ratio: f64,
+}
+
+impl Default for GradientEstimator {
+    fn default() -> Self {
+        GradientEstimator {
+            scheme: FiniteDifference::default(),
+            step: 1e-6,
+            levels: 4,
+            step_ratio: 2.,
+        }
+    }
+}
+
+impl GradientEstimator {
+    /// Estimates the gradient of the objective function at the best parameter vector of
+    /// the given state.
+    ///
+    /// This is a post-processing utility (for sensitivity analysis or reporting),
+    /// it is not used by the solver.
+    ///
+    /// ```
+    /// use argmin::core::{CostFunction, Error, Executor};
+    /// use cobyla_argmin::{CobylaSolver, FiniteDifference, GradientEstimator};
+    ///
+    /// struct ParaboloidProblem;
+    /// impl CostFunction for ParaboloidProblem {
+    ///     type Param = Vec<f64>;
+    ///     type Output = Vec<f64>;
+    ///
+    ///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+    ///         Ok(vec![(x[0] + 1.).exp() + x[1].powf(2.), x[0]])
+    ///     }
+    /// }
+    ///
+    /// let solver = CobylaSolver::builder().initial_param(vec![1., 1.]).iprint(0).build();
+    /// let res = Executor::new(ParaboloidProblem, solver)
+    ///     .configure(|state| state.max_iters(100))
+    ///     .run()
+    ///     .unwrap();
+    /// let estimator = GradientEstimator {
+    ///     scheme: FiniteDifference::Richardson,
+    ///     step: 1e-2,
+    ///     ..GradientEstimator::default()
+    /// };
+    /// let grad = estimator.gradient(res.state(), &ParaboloidProblem).unwrap();
+    /// let x0 = res.state().best_param.as_ref().unwrap()[0];
+    /// assert!((grad[0] - (x0 + 1.).exp()).abs() < 1e-9);
+    /// ```
+    pub fn gradient<O, P, C>(
+        &self,
+        state: &CobylaState<P, C>,
+        problem: &O,
+    ) -> Result<Vec<f64>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+        P: CobylaParam,
+    {
+        state
+            .finite_differences(problem, self, 1)
+            .map(|mut jac| jac.swap_remove(0))
+    }
+
+    /// Estimates the jacobian of the cost function output (objective followed by
+    /// constraints) at the best parameter vector of the given state: row `k` is the
+    /// gradient of the `k`-th output value.
+    pub fn jacobian<O, P, C>(
+        &self,
+        state: &CobylaState<P, C>,
+        problem: &O,
+    ) -> Result<Vec<Vec<f64>>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+        P: CobylaParam,
+    {
+        state.finite_differences(problem, self, usize::MAX)
+    }
+
+    /// Returns the estimator of the given scheme and step, other settings being the
+    /// default ones
+    fn with_step(scheme: FiniteDifference, step: f64) -> Self {
+        GradientEstimator {
+            scheme,
+            step,
+            ..GradientEstimator::default()
+        }
+    }
+
+    /// Checks the estimator settings
+    fn check(&self) -> Result<(), Error> {
+        let h = self.step;
+        if !(h > 0. && h.is_finite()) {
+            return Err(invalid_parameter(format!(
+                "finite difference step should be finite and strictly positive, got {h}"
+            )));
+        }
+        if self.scheme == FiniteDifference::Richardson {
+            if self.levels == 0 {
+                return Err(invalid_parameter(
+                    "number of Richardson extrapolation levels should be strictly positive",
+                ));
+            }
+            let r = self.step_ratio;
+            if !(r > 1. && r.is_finite()) {
+                return Err(invalid_parameter(format!(
+                    "Richardson step ratio should be finite and greater than 1, got {r}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Constraint analysis at the best point (see [CobylaState::constraint_info])
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintInfo {
+    /// Constraint value `c(x)` (feasible when `c(x) >= 0`)
+    pub value: f64,
+    /// Whether the constraint is active ie `|c(x)|` is within the given tolerance
+    pub active: bool,
+    /// Finite difference estimate of the constraint gradient
+    pub gradient: Vec<f64>,
+}
+
+impl<P, C> CobylaState<P, C>
+where
+    P: CobylaParam,
+{
+    /// Estimates the gradient of the objective function at the best parameter vector
+    /// by finite differences with step `h`, see [GradientEstimator] to tune the
+    /// Richardson extrapolation.
+    ///
+    /// This is a post-processing utility (for sensitivity analysis or reporting),
+    /// it is not used by the solver.
+    ///
+    /// ```
+    /// use argmin::core::{CostFunction, Error, Executor};
+    /// use cobyla_argmin::{CobylaSolver, FiniteDifference};
+    ///
+    /// struct ParaboloidProblem;
+    /// impl CostFunction for ParaboloidProblem {
+    ///     type Param = Vec<f64>;
+    ///     type Output = Vec<f64>;
+    ///
+    ///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+    ///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+    ///     }
+    /// }
+    ///
+    /// let solver = CobylaSolver::builder().initial_param(vec![1., 1.]).iprint(0).build();
+    /// let res = Executor::new(ParaboloidProblem, solver)
+    ///     .configure(|state| state.max_iters(100))
+    ///     .run()
+    ///     .unwrap();
+    /// let grad = res
+    ///     .state()
+    ///     .estimate_gradient(&ParaboloidProblem, 1e-6, FiniteDifference::Central)
+    ///     .unwrap();
+    /// assert!((grad[0] - 20.).abs() < 1e-2);
+    /// ```
+    pub fn estimate_gradient<O>(
+        &self,
+        problem: &O,
+        h: f64,
+        scheme: FiniteDifference,
+    ) -> Result<Vec<f64>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+    {
+        GradientEstimator::with_step(scheme, h).gradient(self, problem)
+    }
+
+    /// Estimates the jacobian of the cost function output (objective followed by
+    /// constraints) at the best parameter vector by finite differences with step `h`:
+    /// row `k` is the gradient of the `k`-th output value.
+    pub fn estimate_jacobian<O>(
+        &self,
+        problem: &O,
+        h: f64,
+        scheme: FiniteDifference,
+    ) -> Result<Vec<Vec<f64>>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+    {
+        GradientEstimator::with_step(scheme, h).jacobian(self, problem)
+    }
+
+    /// Returns the analysis of the constraints computed by the cost function at the best
+    /// parameter vector: value, activity (`|c(x)| <= tol`) and finite difference estimate
+    /// of the gradient with step `h`, in the order of the cost function output.
+    ///
+    /// Bounds and equality constraints declarations of the solver are not taken into
+    /// account: constraints are the values returned by the cost function after the objective.
+    ///
+    /// ```
+    /// use argmin::core::{CostFunction, Error, Executor};
+    /// use cobyla_argmin::{CobylaSolver, FiniteDifference};
+    ///
+    /// struct ParaboloidProblem;
+    /// impl CostFunction for ParaboloidProblem {
+    ///     type Param = Vec<f64>;
+    ///     type Output = Vec<f64>;
+    ///
+    ///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+    ///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0], 1. - x[1]])
+    ///     }
+    /// }
+    ///
+    /// let solver = CobylaSolver::builder().initial_param(vec![1., 1.]).iprint(0).build();
+    /// let res = Executor::new(ParaboloidProblem, solver)
+    ///     .configure(|state| state.max_iters(100))
+    ///     .run()
+    ///     .unwrap();
+    /// let info = res
+    ///     .state()
+    ///     .constraint_info(&ParaboloidProblem, 1e-4, 1e-6, FiniteDifference::Central)
+    ///     .unwrap();
+    /// assert!(info[0].active && !info[1].active);
+    /// ```
+    pub fn constraint_info<O>(
+        &self,
+        problem: &O,
+        tol: f64,
+        h: f64,
+        scheme: FiniteDifference,
+    ) -> Result<Vec<ConstraintInfo>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+    {
+        let jac = self.estimate_jacobian(problem, h, scheme)?;
+        let fx = self.best_cost.as_deref().unwrap_or_default();
+        Ok(fx
+            .iter()
+            .skip(1)
+            .zip(jac.into_iter().skip(1))
+            .map(|(&value, gradient)| ConstraintInfo {
+                value,
+                active: value.abs() <= tol,
+                gradient,
+            })
+            .collect())
+    }
+
+    /// Returns the gradients of the first `nrows` cost function output values
+    fn finite_differences<O>(
+        &self,
+        problem: &O,
+        estimator: &GradientEstimator,
+        nrows: usize,
+    ) -> Result<Vec<Vec<f64>>, Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput,
+    {
+        estimator.check()?;
+        let (Some(x), Some(fx)) = (self.best_param.as_ref(), self.best_cost.as_ref()) else {
+            return Err(argmin_error!(
+                NotInitialized,
+                "best parameter vector is not available"
+            ));
+        };
+        let x = x.components().into_owned();
+        let nrows = nrows.min(fx.len());
+        let eval = |i: usize, h: f64| -> Result<Vec<f64>, Error> {
+            let mut xi = x.clone();
+            xi[i] += h;
+            let (fxi, _) = problem.cost(&P::from_components(xi))?.into_parts();
+            if fxi.len() < nrows {
+                return Err(invalid_parameter(format!(
+                    "cost function output size ({}) should be equal to the one at best point ({})",
+                    fxi.len(),
+                    fx.len()
+                )));
+            }
+            Ok(fxi)
+        };
+        let central = |i: usize, h: f64| -> Result<Vec<f64>, Error> {
+            let (fp, fm) = (eval(i, h)?, eval(i, -h)?);
+            Ok((0..nrows).map(|k| (fp[k] - fm[k]) / (2. * h)).collect())
+        };
+
+        let h = estimator.step;
+        let mut jac = vec![vec![0.; x.len()]; nrows];
+        for i in 0..x.len() {
+            let di = match estimator.scheme {
+                FiniteDifference::Forward => {
+                    let fp = eval(i, h)?;
+                    (0..nrows).map(|k| (fp[k] - fx[k]) / h).collect()
+                }
+                FiniteDifference::Central => central(i, h)?,
+                FiniteDifference::Richardson => {
+                    // Neville tableau: row l holds the central differences with step
+                    // h / r^l followed by their successive extrapolations
+                    let r2 = estimator.step_ratio * estimator.step_ratio;
+                    let mut prev: Vec<Vec<f64>> = Vec::new();
+                    let mut hl = h;
+                    for _ in 0..estimator.levels {
+                        let mut cur = vec![central(i, hl)?];
+                        let mut factor = 1.;
+                        for dp in &prev {
+                            factor *= r2;
+                            let d = cur.last().unwrap();
+                            let extrapolated = d
+                                .iter()
+                                .zip(dp)
+                                .map(|(d, dp)| d + (d - dp) / (factor - 1.))
+                                .collect();
+                            cur.push(extrapolated);
+                        }
+                        prev = cur;
+                        hl /= estimator.step_ratio;
+                    }
+                    prev.pop().unwrap()
+                }
+            };
+            for (k, row) in jac.iter_mut().enumerate() {
+                row[i] = di[k];
+            }
+        }
+        Ok(jac)
+    }
+}
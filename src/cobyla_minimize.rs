@@ -0,0 +1,175 @@
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_problem::CobylaOutput;
+use crate::cobyla_solver::CobylaSolver;
+use crate::cobyla_state::{CobylaState, MAX_ITERS};
+use crate::{FailStatus, RhoBeg, Sense, StopTols, SuccessStatus, Verbosity};
+
+use argmin::argmin_error;
+use argmin::core::{CostFunction, Error, Executor, SendAlias, State, SyncAlias};
+use web_time::{Duration, Instant};
+
+/// Options of [minimize]: a subset of [crate::CobylaSolverBuilder] settings covering
+/// the common cases, unset options keeping the solver defaults.
+///
+/// ```rust
+/// # use cobyla_argmin::{MinimizeOptions, RhoBeg, StopTols};
+/// let options = MinimizeOptions {
+///     rhobeg: Some(RhoBeg::All(0.1)),
+///     stop_tols: StopTols { ftol_rel: 1e-6, ..StopTols::default() },
+///     max_iters: 500,
+///     ..MinimizeOptions::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinimizeOptions {
+    /// Termination criteria
+    pub stop_tols: StopTols,
+    /// Initial change of x
+    pub rhobeg: Option<RhoBeg>,
+    /// Final value of the trust region radius
+    pub rhoend: Option<f64>,
+    /// Lower and upper bounds of x components
+    pub bounds: Option<(Vec<f64>, Vec<f64>)>,
+    /// Maximum number of iterations (default: [MAX_ITERS])
+    pub max_iters: u64,
+    /// Budget of cost function evaluations
+    pub max_fun_evals: Option<u64>,
+    /// Wall-clock time budget
+    pub max_time: Option<Duration>,
+    /// Objective value target of feasible points
+    pub stopval: Option<f64>,
+    /// Optimization sense of the objective (default: minimization)
+    pub sense: Sense,
+    /// Level of printing of the algorithm
+    pub verbosity: Verbosity,
+}
+
+impl Default for MinimizeOptions {
+    fn default() -> Self {
+        MinimizeOptions {
+            stop_tols: StopTols::default(),
+            rhobeg: None,
+            rhoend: None,
+            bounds: None,
+            max_iters: MAX_ITERS,
+            max_fun_evals: None,
+            max_time: None,
+            stopval: None,
+            sense: Sense::default(),
+            verbosity: Verbosity::default(),
+        }
+    }
+}
+
+/// Result of [minimize]
+#[derive(Debug, Clone)]
+pub struct OptimizationReport<P = Vec<f64>> {
+    /// Best parameter vector
+    pub best_param: P,
+    /// Objective value at best parameter vector
+    pub best_objective: f64,
+    /// Constraint values computed by the cost function at best parameter vector
+    pub constraints: Vec<f64>,
+    /// Maximum constraint violation at best parameter vector
+    /// (see [CobylaState::best_max_constraint_violation])
+    pub max_violation: f64,
+    /// Termination status (see [CobylaState::termination_status])
+    pub status: Option<Result<SuccessStatus, FailStatus>>,
+    /// Number of iterations
+    pub iterations: u64,
+    /// Number of cost function evaluations
+    pub func_evals: u64,
+    /// Wall-clock duration of the optimization
+    pub elapsed: Duration,
+}
+
+/// Minimizes the objective computed by the cost function of the given problem subject to
+/// the constraints `c(x) >= 0` it computes as well, starting from `x0`.
+///
+/// This is a one-call shortcut for the common case, use [CobylaSolver::builder] with an
+/// argmin `Executor` to access all the solver settings and the final state.
+///
+/// ```
+/// use argmin::core::{CostFunction, Error};
+/// use cobyla_argmin::{MinimizeOptions, Verbosity, minimize};
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     // Minimize 10*(x0+1)^2 + x1^2 subject to x0 >= 0
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+///     }
+/// }
+///
+/// let options = MinimizeOptions {
+///     verbosity: Verbosity::Silent,
+///     ..MinimizeOptions::default()
+/// };
+/// let report = minimize(ParaboloidProblem, vec![1., 1.], options).unwrap();
+/// assert!((report.best_objective - 10.).abs() < 1e-2);
+/// assert!(report.best_param[0].abs() < 1e-2);
+/// assert!(report.max_violation < 1e-6);
+/// ```
+pub fn minimize<O, P>(
+    problem: O,
+    x0: P,
+    options: MinimizeOptions,
+) -> Result<OptimizationReport<P>, Error>
+where
+    O: CostFunction<Param = P> + SyncAlias,
+    O::Output: CobylaOutput + SendAlias,
+    <O::Output as CobylaOutput>::Context: Clone,
+    P: CobylaParam + SyncAlias,
+{
+    let mut builder = CobylaSolver::builder()
+        .initial_param(x0)
+        .stop_tols(options.stop_tols)
+        .sense(options.sense)
+        .verbosity(options.verbosity);
+    if let Some(rhobeg) = options.rhobeg {
+        builder = builder.rhobeg(rhobeg);
+    }
+    if let Some(rhoend) = options.rhoend {
+        builder = builder.rhoend(rhoend);
+    }
+    if let Some((lower, upper)) = options.bounds {
+        builder = builder.with_bounds(lower, upper);
+    }
+    if let Some(max_fun_evals) = options.max_fun_evals {
+        builder = builder.max_fun_evals(max_fun_evals);
+    }
+    if let Some(max_time) = options.max_time {
+        builder = builder.max_time(max_time);
+    }
+    if let Some(stopval) = options.stopval {
+        builder = builder.stopval(stopval);
+    }
+
+    let start = Instant::now();
+    let res = Executor::new(problem, builder.build())
+        .configure(|state: CobylaState<P, _>| state.max_iters(options.max_iters))
+        .run()?;
+    let elapsed = start.elapsed();
+
+    let mut state = res.state;
+    let max_violation = state.best_max_constraint_violation();
+    let Some(best_param) = state.best_param.take() else {
+        return Err(argmin_error!(
+            NotInitialized,
+            "best parameter vector is not available"
+        ));
+    };
+    Ok(OptimizationReport {
+        best_param,
+        best_objective: state.get_best_cost(),
+        constraints: state.best_constraints().to_vec(),
+        max_violation,
+        status: state.termination_status(),
+        iterations: state.get_iter(),
+        func_evals: state.func_evals(),
+        elapsed,
+    })
+}
@@ -0,0 +1,260 @@
+use crate::Sense;
+use crate::cobyla_error::invalid_parameter;
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_solver::CobylaSolverBuilder;
+use crate::cobyla_state::{CobylaState, MAX_ITERS};
+
+use argmin::core::{CostFunction, Error, Executor, State};
+use rand::Rng;
+
+/// Multi-start driver running [crate::CobylaSolver] from several initial points and
+/// selecting the best result.
+///
+/// Each start is run with a solver built from the given builder, the initial guess being
+/// replaced by the start point. The winner is the best feasible result (maximum constraint
+/// violation less than the feasibility tolerance) with the lowest objective value or, when
+/// no result is feasible, the one with the lowest constraint violation.
+///
+/// ```
+/// use argmin::core::{CostFunction, Error};
+/// use cobyla_argmin::{CobylaSolver, MultiStartCobyla};
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+///     }
+/// }
+///
+/// let multistart = MultiStartCobyla::new(CobylaSolver::builder().iprint(0)).max_iters(100);
+/// let res = multistart
+///     .run(&ParaboloidProblem, vec![vec![1., 1.], vec![2., -1.]])
+///     .unwrap();
+/// assert_eq!(res.results.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiStartCobyla {
+    builder: CobylaSolverBuilder,
+    max_iters: u64,
+    feasibility_tol: f64,
+}
+
+/// Results of a multi-start optimization
+#[derive(Debug, Clone)]
+pub struct MultiStartResult<P = Vec<f64>> {
+    /// Index of the best start
+    pub best_index: usize,
+    /// Final states of all starts in the order of the start points
+    pub results: Vec<CobylaState<P>>,
+}
+
+impl<P> MultiStartResult<P> {
+    /// Returns the final state of the best start
+    pub fn best(&self) -> &CobylaState<P> {
+        &self.results[self.best_index]
+    }
+}
+
+impl MultiStartCobyla {
+    /// Constructor given the builder used to configure the solver of each start
+    pub fn new(builder: CobylaSolverBuilder) -> Self {
+        MultiStartCobyla {
+            builder,
+            max_iters: MAX_ITERS,
+            feasibility_tol: 1e-6,
+        }
+    }
+
+    /// Set maximum number of iterations of each start (default: [MAX_ITERS])
+    #[must_use]
+    pub fn max_iters(mut self, max_iters: u64) -> Self {
+        self.max_iters = max_iters;
+        self
+    }
+
+    /// Set maximum constraint violation for a result to be considered feasible (default: 1e-6)
+    #[must_use]
+    pub fn feasibility_tol(mut self, feasibility_tol: f64) -> Self {
+        self.feasibility_tol = feasibility_tol;
+        self
+    }
+
+    /// Runs the optimization from each of the given start points
+    pub fn run<O, P>(&self, problem: &O, starts: Vec<P>) -> Result<MultiStartResult<P>, Error>
+    where
+        O: CostFunction<Param = P, Output = Vec<f64>>,
+        P: CobylaParam,
+    {
+        if starts.is_empty() {
+            return Err(invalid_parameter("at least one start point is required"));
+        }
+        let results = starts
+            .into_iter()
+            .map(|x0| self.run_one(problem, x0))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(self.select(results))
+    }
+
+    /// Runs the optimization from `n` start points sampled uniformly within the given
+    /// (finite) bounds using the given random number generator
+    pub fn run_random<O, P, R>(
+        &self,
+        problem: &O,
+        n: usize,
+        lower: &[f64],
+        upper: &[f64],
+        rng: &mut R,
+    ) -> Result<MultiStartResult<P>, Error>
+    where
+        O: CostFunction<Param = P, Output = Vec<f64>>,
+        P: CobylaParam,
+        R: Rng,
+    {
+        let starts = sample_starts(n, lower, upper, rng)?;
+        self.run(problem, starts)
+    }
+
+    /// Runs the optimization from the given start point
+    pub(crate) fn run_one<O, P>(&self, problem: &O, x0: P) -> Result<CobylaState<P>, Error>
+    where
+        O: CostFunction<Param = P, Output = Vec<f64>>,
+        P: CobylaParam,
+    {
+        let solver = self.builder.clone().initial_param(x0).build();
+        let res = Executor::new(SharedProblem(problem), solver)
+            .configure(|state| state.max_iters(self.max_iters))
+            .run()?;
+        Ok(res.state)
+    }
+
+    /// Selects the best result: feasible first, then lowest objective value
+    /// (resp. lowest constraint violation when infeasible), ties being broken
+    /// by the order of the start points
+    pub(crate) fn select<P: Clone>(&self, results: Vec<CobylaState<P>>) -> MultiStartResult<P> {
+        let key = |state: &CobylaState<P>| {
+            let violation = state.best_max_constraint_violation();
+            if violation <= self.feasibility_tol {
+                match self.builder.get_sense() {
+                    Sense::Minimize => (0, state.get_best_cost()),
+                    Sense::Maximize => (0, -state.get_best_cost()),
+                }
+            } else {
+                (1, violation)
+            }
+        };
+        let mut best_index = 0;
+        for (i, state) in results.iter().enumerate().skip(1) {
+            let (best_rank, best_value) = key(&results[best_index]);
+            let (rank, value) = key(state);
+            if rank < best_rank || (rank == best_rank && value.total_cmp(&best_value).is_lt()) {
+                best_index = i;
+            }
+        }
+        MultiStartResult {
+            best_index,
+            results,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl MultiStartCobyla {
+    /// Runs the optimization from each of the given start points in parallel.
+    ///
+    /// Each start has its own solver and state, results (and hence the selection of
+    /// the best one) are the same as with [MultiStartCobyla::run].
+    pub fn par_run<O, P>(&self, problem: &O, starts: Vec<P>) -> Result<MultiStartResult<P>, Error>
+    where
+        O: CostFunction<Param = P, Output = Vec<f64>> + Sync,
+        P: CobylaParam + Send,
+    {
+        use rayon::prelude::*;
+
+        if starts.is_empty() {
+            return Err(invalid_parameter("at least one start point is required"));
+        }
+        let results = starts
+            .into_par_iter()
+            .map(|x0| self.run_one(problem, x0))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(self.select(results))
+    }
+
+    /// Runs the optimization in parallel from `n` start points sampled uniformly within
+    /// the given (finite) bounds using the given random number generator
+    pub fn par_run_random<O, P, R>(
+        &self,
+        problem: &O,
+        n: usize,
+        lower: &[f64],
+        upper: &[f64],
+        rng: &mut R,
+    ) -> Result<MultiStartResult<P>, Error>
+    where
+        O: CostFunction<Param = P, Output = Vec<f64>> + Sync,
+        P: CobylaParam + Send,
+        R: Rng,
+    {
+        let starts = sample_starts(n, lower, upper, rng)?;
+        self.par_run(problem, starts)
+    }
+}
+
+/// Samples `n` points uniformly within the given bounds
+pub(crate) fn sample_starts<P, R>(
+    n: usize,
+    lower: &[f64],
+    upper: &[f64],
+    rng: &mut R,
+) -> Result<Vec<P>, Error>
+where
+    P: CobylaParam,
+    R: Rng,
+{
+    if lower.len() != upper.len() {
+        return Err(invalid_parameter(format!(
+            "lower and upper bounds sizes should be equal, got {} and {}",
+            lower.len(),
+            upper.len()
+        )));
+    }
+    if let Some(dim) = P::fixed_dim().filter(|&dim| dim != lower.len()) {
+        return Err(invalid_parameter(format!(
+            "sampling bounds sizes ({}) should be equal to parameter dimension ({dim})",
+            lower.len()
+        )));
+    }
+    if let Some(i) = (0..lower.len())
+        .find(|&i| !lower[i].is_finite() || !upper[i].is_finite() || lower[i] > upper[i])
+    {
+        return Err(invalid_parameter(format!(
+            "sampling bounds of x[{i}] should be finite with lower <= upper, got [{}, {}]",
+            lower[i], upper[i]
+        )));
+    }
+    Ok((0..n)
+        .map(|_| {
+            let x = lower
+                .iter()
+                .zip(upper)
+                .map(|(&lo, &up)| lo + (up - lo) * rng.random::<f64>())
+                .collect();
+            P::from_components(x)
+        })
+        .collect())
+}
+
+/// Problem borrowed by the executor of each start
+struct SharedProblem<'a, O>(&'a O);
+
+impl<O: CostFunction> CostFunction for SharedProblem<'_, O> {
+    type Param = O::Param;
+    type Output = O::Output;
+
+    fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+        self.0.cost(x)
+    }
+}
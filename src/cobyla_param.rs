@@ -0,0 +1,90 @@
+use std::borrow::Cow;
+
+/// Parameter vector types which can be used with [crate::CobylaSolver].
+///
+/// COBYLA works on contiguous `f64` components: a parameter type only has to give
+/// access to its components and to be built back from them. Implementations are
+/// provided for `Vec<f64>`, `[f64; N]` and, with the `ndarray` (resp. `nalgebra`) feature,
+/// for `ndarray::Array1<f64>` (resp. `nalgebra::DVector<f64>`).
+///
+/// The best parameter of the state is built back with [CobylaParam::from_components]
+/// from the exact components of an evaluated point: it is equal to the parameter
+/// given to the cost function at that point, even when COBYLA is run on scaled
+/// components (see [crate::CobylaSolverBuilder::rhobeg]).
+pub trait CobylaParam: Clone {
+    /// Returns the components of the parameter vector, borrowed when they are
+    /// stored contiguously
+    fn components(&self) -> Cow<'_, [f64]>;
+
+    /// Builds the parameter vector from its components
+    fn from_components(x: Vec<f64>) -> Self;
+
+    /// Converts the parameter vector into its components, copied by default
+    fn into_components(self) -> Vec<f64> {
+        self.components().into_owned()
+    }
+
+    /// Returns the dimension of the parameter type when it is fixed, None by default
+    fn fixed_dim() -> Option<usize> {
+        None
+    }
+}
+
+impl CobylaParam for Vec<f64> {
+    fn components(&self) -> Cow<'_, [f64]> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_components(x: Vec<f64>) -> Self {
+        x
+    }
+
+    fn into_components(self) -> Vec<f64> {
+        self
+    }
+}
+
+impl<const N: usize> CobylaParam for [f64; N] {
+    fn components(&self) -> Cow<'_, [f64]> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_components(x: Vec<f64>) -> Self {
+        let dim = x.len();
+        x.try_into()
+            .unwrap_or_else(|_| panic!("x dimension ({dim}) should be equal to array size ({N})"))
+    }
+
+    fn fixed_dim() -> Option<usize> {
+        Some(N)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl CobylaParam for ndarray::Array1<f64> {
+    fn components(&self) -> Cow<'_, [f64]> {
+        match self.as_slice() {
+            Some(x) => Cow::Borrowed(x),
+            None => Cow::Owned(self.to_vec()),
+        }
+    }
+
+    fn from_components(x: Vec<f64>) -> Self {
+        ndarray::Array1::from_vec(x)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl CobylaParam for nalgebra::DVector<f64> {
+    fn components(&self) -> Cow<'_, [f64]> {
+        Cow::Borrowed(self.as_slice())
+    }
+
+    fn from_components(x: Vec<f64>) -> Self {
+        nalgebra::DVector::from_vec(x)
+    }
+
+    fn into_components(self) -> Vec<f64> {
+        self.data.into()
+    }
+}
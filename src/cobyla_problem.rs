@@ -0,0 +1,376 @@
+use argmin::core::{CostFunction, Error};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Optimization problem defined by an objective function and inequality constraints.
+///
+/// This is an alternative to implementing [CostFunction] with a `Vec<f64>` output made of
+/// the objective value followed by the constraint values: the problem has to be wrapped
+/// in [Constrained] to be given to the argmin executor which assembles this output.
+///
+/// ```
+/// use argmin::core::{Error, Executor};
+/// use cobyla_argmin::{CobylaSolver, Constrained, ConstrainedProblem};
+///
+/// struct ParaboloidProblem;
+/// impl ConstrainedProblem for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///
+///     // Minimize 10*(x0+1)^2 + x1^2
+///     fn objective(&self, x: &Self::Param) -> Result<f64, Error> {
+///         Ok(10. * (x[0] + 1.).powf(2.) + x[1].powf(2.))
+///     }
+///
+///     // subject to x0 >= 0
+///     fn constraints(&self, x: &Self::Param) -> Result<Vec<f64>, Error> {
+///         Ok(vec![x[0]])
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder()
+///     .initial_param(vec![1., 1.])
+///     .iprint(0)
+///     .build();
+/// let res = Executor::new(Constrained(ParaboloidProblem), solver)
+///     .configure(|state| state.max_iters(100))
+///     .run()
+///     .unwrap();
+/// ```
+pub trait ConstrainedProblem {
+    /// Type of parameter vector
+    type Param;
+
+    /// Returns the objective function value to be minimized at `x`
+    fn objective(&self, x: &Self::Param) -> Result<f64, Error>;
+
+    /// Returns the inequality constraint values `c(x)` at `x`, constraints being satisfied
+    /// when `c(x) >= 0`. Default is no constraint.
+    fn constraints(&self, _x: &Self::Param) -> Result<Vec<f64>, Error> {
+        Ok(Vec::new())
+    }
+}
+
+/// Wrapper of a [ConstrainedProblem] implementing the argmin [CostFunction]
+/// expected by [crate::CobylaSolver]
+#[derive(Debug, Clone)]
+pub struct Constrained<T>(pub T);
+
+impl<T: ConstrainedProblem> CostFunction for Constrained<T> {
+    type Param = T::Param;
+    type Output = Vec<f64>;
+
+    fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+        let mut fx = vec![self.0.objective(x)?];
+        fx.extend(self.0.constraints(x)?);
+        Ok(fx)
+    }
+}
+
+/// Wrapper of a [ConstrainedProblem] evaluating the constraints first and skipping
+/// the objective at points violating them by more than a threshold, for problems whose
+/// objective is expensive compared to their constraints.
+///
+/// At a skipped point, the objective value given to COBYLA is an estimate which makes
+/// the point worse than all the evaluated ones: the largest objective value evaluated
+/// so far plus `penalty` times the maximum constraint violation `max(0, -c_i(x))`. The
+/// objective is always evaluated while no objective value is known (ie at the initial
+/// guess). Constraints are expected to follow the `c(x) >= 0` convention of
+/// [ConstrainedProblem].
+///
+/// Estimates are seen by the solver as objective values, they appear as such in
+/// [crate::CobylaState::evaluations] for instance.
+///
+/// The largest objective value and the count of skipped objectives are kept by the
+/// wrapper across runs: when it is reused for another optimization, estimates still
+/// build on the objective values of the previous runs, create a new wrapper to start
+/// afresh.
+///
+/// ```
+/// use argmin::core::{Error, Executor, State};
+/// use cobyla_argmin::{CobylaSolver, ConstrainedProblem, Screened};
+///
+/// struct ExpensiveProblem;
+/// impl ConstrainedProblem for ExpensiveProblem {
+///     type Param = Vec<f64>;
+///
+///     // Expensive simulation
+///     fn objective(&self, x: &Self::Param) -> Result<f64, Error> {
+///         Ok((x[0] - 3.).powf(2.) + (x[1] - 3.).powf(2.))
+///     }
+///
+///     // Cheap analytic constraint: inside the unit disk
+///     fn constraints(&self, x: &Self::Param) -> Result<Vec<f64>, Error> {
+///         Ok(vec![1. - x[0].powf(2.) - x[1].powf(2.)])
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder()
+///     .initial_param(vec![0., 0.])
+///     .iprint(0)
+///     .build();
+/// let res = Executor::new(Screened::new(ExpensiveProblem, 0.5), solver)
+///     .configure(|state| state.max_iters(200))
+///     .run()
+///     .unwrap();
+/// let skipped = res.problem().problem.as_ref().unwrap().skipped_objectives();
+/// assert!(skipped > 0 && skipped < res.state().func_evals());
+/// // Minimum on the unit circle, at x = [1 / sqrt(2), 1 / sqrt(2)]
+/// assert!((res.state().get_best_cost() - (19. - 6. * 2f64.sqrt())).abs() < 1e-4);
+/// ```
+#[derive(Debug)]
+pub struct Screened<T> {
+    problem: T,
+    max_violation: f64,
+    penalty: f64,
+    worst_objective: Mutex<f64>,
+    skipped: AtomicU64,
+}
+
+impl<T> Screened<T> {
+    /// Constructor given the problem and the maximum constraint violation above which
+    /// the objective is not evaluated
+    pub fn new(problem: T, max_violation: f64) -> Self {
+        Screened {
+            problem,
+            max_violation,
+            penalty: 1.,
+            worst_objective: Mutex::new(f64::NEG_INFINITY),
+            skipped: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the factor of the maximum constraint violation added to the largest objective
+    /// value evaluated so far to estimate the objective at skipped points (default: 1)
+    #[must_use]
+    pub fn penalty(mut self, penalty: f64) -> Self {
+        self.penalty = penalty;
+        self
+    }
+
+    /// Returns the number of cost function evaluations where the objective was skipped,
+    /// counted since the wrapper was created
+    pub fn skipped_objectives(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the wrapped problem
+    pub fn inner(&self) -> &T {
+        &self.problem
+    }
+
+    /// Returns the wrapped problem, consuming the wrapper
+    pub fn into_inner(self) -> T {
+        self.problem
+    }
+}
+
+impl<T: ConstrainedProblem> CostFunction for Screened<T> {
+    type Param = T::Param;
+    type Output = Vec<f64>;
+
+    fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+        let constraints = self.problem.constraints(x)?;
+        let violation = constraints.iter().fold(0., |acc: f64, c| acc.max(-c));
+        let worst = *self
+            .worst_objective
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let objective = if violation > self.max_violation && worst.is_finite() {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            worst + self.penalty * violation
+        } else {
+            let objective = self.problem.objective(x)?;
+            let mut worst = self
+                .worst_objective
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if objective > *worst {
+                *worst = objective;
+            }
+            objective
+        };
+        let mut fx = vec![objective];
+        fx.extend(constraints);
+        Ok(fx)
+    }
+}
+
+/// Cost function output types which can be used with [crate::CobylaSolver].
+///
+/// The output is made of the objective value followed by the constraint values,
+/// optionally along with a user context. Implementations are provided for `Vec<f64>`
+/// (no context), for [Evaluation] and for `f64` and `f32` scalar outputs of
+/// unconstrained problems:
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, Executor};
+/// use cobyla_argmin::CobylaSolver;
+///
+/// struct QuadraticProblem;
+/// impl CostFunction for QuadraticProblem {
+///     type Param = Vec<f64>;
+///     type Output = f64;
+///
+///     // Minimize (x0-1)^2 + (x1+2)^2
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok((x[0] - 1.).powf(2.) + (x[1] + 2.).powf(2.))
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder().initial_param(vec![0., 0.]).iprint(0).build();
+/// let res = Executor::new(QuadraticProblem, solver)
+///     .configure(|state| state.max_iters(200))
+///     .run()
+///     .unwrap();
+/// let x = res.state().best_param.as_ref().unwrap();
+/// assert!((x[0] - 1.).abs() < 1e-2 && (x[1] + 2.).abs() < 1e-2);
+/// ```
+pub trait CobylaOutput {
+    /// Type of the user context attached to an evaluation
+    type Context;
+
+    /// Returns the objective value followed by the constraint values, and the attached context
+    fn into_parts(self) -> (Vec<f64>, Option<Self::Context>);
+}
+
+impl CobylaOutput for Vec<f64> {
+    type Context = ();
+
+    fn into_parts(self) -> (Vec<f64>, Option<()>) {
+        (self, None)
+    }
+}
+
+impl CobylaOutput for f64 {
+    type Context = ();
+
+    fn into_parts(self) -> (Vec<f64>, Option<()>) {
+        (vec![self], None)
+    }
+}
+
+impl CobylaOutput for f32 {
+    type Context = ();
+
+    fn into_parts(self) -> (Vec<f64>, Option<()>) {
+        (vec![f64::from(self)], None)
+    }
+}
+
+/// Evaluation of the objective and constraints along with user data computed along.
+///
+/// Using it as the `Output` of a [CostFunction], the context is not interpreted by the
+/// solver but passed through to the state (see [crate::CobylaState::context]), so that
+/// intermediate results shared by the objective and the constraints are available.
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, Executor};
+/// use cobyla_argmin::{CobylaSolver, Evaluation};
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Evaluation<f64>;
+///
+///     // Minimize 10*(x0+1)^2 + x1^2 subject to x0 >= 0, keeping (x0+1)^2 as context
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         let shared = (x[0] + 1.).powf(2.);
+///         Ok(Evaluation {
+///             objective: 10. * shared + x[1].powf(2.),
+///             constraints: vec![x[0]],
+///             context: shared,
+///         })
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder()
+///     .initial_param(vec![1., 1.])
+///     .iprint(0)
+///     .build();
+/// let res = Executor::new(ParaboloidProblem, solver)
+///     .configure(|state| state.max_iters(100))
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation<T> {
+    /// Objective function value to be minimized
+    pub objective: f64,
+    /// Inequality constraint values `c(x)`, constraints being satisfied when `c(x) >= 0`
+    pub constraints: Vec<f64>,
+    /// User data passed through by the solver
+    pub context: T,
+}
+
+impl<T> CobylaOutput for Evaluation<T> {
+    type Context = T;
+
+    fn into_parts(self) -> (Vec<f64>, Option<T>) {
+        let mut fx = vec![self.objective];
+        fx.extend(self.constraints);
+        (fx, Some(self.context))
+    }
+}
+
+/// Error returned by the optimization when the cost function evaluation fails.
+///
+/// The optimization is aborted (no retry is attempted) and the cost function error is
+/// wrapped with the failing parameter vector in [crate::CobylaError::CostEvaluation] so
+/// that the caller can retrieve it from the error returned by `Executor::run()`:
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, Executor};
+/// use cobyla_argmin::{CobylaError, CobylaSolver};
+///
+/// struct FailingProblem;
+/// impl CostFunction for FailingProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         if x[0] < 0.5 {
+///             return Err(Error::msg("degenerate geometry"));
+///         }
+///         Ok(vec![x[0].powf(2.)])
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder().initial_param(vec![1.]).iprint(0).build();
+/// let err = Executor::new(FailingProblem, solver)
+///     .configure(|state| state.max_iters(100))
+///     .run()
+///     .err()
+///     .unwrap();
+/// let Some(CobylaError::CostEvaluation(err)) = err.downcast_ref::<CobylaError>() else {
+///     panic!("cost evaluation error expected");
+/// };
+/// assert!(err.param[0] < 0.5);
+/// ```
+#[derive(Debug)]
+pub struct CostEvaluationError {
+    /// Iteration of the failed evaluation, 0 being the evaluation of the initial guess
+    /// (same numbering as [crate::CobylaState::history])
+    pub iter: u64,
+    /// Parameter vector components at which the evaluation failed, empty when a batch
+    /// evaluation of the initial simplex fails (see
+    /// [crate::CobylaSolverBuilder::batch_initial_simplex]), iteration being then 1
+    pub param: Vec<f64>,
+    /// Error returned by the cost function
+    pub source: Error,
+}
+
+impl std::fmt::Display for CostEvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cost function evaluation failed at iteration {} with x = {:?}: {}",
+            self.iter, self.param, self.source
+        )
+    }
+}
+
+impl std::error::Error for CostEvaluationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
@@ -1,13 +1,31 @@
 use crate::cobyla::{
-    CobylaStatus, cobyla_context_t, cobyla_create, cobyla_delete, cobyla_get_status,
-    cobyla_iterate, cobyla_reason,
+    CobylaStatus, CobylaWriter, cobyla_context_t, cobyla_get_vertex, cobyla_iterate,
+    cobyla_set_geometry_step, with_output,
 };
+use crate::cobyla_cache::EvalCache;
+use crate::cobyla_config::CobylaConfig;
+use crate::cobyla_context::{CobylaContext, work_sizes};
+use crate::cobyla_error::{CobylaError, ConfigError, invalid_parameter};
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_problem::{CobylaOutput, CostEvaluationError};
 use crate::cobyla_state::*;
-use std::mem::ManuallyDrop;
+use crate::{
+    ConstraintSense, DegenerateSimplex, EqualityConstraint, FailStatus, NanConstraintPolicy,
+    NonFinitePolicy, RhoBeg, RoundoffInfo, Sense, SimplexInit, StopTols, SuccessStatus, TolCombine,
+    TrustRegionStep, VariableTransform, Verbosity, XtolRel,
+};
 
-use argmin::core::{CostFunction, KV, Problem, Solver, State, TerminationStatus};
+use argmin::core::{
+    CostFunction, KV, KvValue, Problem, SendAlias, Solver, State, SyncAlias, TerminationReason,
+    TerminationStatus,
+};
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use web_time::{Duration, Instant};
 
 /// [Argmin Solver](https://www.argmin-rs.org/book/index.html) which implements COBYLA method.
 ///
@@ -42,122 +60,3780 @@ use serde::{Deserialize, Serialize};
 ///
 /// println!("Result of COBYLA:\n{}", res);
 /// ```
+///
+/// COBYLA specific parameters can be set using [CobylaSolverBuilder]
+/// (see [CobylaSolver::builder]).
+///
+/// At initialization and after each iteration, observers are given the key-value pairs:
+/// * `cost`: objective value at the current point,
+/// * `best_cost`: best objective value of the feasible points evaluated so far (infinite
+///   when none, see [CobylaSolverBuilder::feasibility_tol]),
+/// * `rho`: trust region radius (see [CobylaState::current_rho]),
+/// * `max_violation`: maximum constraint violation at the current point,
+/// * `func_evals`: number of cost function evaluations so far,
+/// * `improvement`: decrease of the best feasible objective value over the iteration
+///   (see [CobylaState::last_improvement]),
+/// * `c1`, `c2`, ...: constraint values when enabled with
+///   [CobylaSolverBuilder::observe_constraints].
+///
+/// Observers reading the state (eg `SlogLogger`) also log its own `cost` and `best_cost`,
+/// which are the ones of the current point.
+///
+/// With the `log` feature, the outcome of each trust region step (acceptance, predicted
+/// and actual reductions of the merit function) and the reductions of the trust region
+/// radius are logged at `trace` level with the `log` crate, to diagnose stalls.
+///
+/// The solver and its state are `Send + Sync`: independent optimizations can be run
+/// concurrently in different threads (stop and progress callbacks and the output writer
+/// are required to be `Send`, added constraints and best point selectors to be
+/// `Send + Sync`).
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub struct CobylaSolver {
     /// Initial guess for x value
     x0: Vec<f64>,
+    /// Initial change of x, when not set `rhobeg` value of the state is used
+    rhobeg: Option<RhoBeg>,
+    /// Minimum initial change of x components used with [RhoBeg::Relative]
+    rhobeg_floor: f64,
+    /// Final value of the trust region radius, when not set `rhoend` value of the state is used
+    rhoend: Option<f64>,
+    /// Factor applied to the trust region radius when it is reduced
+    rho_reduction: f64,
+    /// Initial value of the penalty parameter of the merit function, Powell's one when not set
+    initial_penalty: Option<f64>,
+    /// Construction of the initial simplex
+    simplex_init: SimplexInit,
+    /// Handling of a degenerate custom initial simplex
+    degenerate_simplex: DegenerateSimplex,
+    /// Termination criteria
+    stop_tols: StopTols,
+    /// Lower and upper bounds of x components
+    bounds: Option<(Vec<f64>, Vec<f64>)>,
+    /// Names of x components
+    variable_names: Option<Vec<String>>,
+    /// User scaling of x components: COBYLA is run on `x / scaling`
+    scaling: Option<Vec<f64>>,
+    /// Transforms of x components applied before scaling
+    transforms: Option<Vec<VariableTransform>>,
+    /// User scaling of cost function constraints: COBYLA is given `c / constraint_scaling`
+    constraint_scaling: Option<Vec<f64>>,
+    /// Equality constraints among cost function output
+    eq_constraints: Vec<EqualityConstraint>,
+    /// Convention of the cost function constraints without a sense of their own
+    constraint_sense: ConstraintSense,
+    /// Convention of each cost function constraint
+    constraint_senses: Option<Vec<ConstraintSense>>,
+    /// Inequality constraints appended to the cost function output
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    added_constraints: Vec<AddedConstraint>,
+    /// Control of traces, when not set `iprint` value of the state is used
+    iprint: Option<i32>,
+    /// Policy applied to non-finite cost function values
+    non_finite_policy: NonFinitePolicy,
+    /// Policy applied to NaN constraint values
+    nan_constraint_policy: NanConstraintPolicy,
+    /// Whether values of the last cost function output were substituted by the NaN
+    /// constraint or the non-finite policies
+    substituted: bool,
+    /// Wall-clock time budget of the optimization
+    max_time: Option<Duration>,
+    /// Budget of cost function evaluations of the optimization
+    max_fun_evals: Option<u64>,
+    /// Objective value target of feasible points stopping the optimization
+    stopval: Option<f64>,
+    /// Optimization sense of the objective
+    sense: Sense,
+    /// Number of iterations and minimum improvement of the best objective over them
+    stagnation_window: Option<(usize, f64)>,
+    /// Constraint margin and objective gradient tolerance of the interior criterion
+    interior_stop: Option<(f64, f64)>,
+    /// Best objective values at the start of the last iterations of the stagnation window
+    best_costs: VecDeque<f64>,
+    /// Best objective value of the feasible points evaluated so far (infinite if none)
+    incumbent: f64,
+    /// Objective value at the initial guess, reference of [StopTols::ftol_init]
+    initial_objective: f64,
+    /// Whether the final trust region radius is set by the tolerances on x rather than
+    /// by `rhoend`, COBYLA success being then reported as [SuccessStatus::XtolReached]
+    xtol_stop: bool,
+    /// User callback consulted after each evaluation to force the optimization to stop
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    stop_callback: Option<StopCallback>,
+    /// User callback called after each iteration to monitor the optimization progress
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    progress_callback: Option<ProgressCallback>,
+    /// User selection of the reported best point among the evaluated ones
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    best_selector: Option<BestSelector>,
+    /// Writer of COBYLA printouts, stdout when not set
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    output: Option<Output>,
+    /// Cache of cost function evaluations
+    cache: Option<EvalCache>,
+    /// Number of repeated cost function evaluations averaged at each point
+    resample: usize,
+    /// Evaluated points with the sample standard deviations of their repeated cost
+    /// function evaluations, empty when not resampled
+    sample_stds: Vec<(Vec<f64>, Vec<f64>)>,
+    /// Whether the history of evaluations is recorded in the state
+    keep_history: bool,
+    /// Whether evaluated points and cost function outputs are recorded in the state
+    keep_evaluations: bool,
+    /// Whether the simplex vertices are recorded in the state at each iteration
+    keep_simplex: bool,
+    /// Whether panics of the cost function are caught to stop the optimization
+    catch_panics: bool,
+    /// Whether the initial simplex vertices are evaluated in a single batch
+    batch_initial_simplex: bool,
+    /// Whether points are clamped within the bounds before calling the cost function
+    clamp_to_bounds: bool,
+    /// Whether trust region steps out of the bounds are rejected without evaluation
+    reject_out_of_bounds: bool,
+    /// Number of constraints of the cost function output declared by the user
+    num_constraints: Option<usize>,
+    /// Number of constraints of the cost function output at the initial guess, None when
+    /// not initialized
+    output_constraints: Option<usize>,
+    /// Batch evaluations of the initial simplex vertices not yet requested by COBYLA
+    prefetched: Vec<(Vec<f64>, Vec<f64>)>,
+    /// Whether constraint values are given to observers in addition to the maximum violation
+    observe_constraints: bool,
+    /// COBYLA context of a previous run to be continued, None inside when the previous
+    /// run cannot be continued
+    warm_start: Option<Option<CobylaContext>>,
+    /// Whether a feasibility restoration phase is run from an infeasible initial guess
+    restore_feasibility: bool,
+    /// Whether only a feasible point is searched, the objective being ignored
+    feasibility_only: bool,
+    /// Maximum number of restarts of COBYLA when its simplex degenerates
+    max_restarts: usize,
+    /// Whether the best feasible point is returned instead of an infeasible best point
+    prefer_feasible: bool,
+    /// Whether convergence on x is only accepted at feasible points
+    feasible_stop: bool,
+    /// Number of reductions of the final trust region radius to reach feasibility
+    refinements: usize,
+    /// Whether an infeasible result is polished by a final feasibility restoration
+    polish_feasibility: bool,
+    /// Termination status of the optimization while polishing the feasibility of its result
+    polishing: Option<SuccessStatus>,
+    /// Maximum constraint violation ending the feasibility restoration phase
+    feasibility_tol: f64,
+    /// User seed archived in the configuration (see [CobylaConfig::seed])
+    seed: Option<u64>,
+    /// Least infeasible point of the feasibility restoration phase, None when not restoring
+    restoration: Option<Restoration>,
+    /// Scaling of x components computed from `rhobeg` at initialization
+    scale: Option<Vec<f64>>,
+    /// Scaled components and user space components of the start point of the current
+    /// COBYLA run when scaled, converted exactly into each other
+    start_point: Option<(Vec<f64>, Vec<f64>)>,
+    /// Start time of the optimization (reset when resuming from a checkpoint)
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    start: Option<Instant>,
 }
 
 impl CobylaSolver {
-    pub fn new(x0: Vec<f64>) -> Self {
-        CobylaSolver { x0 }
+    /// Constructor given the initial guess for x value
+    pub fn new<P: CobylaParam>(x0: P) -> Self {
+        CobylaSolverBuilder::default().initial_param(x0).build()
     }
-}
 
-impl<O> Solver<O, CobylaState> for CobylaSolver
-where
-    O: CostFunction<Param = Vec<f64>, Output = Vec<f64>>,
-{
-    fn name(&self) -> &str {
-        "COBYLA"
+    /// Constructor given the initial guess for x value as a borrowed slice (copied once)
+    pub fn new_from_slice(x0: &[f64]) -> Self {
+        CobylaSolverBuilder::default()
+            .initial_param(x0.to_vec())
+            .build()
     }
 
-    /// Initializes the algorithm.
+    /// Returns a builder to configure the solver
+    pub fn builder() -> CobylaSolverBuilder {
+        CobylaSolverBuilder::default()
+    }
+
+    /// Constructor given a configuration archived with [CobylaSolver::config]
+    pub fn from_config(config: CobylaConfig) -> Self {
+        let mut solver = CobylaSolverBuilder {
+            x0: config.x0,
+            rhobeg: config.rhobeg,
+            rhobeg_floor: Some(config.rhobeg_floor),
+            rhoend: config.rhoend,
+            rho_reduction: Some(config.rho_reduction),
+            initial_penalty: config.initial_penalty,
+            simplex_init: config.simplex_init,
+            degenerate_simplex: config.degenerate_simplex,
+            stop_tols: config.stop_tols,
+            bounds: config.bounds,
+            scaling: config.scaling,
+            transforms: config.transforms,
+            constraint_scaling: config.constraint_scaling,
+            eq_constraints: config.eq_constraints,
+            constraint_sense: config.constraint_sense,
+            constraint_senses: config.constraint_senses,
+            non_finite_policy: config.non_finite_policy,
+            nan_constraint_policy: config.nan_constraint_policy,
+            max_time: config.max_time,
+            max_fun_evals: config.max_fun_evals,
+            stopval: config.stopval,
+            sense: config.sense,
+            stagnation_window: config.stagnation_window,
+            interior_stop: config.interior_stop,
+            resample: Some(config.resample),
+            batch_initial_simplex: config.batch_initial_simplex,
+            clamp_to_bounds: config.clamp_to_bounds,
+            reject_out_of_bounds: config.reject_out_of_bounds,
+            restore_feasibility: config.restore_feasibility,
+            feasibility_only: config.feasibility_only,
+            max_restarts: config.max_restarts,
+            prefer_feasible: config.prefer_feasible,
+            feasible_stop: config.feasible_stop,
+            polish_feasibility: config.polish_feasibility,
+            feasibility_tol: Some(config.feasibility_tol),
+            cache: config
+                .cache
+                .map(|(capacity, tol)| EvalCache::new(capacity, tol)),
+            catch_panics: Some(config.catch_panics),
+            ..CobylaSolverBuilder::default()
+        }
+        .build();
+        solver.seed = config.seed;
+        solver
+    }
+
+    /// Returns the memory footprint in bytes of the COBYLA working arrays given the
+    /// dimension `n` of x and the number `m` of constraints handled by COBYLA, ie the
+    /// constraints of the cost function, plus one per equality constraint and one per
+    /// finite bound (saturating at `usize::MAX`).
     ///
-    /// Executed before any iterations are performed and has access to the optimization problem
-    /// definition and the internal state of the solver.
-    /// Returns an updated `state` and optionally a `KV` which holds key-value pairs used in
-    /// [Observers](`argmin::core::observers::Observe`).
-    /// The default implementation returns the unaltered `state` and no `KV`.
-    #[allow(clippy::useless_conversion)]
-    fn init(
+    /// It grows as `8 * n * (3 * n + 2 * m)` bytes: the working arrays are allocated when the
+    /// optimization starts, which stops with [FailStatus::OutOfMemory] when they cannot be.
+    ///
+    /// ```
+    /// use cobyla_argmin::CobylaSolver;
+    ///
+    /// // about 600 MB for 5000 unconstrained variables
+    /// let footprint = CobylaSolver::memory_footprint(5000, 0);
+    /// assert!(footprint > 600_000_000 && footprint < 601_000_000);
+    /// ```
+    pub fn memory_footprint(n: usize, m: usize) -> usize {
+        work_sizes(n, m).map_or(usize::MAX, |(iact, work)| {
+            iact.saturating_mul(std::mem::size_of::<libc::c_long>())
+                .saturating_add(work.saturating_mul(std::mem::size_of::<f64>()))
+                .saturating_add(std::mem::size_of::<cobyla_context_t>())
+        })
+    }
+
+    /// Returns the number of variables, the size of the initial guess or of the bounds
+    /// when the initial guess defaults to their center
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaSolver;
+    /// let solver = CobylaSolver::builder().with_bounds([0., 0., -1.], [1., 2., 1.]).build();
+    /// assert_eq!(solver.dim(), 3);
+    /// ```
+    pub fn dim(&self) -> usize {
+        match self.bounds.as_ref() {
+            Some((lower, _)) if self.x0.is_empty() => lower.len(),
+            _ => self.x0.len(),
+        }
+    }
+
+    /// Returns the number of constraints of the cost function output (including the ones
+    /// added with [CobylaSolverBuilder::add_constraint], excluding bounds), the declared one
+    /// (see [CobylaSolverBuilder::with_num_constraints]) or the one found at the initial
+    /// guess once initialized, None otherwise.
+    pub fn num_constraints(&self) -> Option<usize> {
+        self.num_constraints.or(self.output_constraints)
+    }
+
+    /// Returns the configuration of the solver: initial guess and tunables to be archived
+    /// to reproduce the run with [CobylaSolver::from_config]
+    pub fn config(&self) -> CobylaConfig {
+        CobylaConfig {
+            x0: self.x0.clone(),
+            rhobeg: self.rhobeg.clone(),
+            rhobeg_floor: self.rhobeg_floor,
+            rhoend: self.rhoend,
+            rho_reduction: self.rho_reduction,
+            initial_penalty: self.initial_penalty,
+            simplex_init: self.simplex_init.clone(),
+            degenerate_simplex: self.degenerate_simplex,
+            stop_tols: self.stop_tols.clone(),
+            bounds: self.bounds.clone(),
+            scaling: self.scaling.clone(),
+            transforms: self.transforms.clone(),
+            constraint_scaling: self.constraint_scaling.clone(),
+            eq_constraints: self.eq_constraints.clone(),
+            constraint_sense: self.constraint_sense,
+            constraint_senses: self.constraint_senses.clone(),
+            non_finite_policy: self.non_finite_policy,
+            nan_constraint_policy: self.nan_constraint_policy,
+            max_time: self.max_time,
+            max_fun_evals: self.max_fun_evals,
+            stopval: self.stopval,
+            sense: self.sense,
+            stagnation_window: self.stagnation_window,
+            interior_stop: self.interior_stop,
+            resample: self.resample,
+            batch_initial_simplex: self.batch_initial_simplex,
+            clamp_to_bounds: self.clamp_to_bounds,
+            reject_out_of_bounds: self.reject_out_of_bounds,
+            restore_feasibility: self.restore_feasibility,
+            feasibility_only: self.feasibility_only,
+            max_restarts: self.max_restarts,
+            prefer_feasible: self.prefer_feasible,
+            feasible_stop: self.feasible_stop,
+            polish_feasibility: self.polish_feasibility,
+            feasibility_tol: self.feasibility_tol,
+            cache: self.cache.as_ref().map(EvalCache::settings),
+            catch_panics: self.catch_panics,
+            seed: self.seed,
+        }
+    }
+
+    /// Converts a status code returned by COBYLA iterations into a termination status
+    fn status_from(&self, status: i32) -> Result<SuccessStatus, FailStatus> {
+        match status {
+            s if s == CobylaStatus::COBYLA_SUCCESS as i32 => {
+                if self.xtol_stop {
+                    Ok(SuccessStatus::XtolReached)
+                } else {
+                    Ok(SuccessStatus::Success)
+                }
+            }
+            s if s == CobylaStatus::COBYLA_ROUNDING_ERRORS as i32 => {
+                Err(FailStatus::RoundoffLimited)
+            }
+            // COBYLA evaluation budget is the maximum number of iterations of the state
+            s if s == CobylaStatus::COBYLA_TOO_MANY_EVALUATIONS as i32 => {
+                Ok(SuccessStatus::MaxItersReached)
+            }
+            _ => Err(FailStatus::UnexpectedError),
+        }
+    }
+
+    /// Defaults the initial guess to the center of the bounds when it is not given
+    fn initial_guess_from_bounds(&mut self) -> Result<(), argmin::core::Error> {
+        let Some((lower, upper)) = self.bounds.as_ref() else {
+            return Ok(());
+        };
+        if !self.x0.is_empty() || lower.is_empty() {
+            return Ok(());
+        }
+        if lower.len() != upper.len() || lower.iter().chain(upper).any(|b| !b.is_finite()) {
+            return Err(invalid_parameter(
+                "bounds should be finite and of the same size when no initial guess is given",
+            ));
+        }
+        self.x0 = lower
+            .iter()
+            .zip(upper)
+            .map(|(lo, up)| 0.5 * (lo + up))
+            .collect();
+        Ok(())
+    }
+
+    /// Checks bounds consistency and the initial guess lies within them
+    fn check_bounds(&self) -> Result<(), argmin::core::Error> {
+        if let Some((lower, upper)) = self.bounds.as_ref() {
+            if lower.len() != self.x0.len() || upper.len() != self.x0.len() {
+                return Err(invalid_parameter(format!(
+                    "bounds sizes ({}, {}) should be equal to x dimension ({})",
+                    lower.len(),
+                    upper.len(),
+                    self.x0.len()
+                )));
+            }
+            for (i, (lo, up)) in lower.iter().zip(upper).enumerate() {
+                if lo > up {
+                    return Err(invalid_parameter(format!(
+                        "lower bound {lo} is greater than upper bound {up} for x[{i}]"
+                    )));
+                }
+                let xi = self.x0[i];
+                if !(*lo <= xi && xi <= *up) {
+                    return Err(invalid_parameter(format!(
+                        "initial guess x[{i}] = {xi} is out of bounds [{lo}, {up}]"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks there is one unique name per x component when variables are named
+    fn check_variable_names(&self) -> Result<(), argmin::core::Error> {
+        let Some(names) = self.variable_names.as_ref() else {
+            return Ok(());
+        };
+        if names.len() != self.x0.len() {
+            return Err(invalid_parameter(format!(
+                "number of variable names ({}) should be equal to x dimension ({})",
+                names.len(),
+                self.x0.len()
+            )));
+        }
+        if let Some((i, name)) = names
+            .iter()
+            .enumerate()
+            .find(|(i, name)| names[..*i].contains(name))
+        {
+            return Err(invalid_parameter(format!(
+                "variable name {name:?} of x[{i}] is already used"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the settings which do not depend on the cost function output, returning all
+    /// the errors found (the initial guess being replaced as when running)
+    fn settings_errors(&mut self) -> Vec<argmin::core::Error> {
+        let mut errors: Vec<_> = [
+            self.check_simplex().err(),
+            self.check_bounds().err(),
+            self.check_variable_names().err(),
+            self.check_rhobeg().err(),
+            self.check_scaling().err(),
+            self.check_transforms().err(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if let Some((iters, epsilon)) = self.stagnation_window
+            && (iters == 0 || epsilon.is_nan() || epsilon < 0.)
+        {
+            errors.push(invalid_parameter(format!(
+                "stagnation window ({iters}) should be strictly positive and epsilon ({epsilon}) positive"
+            )));
+        }
+        if let Some((margin, gtol)) = self.interior_stop
+            && !(margin >= 0. && gtol > 0.)
+        {
+            errors.push(invalid_parameter(format!(
+                "interior stop margin ({margin}) should be positive and gradient tolerance ({gtol}) strictly positive"
+            )));
+        }
+        if self.resample == 0 {
+            errors.push(invalid_parameter(
+                "number of resampled evaluations should be strictly positive",
+            ));
+        }
+        if self.batch_initial_simplex && matches!(self.simplex_init, SimplexInit::AxisAligned) {
+            errors.push(invalid_parameter(
+                "batch evaluation of the initial simplex requires a custom initial simplex",
+            ));
+        }
+        if self.max_fun_evals == Some(0) {
+            errors.push(invalid_parameter(
+                "maximum number of cost function evaluations should be strictly positive",
+            ));
+        }
+        if self.best_selector.is_some() && !self.keep_evaluations {
+            errors.push(invalid_parameter(
+                "best selector requires the evaluation log to be kept",
+            ));
+        }
+        if let NanConstraintPolicy::Violation(violation) = self.nan_constraint_policy
+            && !(violation > 0. && violation.is_finite())
+        {
+            errors.push(invalid_parameter(format!(
+                "NaN constraint violation ({violation}) should be finite and strictly positive"
+            )));
+        }
+        errors
+    }
+
+    /// Returns the context of the run to continue with the point to be evaluated first
+    /// (see [CobylaSolverBuilder::warm_start]). The scaling of the previous run is restored.
+    fn warm_start_point(
         &mut self,
-        problem: &mut Problem<O>,
-        state: CobylaState,
-    ) -> std::result::Result<(CobylaState, Option<KV>), argmin::core::Error> {
-        let n = self.x0.len() as i32;
-        let fx0 = problem.cost(&self.x0)?;
-        let m = (fx0.len() - 1) as i32;
-        let rhobeg = state.rhobeg();
-        let rhoend = state.get_rhoend();
-        let iprint = state.get_iprint();
-        let maxfun = state.get_maxfun();
-        let mut initial_state = state;
-        let ptr = unsafe {
-            cobyla_create(
-                n.into(),
-                m.into(),
-                rhobeg,
-                rhoend,
-                iprint.into(),
-                maxfun.into(),
-            )
+    ) -> Result<Option<(CobylaContext, Vec<f64>)>, argmin::core::Error> {
+        let Some(warm_start) = self.warm_start.as_ref() else {
+            return Ok(None);
         };
-        initial_state.cobyla_context = Some(ManuallyDrop::new(ptr));
+        let Some((context, pending)) = warm_start
+            .clone()
+            .and_then(|context| context.pending.clone().map(|x| (context, x)))
+        else {
+            return Err(invalid_parameter(
+                "warm start state cannot be continued: COBYLA iterations are terminated",
+            ));
+        };
+        let (n, _) = context.dims();
+        if n != self.x0.len() {
+            return Err(invalid_parameter(format!(
+                "warm start state dimension ({n}) should be equal to x dimension ({})",
+                self.x0.len()
+            )));
+        }
+        self.scale = context.scale.clone();
+        self.start_point = None;
+        let x = self.unscaled(&pending);
+        if self.scale.is_some() {
+            self.start_point = Some((pending, x.clone()));
+        }
+        Ok(Some((context, x)))
+    }
 
-        let initial_state = initial_state.param(self.x0.clone()).cost(fx0);
-        Ok((initial_state, None))
+    /// Returns the reason of a mismatch between x dimension and the sizes of
+    /// [RhoBeg::Set], [XtolRel::Set] (when not a single value) or `xtol_abs` (when not empty)
+    /// if any
+    fn size_mismatch(&self) -> Option<String> {
+        let n = self.x0.len();
+        if let Some(RhoBeg::Set(dx)) = self.rhobeg.as_ref()
+            && dx.len() != n
+        {
+            return Some(format!(
+                "rhobeg size ({}) should be equal to x dimension ({n})",
+                dx.len()
+            ));
+        }
+        if let XtolRel::Set(xtol_rel) = &self.stop_tols.xtol_rel
+            && xtol_rel.len() != 1
+            && xtol_rel.len() != n
+        {
+            return Some(format!(
+                "xtol_rel size ({}) should be equal to x dimension ({n})",
+                xtol_rel.len()
+            ));
+        }
+        let xtol_abs = &self.stop_tols.xtol_abs;
+        if !xtol_abs.is_empty() && xtol_abs.len() != n {
+            return Some(format!(
+                "xtol_abs size ({}) should be equal to x dimension ({n})",
+                xtol_abs.len()
+            ));
+        }
+        None
     }
 
-    /// Computes a single iteration of the algorithm and has access to the optimization problem
-    /// definition and the internal state of the solver.
-    /// Returns an updated `state` and optionally a `KV` which holds key-value pairs used in
-    /// [Observers](`argmin::core::observers::Observe`).
-    fn next_iter(
+    /// Creates the COBYLA context of a new run given the number of constraints `m`,
+    /// the initial simplex being the axis aligned one unless `custom` (see [SimplexInit])
+    #[allow(clippy::useless_conversion)]
+    fn create_context<P, C>(&mut self, state: &mut CobylaState<P, C>, m: usize, custom: bool)
+    where
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let n = self.x0.len();
+        if let Some(reason) = self.size_mismatch() {
+            state.status = Some(Err(FailStatus::InvalidArgs));
+            state.termination_status =
+                TerminationStatus::Terminated(TerminationReason::SolverExit(reason));
+            return;
+        }
+        let mut dx = match self.rhobeg.as_ref() {
+            Some(RhoBeg::All(v)) => vec![*v; n],
+            Some(RhoBeg::Set(v)) => v.clone(),
+            Some(RhoBeg::Relative(factor)) => self
+                .transformed(&self.x0)
+                .iter()
+                .map(|xi| (factor * xi.abs()).max(self.rhobeg_floor))
+                .collect(),
+            None => vec![state.rhobeg(); n],
+        };
+        if let Some(i) = dx.iter().position(|d| !(*d > 0. && d.is_finite())) {
+            // A zero change of x gives a degenerate initial simplex
+            let reason = format!(
+                "rhobeg component x[{i}] ({}) should be finite and strictly positive",
+                dx[i]
+            );
+            state.status = Some(Err(FailStatus::InvalidArgs));
+            state.termination_status =
+                TerminationStatus::Terminated(TerminationReason::SolverExit(reason));
+            return;
+        }
+        // Changes of x components are converted in user scaled space
+        if let (Some(s), Some(RhoBeg::Set(_) | RhoBeg::Relative(_))) =
+            (self.scaling.as_ref(), self.rhobeg.as_ref())
+        {
+            dx = dx.iter().zip(s).map(|(d, si)| d / si).collect();
+        }
+        self.start_point = None;
+        self.scale = match (compute_rescaling(&dx), self.scaling.as_ref()) {
+            (Some(r), Some(s)) => Some(r.iter().zip(s).map(|(ri, si)| ri * si).collect()),
+            (r, None) => r,
+            (None, s) => s.cloned(),
+        };
+        let rhobeg = dx[0].abs();
+
+        // Tolerances on x are taken into account through rho end value
+        let rhoend = self.rhoend.unwrap_or(state.get_rhoend());
+        let xtol_rel = match &self.stop_tols.xtol_rel {
+            XtolRel::All(tol) => vec![*tol],
+            XtolRel::Set(tols) => tols.clone(),
+        };
+        let xtol_abs = self.stop_tols.xtol_abs.iter().enumerate().map(|(j, tol)| {
+            let sj = self.scale.as_ref().map_or(1., |s| s[j].abs());
+            tol / sj
+        });
+        let radii = xtol_rel
+            .iter()
+            .map(|tol| tol * rhobeg)
+            .chain(xtol_abs)
+            .filter(|radius| *radius > 0.);
+        let xtol = match self.stop_tols.xtol_combine {
+            TolCombine::Any => radii.fold(0., f64::max),
+            TolCombine::All => radii.reduce(f64::min).unwrap_or(0.),
+        };
+        self.xtol_stop = xtol > rhoend;
+        self.new_context(state, n, m, rhobeg, rhoend.max(xtol), custom);
+    }
+
+    /// Creates the COBYLA context with `m` constraints in the state given the initial and
+    /// final trust region radii in the space where COBYLA is run (see [CobylaSolver::scaled])
+    fn new_context<P, C>(
+        &self,
+        state: &mut CobylaState<P, C>,
+        n: usize,
+        m: usize,
+        rhobeg: f64,
+        rhoend: f64,
+        custom: bool,
+    ) where
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let iprint = self.iprint.unwrap_or(state.get_iprint());
+        let maxfun = state.get_maxfun();
+        state.rho = rhobeg;
+        state.effective_rhobeg = Some(self.component_changes(rhobeg, n));
+        state.effective_rhoend = Some(self.component_changes(rhoend, n));
+        let context = CobylaContext::new(
+            (n as i32).into(),
+            (m as i32).into(),
+            rhobeg,
+            rhoend,
+            iprint.into(),
+            maxfun.into(),
+        )
+        .map(|mut context| {
+            context.scale = self.scale.clone();
+            context.set_rho_reduction(self.rho_reduction);
+            if let Some(penalty) = self.initial_penalty {
+                context.set_initial_penalty(penalty);
+            }
+            if let (SimplexInit::Custom(vertices), true) = (&self.simplex_init, custom) {
+                // Displacements are expressed in the space where COBYLA is run
+                let sim = simplex_displacements(vertices, |v| self.scaled(v));
+                if let Some(simi) = invert(&sim, n) {
+                    context.set_simplex(&sim, &simi);
+                }
+            }
+            context
+        });
+        match context {
+            Ok(context) => state.cobyla_context = Some(context),
+            Err(status) => {
+                state.cobyla_context = None;
+                state.status = Some(Err(status));
+                if status == FailStatus::OutOfMemory {
+                    let reason = format!(
+                        "cannot allocate {} bytes of COBYLA working arrays",
+                        CobylaSolver::memory_footprint(n, m)
+                    );
+                    state.termination_status =
+                        TerminationStatus::Terminated(TerminationReason::SolverExit(reason));
+                }
+            }
+        }
+    }
+
+    /// Resumes the COBYLA context of a previous run given the number of constraints `m`,
+    /// the evaluation budget of the state being given in addition to the previous one
+    #[allow(clippy::useless_conversion)]
+    fn resume_context<P, C>(
+        &mut self,
+        state: &mut CobylaState<P, C>,
+        mut context: CobylaContext,
+        m: usize,
+    ) -> Result<(), argmin::core::Error>
+    where
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let (_, cm) = context.dims();
+        if cm != m {
+            return Err(invalid_parameter(format!(
+                "warm start state number of constraints ({cm}) should be equal to the one of the problem ({m})"
+            )));
+        }
+        context.pending = None;
+        state.rho = context.rho();
+        let rhoend = self.rhoend.unwrap_or(state.get_rhoend());
+        self.xtol_stop = unsafe { (*context.as_mut_ptr()).rhoend } > rhoend;
+        if context.extend_maxfun(state.get_maxfun().into()) {
+            state.cobyla_context = Some(context);
+        } else {
+            state.status = Some(Err(FailStatus::InvalidArgs));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the cost function at x using the evaluation cache when enabled,
+    /// returns the cost function values and the attached user context (not cached).
+    ///
+    /// A cost function error is wrapped in a [CobylaError::CostEvaluation] with the iteration `iter`.
+    /// The cost function is called at x clamped within the bounds when required.
+    fn evaluate<O, P, C>(
         &mut self,
         problem: &mut Problem<O>,
-        state: CobylaState,
-    ) -> std::result::Result<(CobylaState, Option<KV>), argmin::core::Error> {
-        let mut x = state.get_param().unwrap().clone();
-        if let Some(ctx) = state.cobyla_context.as_ref() {
-            let cost = problem.cost(&x)?;
-            let f = cost[0];
-            let mut c = Box::new(cost[1..].to_vec());
-
-            let _status = unsafe {
-                cobyla_iterate(
-                    **ctx as *mut cobyla_context_t,
-                    f,
-                    x.as_mut_ptr(),
-                    c.as_mut_ptr(),
-                )
+        x: &P,
+        iter: u64,
+        state: &mut CobylaState<P, C>,
+    ) -> Result<(Vec<f64>, Option<C>), argmin::core::Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput<Context = C>,
+        P: CobylaParam,
+        C: Clone,
+    {
+        if let Some(prefetched) = self
+            .prefetched
+            .iter()
+            .position(|(xk, _)| same_point(xk, &x.components()))
+        {
+            let (_, fx) = self.prefetched.swap_remove(prefetched);
+            return Ok((fx, None));
+        }
+        let key = (self.resample > 1).then(|| x.components().into_owned());
+        let clamped = self.clamped(&x.components()).map(P::from_components);
+        let x = clamped.as_ref().unwrap_or(x);
+        let catch_panics = self.catch_panics;
+        let cost = |problem: &mut Problem<O>| {
+            let output = if catch_panics {
+                std::panic::catch_unwind(AssertUnwindSafe(|| problem.cost(x))).map_err(
+                    |payload| {
+                        argmin::core::Error::new(CostPanic(format!(
+                            "cost function panicked at iteration {iter} with x = {:?}: {}",
+                            x.components(),
+                            panic_message(payload.as_ref())
+                        )))
+                    },
+                )?
+            } else {
+                problem.cost(x)
             };
-            let fx = problem.cost(&x)?;
-            let state = state.param(x).cost(fx);
-            return Ok((state, None));
+            output.map_err(|source| {
+                argmin::core::Error::new(CobylaError::CostEvaluation(CostEvaluationError {
+                    iter,
+                    param: x.components().into_owned(),
+                    source,
+                }))
+            })
+        };
+        let resample = self.resample;
+        let sampled = |problem: &mut Problem<O>| {
+            let (fx, context) = cost(problem)?.into_parts();
+            let mut samples = vec![fx];
+            for _ in 1..resample {
+                samples.push(cost(problem)?.into_parts().0);
+            }
+            sample_statistics(samples).map(|(fx, std)| (fx, std, context))
+        };
+        let xc = x.components();
+        if let Some(fx) = self.cache.as_mut().and_then(|cache| cache.get(&xc)) {
+            state.cache_hits += 1;
+            return Ok((fx, None));
         }
-
-        Ok((state, None))
+        if self.cache.is_some() {
+            state.cache_misses += 1;
+        }
+        state.func_evals += resample as u64;
+        let start = Instant::now();
+        let output = sampled(problem);
+        state.eval_time += start.elapsed();
+        let (fx, std, context) = output?;
+        if let (Some(key), Some(std)) = (key, std) {
+            self.sample_stds.push((key, std));
+        }
+        let fx = with_added_constraints(&self.added_constraints, &xc, fx);
+        state.record_evaluation(&xc, &fx);
+        let Some(cache) = self.cache.as_mut() else {
+            return Ok((fx, context));
+        };
+        cache.insert(&xc, fx.clone());
+        Ok((fx, context))
     }
 
-    /// Used to implement stopping criteria, in particular criteria which are not covered by
-    /// ([`terminate_internal`](`Solver::terminate_internal`).
+    /// Evaluates the vertices of the custom initial simplex other than the initial guess
+    /// in a single batch within the evaluation budget, results being used when requested
+    /// by COBYLA.
     ///
-    /// This method has access to the internal state and returns an `TerminationReason`.
-    fn terminate(&mut self, state: &CobylaState) -> TerminationStatus {
-        if let Some(ctx) = state.cobyla_context.as_ref() {
-            let status = unsafe {
-                let ctx_ptr = **ctx;
-                cobyla_get_status(ctx_ptr)
-            };
-            if status == CobylaStatus::COBYLA_ITERATE as i32 {
-                return TerminationStatus::NotTerminated;
-            } else {
-                let cstr = unsafe { std::ffi::CStr::from_ptr(cobyla_reason(status)) };
-                let reason = cstr.to_str().unwrap().to_string();
-                unsafe { cobyla_delete(**ctx as *mut cobyla_context_t) }
-                if reason == "algorithm was successful" {
-                    return TerminationStatus::Terminated(
-                        argmin::core::TerminationReason::SolverConverged,
-                    );
+    /// A cost function error is wrapped in a [CobylaError::CostEvaluation] as in `evaluate`.
+    fn prefetch_vertices<O, P, C>(
+        &mut self,
+        problem: &mut Problem<O>,
+        state: &mut CobylaState<P, C>,
+    ) -> Result<(), argmin::core::Error>
+    where
+        O: CostFunction<Param = P> + SyncAlias,
+        O::Output: CobylaOutput<Context = C> + SendAlias,
+        P: CobylaParam + SyncAlias,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let budget = self
+            .max_fun_evals
+            .unwrap_or(u64::MAX)
+            .min(state.get_maxfun().max(0) as u64)
+            .saturating_sub(state.func_evals + self.resample as u64)
+            / self.resample as u64;
+        let SimplexInit::Custom(vertices) = &self.simplex_init else {
+            return Ok(());
+        };
+        let mut vertices: Vec<_> = vertices.iter().skip(1).cloned().collect();
+        vertices.truncate(budget.min(vertices.len() as u64) as usize);
+        if vertices.is_empty() {
+            return Ok(());
+        }
+        let evaluated: Vec<Vec<f64>> = vertices
+            .iter()
+            .map(|xk| self.clamped(xk).unwrap_or_else(|| xk.clone()))
+            .collect();
+        let params: Vec<P> = evaluated
+            .iter()
+            .flat_map(|xe| std::iter::repeat_n(xe, self.resample))
+            .cloned()
+            .map(P::from_components)
+            .collect();
+        let start = Instant::now();
+        let outputs = if self.catch_panics {
+            std::panic::catch_unwind(AssertUnwindSafe(|| problem.bulk_cost(&params))).map_err(
+                |payload| {
+                    argmin::core::Error::new(CostPanic(format!(
+                        "cost function panicked while evaluating the initial simplex: {}",
+                        panic_message(payload.as_ref())
+                    )))
+                },
+            )?
+        } else {
+            problem.bulk_cost(&params)
+        };
+        state.eval_time += start.elapsed();
+        // The failing vertex of the batch is unknown: the error is reported at the
+        // iteration of the first one
+        let outputs = outputs.map_err(|source| {
+            argmin::core::Error::new(CobylaError::CostEvaluation(CostEvaluationError {
+                iter: 1,
+                param: Vec::new(),
+                source,
+            }))
+        })?;
+        let mut outputs = outputs.into_iter().map(|output| output.into_parts().0);
+        for (xk, xe) in vertices.into_iter().zip(evaluated) {
+            let samples = outputs.by_ref().take(self.resample).collect();
+            let (fx, std) = sample_statistics(samples)?;
+            if let Some(std) = std {
+                self.sample_stds.push((xk.clone(), std));
+            }
+            let fx = with_added_constraints(&self.added_constraints, &xe, fx);
+            state.func_evals += self.resample as u64;
+            state.record_evaluation(&xe, &fx);
+            if let Some(cache) = self.cache.as_mut() {
+                cache.insert(&xe, fx.clone());
+            }
+            self.prefetched.push((xk, fx));
+        }
+        Ok(())
+    }
+
+    /// Checks initial guess dimension against the one of the parameter type when fixed
+    fn check_dim<P: CobylaParam>(&self) -> Result<(), argmin::core::Error> {
+        match P::fixed_dim() {
+            Some(dim) if dim != self.x0.len() => Err(invalid_parameter(format!(
+                "initial guess dimension ({}) should be equal to parameter dimension ({dim})",
+                self.x0.len()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks initial change of x, trust region radius reduction and penalty settings
+    fn check_rhobeg(&self) -> Result<(), argmin::core::Error> {
+        if let Some(RhoBeg::Relative(factor)) = self.rhobeg.as_ref()
+            && (*factor <= 0. || self.rhobeg_floor <= 0.)
+        {
+            return Err(invalid_parameter(format!(
+                "relative rhobeg factor ({factor}) and floor ({}) should be strictly positive",
+                self.rhobeg_floor
+            )));
+        }
+        if !(self.rho_reduction > 0. && self.rho_reduction < 1.) {
+            return Err(invalid_parameter(format!(
+                "rho reduction factor ({}) should be strictly between 0 and 1",
+                self.rho_reduction
+            )));
+        }
+        if let Some(penalty) = self.initial_penalty
+            && !(penalty > 0. && penalty.is_finite())
+        {
+            return Err(invalid_parameter(format!(
+                "initial penalty ({penalty}) should be finite and strictly positive"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks user initial simplex: `n + 1` affinely independent vertices of dimension `n`,
+    /// the first vertex replacing the initial guess
+    fn check_simplex(&mut self) -> Result<(), argmin::core::Error> {
+        let SimplexInit::Custom(vertices) = &mut self.simplex_init else {
+            return Ok(());
+        };
+        let n = self.x0.len();
+        if vertices.len() != n + 1 {
+            return Err(invalid_parameter(format!(
+                "initial simplex should have {} vertices given x dimension ({n}), got {}",
+                n + 1,
+                vertices.len()
+            )));
+        }
+        if let Some(k) = vertices.iter().position(|v| v.len() != n) {
+            return Err(invalid_parameter(format!(
+                "initial simplex vertex {k} dimension ({}) should be equal to x dimension ({n})",
+                vertices[k].len()
+            )));
+        }
+        let (tol, perturb) = match self.degenerate_simplex {
+            DegenerateSimplex::Error(tol) => (tol, false),
+            DegenerateSimplex::Perturb(tol) => (tol, true),
+        };
+        if let Err(k) = orthogonalized(vertices, tol, perturb) {
+            return Err(invalid_parameter(format!(
+                "initial simplex is degenerate: vertex {k} is affinely dependent on the previous ones"
+            )));
+        }
+        if invert(&simplex_displacements(vertices, |v| v.to_vec()), n).is_none() {
+            return Err(invalid_parameter(
+                "initial simplex is degenerate: vertices should be affinely independent",
+            ));
+        }
+        self.x0 = vertices[0].clone();
+        Ok(())
+    }
+
+    /// Checks user scaling size and values
+    fn check_scaling(&self) -> Result<(), argmin::core::Error> {
+        if let Some(scaling) = self.scaling.as_ref() {
+            if scaling.len() != self.x0.len() {
+                return Err(invalid_parameter(format!(
+                    "scaling size ({}) should be equal to x dimension ({})",
+                    scaling.len(),
+                    self.x0.len()
+                )));
+            }
+            if let Some(i) = scaling.iter().position(|s| *s == 0. || !s.is_finite()) {
+                return Err(invalid_parameter(format!(
+                    "scaling of x[{i}] should be finite and non zero, got {}",
+                    scaling[i]
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks transforms size and that the initial guess is within their domain
+    fn check_transforms(&self) -> Result<(), argmin::core::Error> {
+        let Some(transforms) = self.transforms.as_ref() else {
+            return Ok(());
+        };
+        if transforms.len() != self.x0.len() {
+            return Err(invalid_parameter(format!(
+                "transforms size ({}) should be equal to x dimension ({})",
+                transforms.len(),
+                self.x0.len()
+            )));
+        }
+        for (i, (t, xi)) in transforms.iter().zip(&self.x0).enumerate() {
+            let (lower, upper) = self.component_bounds(i);
+            match t {
+                VariableTransform::Identity => (),
+                VariableTransform::Log if !(*xi > 0. && xi.is_finite()) => {
+                    return Err(invalid_parameter(format!(
+                        "x[{i}] ({xi}) should be finite and strictly positive with a log transform"
+                    )));
                 }
-                return TerminationStatus::Terminated(argmin::core::TerminationReason::SolverExit(
-                    reason,
-                ));
+                VariableTransform::Logit if !(lower.is_finite() && upper.is_finite()) => {
+                    return Err(invalid_parameter(format!(
+                        "bounds of x[{i}] ({lower}, {upper}) should be finite with a logit transform"
+                    )));
+                }
+                VariableTransform::Logit if !(lower < *xi && *xi < upper) => {
+                    return Err(invalid_parameter(format!(
+                        "x[{i}] ({xi}) should be strictly within its bounds ({lower}, {upper}) with a logit transform"
+                    )));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the bounds of the `i`-th x component, infinite when not bounded
+    fn component_bounds(&self, i: usize) -> (f64, f64) {
+        let bounds = self.bounds.as_ref();
+        (
+            bounds
+                .and_then(|(lower, _)| lower.get(i).copied())
+                .unwrap_or(f64::NEG_INFINITY),
+            bounds
+                .and_then(|(_, upper)| upper.get(i).copied())
+                .unwrap_or(f64::INFINITY),
+        )
+    }
+
+    /// Checks user constraint scaling size and values against the cost function output size
+    fn check_constraint_scaling(&self, nout: usize) -> Result<(), argmin::core::Error> {
+        if let Some(scales) = self.constraint_scaling.as_ref() {
+            if scales.len() != nout - 1 {
+                return Err(invalid_parameter(format!(
+                    "constraint scaling size ({}) should be equal to the number of constraints ({}) given cost function output size ({})",
+                    scales.len(),
+                    nout - 1,
+                    nout
+                )));
+            }
+            if let Some(k) = scales.iter().position(|s| !(*s > 0. && s.is_finite())) {
+                return Err(invalid_parameter(format!(
+                    "scaling of constraint {k} should be finite and strictly positive, got {}",
+                    scales[k]
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks equality constraints declaration against the cost function output size
+    fn check_eq_constraints(&self, nout: usize) -> Result<(), argmin::core::Error> {
+        for (k, eq) in self.eq_constraints.iter().enumerate() {
+            if eq.index == 0 || eq.index >= nout {
+                return Err(invalid_parameter(format!(
+                    "equality constraint index {} should be in [1, {}] given cost function output size ({})",
+                    eq.index,
+                    nout - 1,
+                    nout
+                )));
+            }
+            if self.eq_constraints[..k].iter().any(|e| e.index == eq.index) {
+                return Err(invalid_parameter(format!(
+                    "equality constraint index {} is declared twice",
+                    eq.index
+                )));
+            }
+            if eq.tol <= 0. {
+                return Err(invalid_parameter(format!(
+                    "equality constraint tolerance {} should be strictly positive (index {})",
+                    eq.tol, eq.index
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of constraints given to COBYLA for a cost function output of size `nout`
+    fn nb_constraints(&self, nout: usize) -> usize {
+        nout - 1 + self.eq_constraints.len() + self.nb_bound_constraints()
+    }
+
+    /// Returns x clamped within the bounds when [CobylaSolverBuilder::clamp_to_bounds] is set
+    fn clamped(&self, x: &[f64]) -> Option<Vec<f64>> {
+        self.projected(x).filter(|_| self.clamp_to_bounds)
+    }
+
+    /// Returns x clamped within the bounds, None without bounds
+    fn projected(&self, x: &[f64]) -> Option<Vec<f64>> {
+        let (lower, upper) = self.bounds.as_ref()?;
+        Some(
+            x.iter()
+                .zip(lower.iter().zip(upper))
+                .map(|(xi, (lo, up))| xi.clamp(*lo, *up))
+                .collect(),
+        )
+    }
+
+    /// Replaces the pending geometry step of COBYLA to `x` (of components `xu`), out of the
+    /// bounds, by the opposite one when it is within them, otherwise by the one of both
+    /// projected onto the bounds which degrades the simplex the least, and returns the
+    /// components of the new point (see [CobylaSolverBuilder::reject_out_of_bounds]). The
+    /// step is kept in the unlikely case where both projections make it degenerate.
+    fn bounded_geometry_step(
+        &self,
+        ctx: *mut cobyla_context_t,
+        x: &mut [f64],
+        xu: Vec<f64>,
+    ) -> Vec<f64> {
+        let n = x.len();
+        let (best, simi) = unsafe {
+            let sim = std::slice::from_raw_parts((*ctx).sim, n * (n + 1));
+            let simi = std::slice::from_raw_parts((*ctx).simi, n * n);
+            let jdrop = (*ctx).jdrop as usize - 1;
+            let row: Vec<f64> = (0..n).map(|i| simi[jdrop + n * i]).collect();
+            (sim[n * n..].to_vec(), row)
+        };
+        let reversed: Vec<f64> = best.iter().zip(&*x).map(|(b, xi)| 2. * b - xi).collect();
+        let reversed_u = self.unscaled(&reversed);
+        let candidates = if self.is_rejected(&reversed_u) {
+            [xu.clone(), reversed_u]
+                .iter()
+                .filter_map(|xc| self.projected(xc))
+                .collect()
+        } else {
+            vec![reversed_u]
+        };
+        // Simplex volume relatively to the one given by the original step, whose inner
+        // product with the row of the inverse of the replaced vertex is 1
+        let volume = |xs: &[f64]| {
+            simi.iter()
+                .zip(xs.iter().zip(&best))
+                .map(|(si, (xi, bi))| si * (xi - bi))
+                .sum::<f64>()
+                .abs()
+        };
+        let Some((xs, xc)) = candidates
+            .into_iter()
+            .map(|xc| (self.scaled(&xc), xc))
+            .filter(|(xs, _)| volume(xs) > 0.)
+            .max_by(|(a, _), (b, _)| volume(a).total_cmp(&volume(b)))
+        else {
+            return xu;
+        };
+        unsafe { cobyla_set_geometry_step(ctx, xs.as_ptr()) };
+        x.copy_from_slice(&xs);
+        xc
+    }
+
+    /// Returns whether x is out of the bounds and has to be rejected without evaluation
+    /// (see [CobylaSolverBuilder::reject_out_of_bounds])
+    fn is_rejected(&self, x: &[f64]) -> bool {
+        self.bounds
+            .as_ref()
+            .filter(|_| self.reject_out_of_bounds)
+            .is_some_and(|(lower, upper)| {
+                x.iter()
+                    .zip(lower.iter().zip(upper))
+                    .any(|(xi, (lo, up))| xi < lo || xi > up)
+            })
+    }
+
+    /// Returns the number of constraints resulting from finite bounds
+    fn nb_bound_constraints(&self) -> usize {
+        self.bounds.as_ref().map_or(0, |(lower, upper)| {
+            lower.iter().chain(upper).filter(|b| b.is_finite()).count()
+        })
+    }
+
+    /// Returns constraint values given to COBYLA: (scaled) constraint values of the cost
+    /// function, followed by the second inequality of equality constraints, followed
+    /// by the ones resulting from bounds at x
+    fn cobyla_constraints(&self, x: &[f64], cost: &[f64]) -> Vec<f64> {
+        let mut c: Vec<f64> = cost[1..]
+            .iter()
+            .enumerate()
+            .map(|(k, ck)| self.constraint_sign(k) * ck)
+            .collect();
+        if let Some(scales) = self.constraint_scaling.as_ref() {
+            c.iter_mut().zip(scales).for_each(|(ci, s)| *ci /= s);
+        }
+        for eq in self.eq_constraints.iter() {
+            let h = c[eq.index - 1];
+            c[eq.index - 1] = h + eq.tol;
+            c.push(eq.tol - h);
+        }
+        if let Some((lower, upper)) = self.bounds.as_ref() {
+            for (i, xi) in x.iter().enumerate() {
+                if lower[i].is_finite() {
+                    c.push(xi - lower[i]);
+                }
+                if upper[i].is_finite() {
+                    c.push(upper[i] - xi);
+                }
+            }
+        }
+        c
+    }
+
+    /// Restarts COBYLA with constraints and a new simplex (the custom one if `custom`) from
+    /// the given point and its cost function output, at the end of the feasibility
+    /// restoration phase or when the simplex has degenerated (see
+    /// [CobylaSolverBuilder::max_restarts])
+    fn restart_from<P, C>(
+        &mut self,
+        state: CobylaState<P, C>,
+        x: Vec<f64>,
+        fx: Vec<f64>,
+        context: Option<C>,
+        custom: bool,
+    ) -> CobylaState<P, C>
+    where
+        P: CobylaParam,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let mut state = state;
+        self.create_context(&mut state, self.nb_constraints(fx.len()), custom);
+        self.set_start_point(&x);
+        let violation = self.max_violation(&x, &fx);
+        let mut state = state.param(P::from_components(x)).cost(fx);
+        state.context = context;
+        state.max_violation = Some(violation);
+        state
+    }
+
+    /// Restarts COBYLA with constraints from the given point and its cost function output
+    /// with the given trust region radii and an axis aligned simplex, to reduce the
+    /// constraint violation of a converged point (see [CobylaSolverBuilder::feasible_stop])
+    fn refine_from<P, C>(
+        &mut self,
+        state: CobylaState<P, C>,
+        x: Vec<f64>,
+        fx: Vec<f64>,
+        rhobeg: f64,
+        rhoend: f64,
+    ) -> CobylaState<P, C>
+    where
+        P: CobylaParam,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let mut state = state;
+        let m = self.nb_constraints(fx.len());
+        self.xtol_stop = false;
+        self.new_context(&mut state, x.len(), m, rhobeg, rhoend, false);
+        self.set_start_point(&x);
+        let violation = self.max_violation(&x, &fx);
+        let mut state = state.param(P::from_components(x)).cost(fx);
+        state.context = None;
+        state.max_violation = Some(violation);
+        state
+    }
+
+    /// Terminates the state when the iteration, time or target cost budgets are reached
+    /// once it is updated, so that the solver knows the iteration is the last one
+    fn budget_terminated<P, C>(&self, state: CobylaState<P, C>) -> CobylaState<P, C>
+    where
+        P: CobylaParam,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        if state.status.is_some() || state.terminated() {
+            return state;
+        }
+        let timeout = matches!((self.max_time, self.start), (Some(max_time), Some(start)) if start.elapsed() >= max_time);
+        if state.get_iter() + 1 >= state.get_max_iters() {
+            state.terminate_with(TerminationReason::MaxItersReached)
+        } else if timeout {
+            state.terminate_with(TerminationReason::Timeout)
+        } else if state.get_cost() <= state.get_target_cost() {
+            state.terminate_with(TerminationReason::TargetCostReached)
+        } else {
+            state
+        }
+    }
+
+    /// Moves the state to the evaluated point picked by the best selector when the
+    /// optimization terminates (see [CobylaSolverBuilder::best_selector])
+    fn selected_best<P, C>(
+        &self,
+        state: CobylaState<P, C>,
+    ) -> Result<CobylaState<P, C>, argmin::core::Error>
+    where
+        P: CobylaParam,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let Some(selector) = self.best_selector.as_ref() else {
+            return Ok(state);
+        };
+        let terminating = state.status.is_some() || state.terminated();
+        let evaluations = state.evaluations();
+        if !terminating || evaluations.is_empty() {
+            return Ok(state);
+        }
+        let i = (selector.0)(evaluations);
+        let Some((x, fx)) = evaluations.get(i).cloned() else {
+            return Err(invalid_parameter(format!(
+                "best selector index ({i}) should be less than the number of evaluations ({})",
+                evaluations.len()
+            )));
+        };
+        let violation = self.max_violation(&x, &fx);
+        let mut state = state.param(P::from_components(x)).cost(fx);
+        state.max_violation = Some(violation);
+        state.context = None;
+        Ok(state)
+    }
+
+    /// Starts polishing the feasibility of the current infeasible point, the result of the
+    /// optimization terminated with the given status (see
+    /// [CobylaSolverBuilder::polish_feasibility])
+    fn polish_from<P, C>(&mut self, state: &mut CobylaState<P, C>, status: SuccessStatus)
+    where
+        P: CobylaParam,
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let (Some(x), Some(fx)) = (state.param.as_ref(), state.cost.clone()) else {
+            return;
+        };
+        let x = x.components().into_owned();
+        let rho = state.rho;
+        self.new_context(state, x.len(), 0, rho, 0.01 * rho, false);
+        if matches!(state.status, Some(Err(_))) {
+            // Context creation failure
+            return;
+        }
+        self.set_start_point(&x);
+        self.restoration = Some(Restoration {
+            violation: self.max_violation(&x, &fx),
+            x,
+            fx,
+        });
+        self.polishing = Some(status);
+        state.status = None;
+    }
+
+    /// Returns the maximum constraint violation at x given the cost function output,
+    /// taking bounds and equality constraints (relaxed by their tolerance) into account
+    fn max_violation(&self, x: &[f64], cost: &[f64]) -> f64 {
+        max_constraint_violation(&self.cobyla_constraints(x, cost))
+    }
+
+    /// Stops the optimization with the given status, moving the state to the best point
+    /// of the simplex (copied in `x`), see [CobylaSolver::best_point], or to the least
+    /// infeasible point when restoring feasibility
+    fn stop_at_best<P: CobylaParam, C: Clone>(
+        &self,
+        ctx: *mut cobyla_context_t,
+        state: CobylaState<P, C>,
+        x: &mut [f64],
+        nout: usize,
+        m: usize,
+        status: Result<SuccessStatus, FailStatus>,
+    ) -> CobylaState<P, C> {
+        let (xb, best) = match self.restoration.as_ref() {
+            Some(r) => {
+                x.copy_from_slice(&self.scaled(&r.x));
+                (r.x.clone(), r.fx.clone())
+            }
+            None => {
+                let best = self.best_point(ctx, x, nout, m);
+                (self.unscaled(x), best)
+            }
+        };
+        let mut state = state.param(P::from_components(xb.clone())).cost(best);
+        state.max_violation = Some(self.max_violation(&xb, state.get_full_cost().unwrap()));
+        // User context of the best point is not kept by COBYLA
+        state.context = None;
+        state.cobyla_context = None;
+        state.status = Some(status);
+        self.feasible_result(state)
+    }
+
+    /// Moves the state to the best feasible point when the current one is infeasible
+    /// and feasible points are preferred (see [CobylaSolverBuilder::prefer_feasible])
+    fn feasible_result<P: CobylaParam, C: Clone>(
+        &self,
+        state: CobylaState<P, C>,
+    ) -> CobylaState<P, C> {
+        if !self.prefer_feasible
+            || state
+                .max_violation
+                .is_none_or(|violation| violation <= self.feasibility_tol)
+        {
+            return state;
+        }
+        let Some((x, fx)) = state.best_feasible.clone() else {
+            return state;
+        };
+        let violation = self.max_violation(&x, &fx);
+        let mut state = state.param(P::from_components(x)).cost(fx);
+        state.max_violation = Some(violation);
+        state.context = None;
+        state
+    }
+
+    /// Copies the best point of the simplex in `x` and returns the corresponding cost function
+    /// output (of size `nout`) retrieved from the `m` constraint values handled by COBYLA
+    fn best_point(
+        &self,
+        ctx: *mut cobyla_context_t,
+        x: &mut [f64],
+        nout: usize,
+        m: usize,
+    ) -> Vec<f64> {
+        self.vertex(ctx, x.len(), x, nout, m)
+    }
+
+    /// Copies the `j`-th vertex of the simplex in `x` and returns the corresponding cost
+    /// function output (of size `nout`) retrieved from the `m` constraint values handled by
+    /// COBYLA, the best vertex being the `n`-th one
+    fn vertex(
+        &self,
+        ctx: *mut cobyla_context_t,
+        j: usize,
+        x: &mut [f64],
+        nout: usize,
+        m: usize,
+    ) -> Vec<f64> {
+        let mut fx = vec![0.; m + 1];
+        unsafe { cobyla_get_vertex(ctx, j as libc::c_long, x.as_mut_ptr(), fx.as_mut_ptr()) };
+        fx.truncate(nout);
+        fx[0] = self.objective(fx[0]);
+        for eq in self.eq_constraints.iter() {
+            fx[eq.index] -= eq.tol;
+        }
+        if let Some(scales) = self.constraint_scaling.as_ref() {
+            fx[1..].iter_mut().zip(scales).for_each(|(ci, s)| *ci *= s);
+        }
+        for (k, ck) in fx[1..].iter_mut().enumerate() {
+            *ck *= self.constraint_sign(k);
+        }
+        fx
+    }
+
+    /// Returns the factor turning the `k`-th cost function constraint into a `c(x) >= 0` one
+    fn constraint_sign(&self, k: usize) -> f64 {
+        self.constraint_senses
+            .as_ref()
+            .and_then(|senses| senses.get(k).copied())
+            .unwrap_or(self.constraint_sense)
+            .sign()
+    }
+
+    /// Checks constraint senses against the cost function output size
+    fn check_constraint_senses(&self, nout: usize) -> Result<(), argmin::core::Error> {
+        match self.constraint_senses.as_ref() {
+            Some(senses) if senses.len() != nout - 1 => Err(invalid_parameter(format!(
+                "constraint senses size ({}) should be equal to the number of constraints ({}) given cost function output size ({nout})",
+                senses.len(),
+                nout - 1,
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the objective value target is reached by a feasible evaluated point
+    fn stopval_reached(&self, f: f64, violation: f64) -> bool {
+        self.stopval.is_some_and(|stopval| {
+            self.objective(f) <= self.objective(stopval) && violation <= self.feasibility_tol
+        })
+    }
+
+    /// Converts an objective value to the minimized one and conversely
+    fn objective(&self, f: f64) -> f64 {
+        match self.sense {
+            Sense::Minimize => f,
+            Sense::Maximize => -f,
+        }
+    }
+
+    /// Returns whether the user callback requests to stop given evaluated x and objective value
+    fn forced_stop(&self, x: &[f64], f: f64) -> bool {
+        self.stop_callback.as_ref().is_some_and(|callback| {
+            let mut callback = callback.0.lock().unwrap_or_else(|e| e.into_inner());
+            callback(x, f)
+        })
+    }
+
+    /// Returns the sample standard deviations of the repeated cost function evaluations
+    /// at x, None when not resampled
+    fn sample_std_at(&self, x: &[f64]) -> Option<Vec<f64>> {
+        self.sample_stds
+            .iter()
+            .rev()
+            .find(|(xk, _)| same_point(xk, x))
+            .map(|(_, std)| std.clone())
+    }
+
+    /// Returns the sample standard deviations of the repeated cost function evaluations
+    /// at the current parameter vector of the state, None when not resampled
+    fn current_sample_std<P: CobylaParam, C>(&self, state: &CobylaState<P, C>) -> Option<Vec<f64>> {
+        let x = state.param.as_ref()?;
+        self.sample_std_at(&x.components())
+    }
+
+    /// Replaces NaN constraint values of the cost function output according to
+    /// the NaN constraint policy, recording whether a value is substituted
+    fn nan_constraints_replaced(&mut self, mut fx: Vec<f64>) -> Vec<f64> {
+        self.substituted = false;
+        if let NanConstraintPolicy::Violation(violation) = self.nan_constraint_policy {
+            for (k, v) in fx.iter_mut().enumerate().skip(1) {
+                if v.is_nan() {
+                    *v = -self.constraint_sign(k - 1) * violation;
+                    self.substituted = true;
+                }
+            }
+        }
+        fx
+    }
+
+    /// Replaces non-finite values of the cost function output: objective by `penalty`
+    /// and constraints by the violation `penalty`, recording the substitution
+    fn penalized(&mut self, fx: Vec<f64>, penalty: f64) -> Vec<f64> {
+        self.substituted = true;
+        let f_penalty = self.objective(penalty);
+        fx.into_iter()
+            .enumerate()
+            .map(|(i, v)| match (v.is_finite(), i) {
+                (true, _) => v,
+                (false, 0) => f_penalty,
+                (false, _) => -self.constraint_sign(i - 1) * penalty,
+            })
+            .collect()
+    }
+
+    /// Returns the changes of the `n` x components given the trust region radius `rho`
+    fn component_changes(&self, rho: f64, n: usize) -> Vec<f64> {
+        match self.scale.as_ref() {
+            Some(s) => s.iter().map(|si| rho * si.abs()).collect(),
+            None => vec![rho; n],
+        }
+    }
+
+    /// Sets the start point of a COBYLA run given in user space, its scaled components
+    /// being computed once so that the start point is given back exactly when unscaled
+    fn set_start_point(&mut self, x: &[f64]) {
+        self.start_point = None;
+        self.start_point = (self.scale.is_some() || self.transforms.is_some())
+            .then(|| (self.scaled(x), x.to_vec()));
+    }
+
+    /// Transforms x from user space (see [CobylaSolverBuilder::with_transforms])
+    fn transformed(&self, x: &[f64]) -> Vec<f64> {
+        match self.transforms.as_ref() {
+            Some(t) => x
+                .iter()
+                .zip(t)
+                .enumerate()
+                .map(|(i, (xi, ti))| ti.apply(*xi, self.component_bounds(i)))
+                .collect(),
+            None => x.to_vec(),
+        }
+    }
+
+    /// Transforms back x into user space (see [CobylaSolverBuilder::with_transforms])
+    fn untransformed(&self, u: Vec<f64>) -> Vec<f64> {
+        match self.transforms.as_ref() {
+            Some(t) => u
+                .iter()
+                .zip(t)
+                .enumerate()
+                .map(|(i, (ui, ti))| ti.invert(*ui, self.component_bounds(i)))
+                .collect(),
+            None => u,
+        }
+    }
+
+    /// Transforms and scales x from user space to the space where COBYLA is run
+    fn scaled(&self, x: &[f64]) -> Vec<f64> {
+        if let Some((xs, x0)) = self.start_point.as_ref()
+            && x0 == x
+        {
+            return xs.clone();
+        }
+        let u = self.transformed(x);
+        match self.scale.as_ref() {
+            Some(s) => u.iter().zip(s).map(|(ui, si)| ui / si).collect(),
+            None => u,
+        }
+    }
+
+    /// Unscales and transforms back x from the space where COBYLA is run to user space
+    fn unscaled(&self, xs: &[f64]) -> Vec<f64> {
+        if let Some((xs0, x)) = self.start_point.as_ref()
+            && xs0 == xs
+        {
+            return x.clone();
+        }
+        let u = match self.scale.as_ref() {
+            Some(s) => xs.iter().zip(s).map(|(xi, si)| xi * si).collect(),
+            None => xs.to_vec(),
+        };
+        self.untransformed(u)
+    }
+}
+
+/// Builder of [CobylaSolver]
+///
+/// ```
+/// use cobyla_argmin::{CobylaSolver, RhoBeg, StopTols};
+///
+/// let solver = CobylaSolver::builder()
+///     .initial_param(vec![1., 1.])
+///     .rhobeg(RhoBeg::All(0.5))
+///     .rhoend(1e-3)
+///     .stop_tols(StopTols {
+///         ftol_rel: 1e-6,
+///         ..StopTols::default()
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CobylaSolverBuilder {
+    x0: Vec<f64>,
+    rhobeg: Option<RhoBeg>,
+    rhobeg_floor: Option<f64>,
+    rhoend: Option<f64>,
+    rho_reduction: Option<f64>,
+    initial_penalty: Option<f64>,
+    simplex_init: SimplexInit,
+    degenerate_simplex: DegenerateSimplex,
+    stop_tols: StopTols,
+    bounds: Option<(Vec<f64>, Vec<f64>)>,
+    variable_names: Option<Vec<String>>,
+    scaling: Option<Vec<f64>>,
+    transforms: Option<Vec<VariableTransform>>,
+    constraint_scaling: Option<Vec<f64>>,
+    eq_constraints: Vec<EqualityConstraint>,
+    constraint_sense: ConstraintSense,
+    constraint_senses: Option<Vec<ConstraintSense>>,
+    added_constraints: Vec<AddedConstraint>,
+    iprint: Option<i32>,
+    non_finite_policy: NonFinitePolicy,
+    nan_constraint_policy: NanConstraintPolicy,
+    max_time: Option<Duration>,
+    max_fun_evals: Option<u64>,
+    stopval: Option<f64>,
+    sense: Sense,
+    stagnation_window: Option<(usize, f64)>,
+    interior_stop: Option<(f64, f64)>,
+    stop_callback: Option<StopCallback>,
+    progress_callback: Option<ProgressCallback>,
+    best_selector: Option<BestSelector>,
+    output: Option<Output>,
+    cache: Option<EvalCache>,
+    resample: Option<usize>,
+    keep_history: bool,
+    keep_evaluations: bool,
+    keep_simplex: bool,
+    catch_panics: Option<bool>,
+    batch_initial_simplex: bool,
+    clamp_to_bounds: bool,
+    reject_out_of_bounds: bool,
+    num_constraints: Option<usize>,
+    observe_constraints: bool,
+    warm_start: Option<Option<CobylaContext>>,
+    restore_feasibility: bool,
+    feasibility_only: bool,
+    max_restarts: usize,
+    prefer_feasible: bool,
+    feasible_stop: bool,
+    polish_feasibility: bool,
+    feasibility_tol: Option<f64>,
+}
+
+impl CobylaSolverBuilder {
+    /// Set initial guess for x value
+    ///
+    /// Any [CobylaParam] type can be used, the solver has then to be run on a problem
+    /// using the same `Param` type. When not given, the initial guess is the center of
+    /// the bounds which have then to be finite (see [CobylaSolverBuilder::with_bounds]).
+    #[must_use]
+    pub fn initial_param<P: CobylaParam>(mut self, x0: P) -> Self {
+        self.x0 = x0.into_components();
+        self
+    }
+
+    /// Set initial change of x (aka `rhobeg`).
+    ///
+    /// When components are set with different values ([RhoBeg::Set]), COBYLA is run
+    /// in a scaled space where the initial change is the same for all components.
+    #[must_use]
+    pub fn rhobeg(mut self, rhobeg: RhoBeg) -> Self {
+        self.rhobeg = Some(rhobeg);
+        self
+    }
+
+    /// Set the minimum initial change of x components used with [RhoBeg::Relative]
+    /// (default: [RHOBEG_FLOOR]), should be strictly positive
+    #[must_use]
+    pub fn rhobeg_floor(mut self, rhobeg_floor: f64) -> Self {
+        self.rhobeg_floor = Some(rhobeg_floor);
+        self
+    }
+
+    /// Set final value of the trust region radius (aka `rhoend`).
+    ///
+    /// The algorithm stops when the radius reaches this value, hence it controls the
+    /// final accuracy on x: the larger, the fewer function evaluations.
+    /// Tolerances on x given with [StopTols] are also enforced through the radius:
+    /// the actual final radius is the maximum of `rhoend`, `xtol_rel * rhobeg`
+    /// and `xtol_abs` components, so the loosest of these criteria fires first, unless
+    /// they are combined with [TolCombine::All] (see [StopTols::xtol_combine]).
+    #[must_use]
+    pub fn rhoend(mut self, rhoend: f64) -> Self {
+        self.rhoend = Some(rhoend);
+        self
+    }
+
+    /// Set the factor applied to the trust region radius each time it is reduced
+    /// (default: [RHO_REDUCTION], the value of Powell's code), should be strictly
+    /// between 0 and 1.
+    ///
+    /// A smaller factor reaches `rhoend` in fewer reductions (less evaluations but a coarser
+    /// exploration at each radius), a larger one gives a gentler reduction. The radius is
+    /// set to `rhoend` when a reduction gives a value less than `1.5 * rhoend`.
+    #[must_use]
+    pub fn rho_reduction(mut self, rho_reduction: f64) -> Self {
+        self.rho_reduction = Some(rho_reduction);
+        self
+    }
+
+    /// Set the initial value of the penalty parameter `mu` of the merit function
+    /// `f(x) + mu * max(0, -c(x))` used by COBYLA to compare points, should be strictly
+    /// positive (default: 0 as in Powell's code).
+    ///
+    /// COBYLA increases the penalty whenever it is too small to make the predicted merit
+    /// reduction positive, and may decrease it when the trust region radius is reduced.
+    /// An initial value of the order of the objective change per unit of constraint
+    /// violation avoids wasted early iterations.
+    #[must_use]
+    pub fn initial_penalty(mut self, initial_penalty: f64) -> Self {
+        self.initial_penalty = Some(initial_penalty);
+        self
+    }
+
+    /// Set the construction of the initial simplex (default: [SimplexInit::AxisAligned]).
+    ///
+    /// With [SimplexInit::Custom], the first vertex replaces the initial guess and the
+    /// displacements of the other vertices from it are also used when the optimization is
+    /// restarted after the feasibility restoration phase (see
+    /// [CobylaSolverBuilder::restore_feasibility]).
+    #[must_use]
+    pub fn simplex_init(mut self, simplex_init: SimplexInit) -> Self {
+        self.simplex_init = simplex_init;
+        self
+    }
+
+    /// Set the handling of a degenerate [SimplexInit::Custom] initial simplex
+    /// (default: [DegenerateSimplex::Error] with a zero tolerance, ie only exactly
+    /// degenerate simplices are rejected).
+    #[must_use]
+    pub fn degenerate_simplex(mut self, degenerate_simplex: DegenerateSimplex) -> Self {
+        self.degenerate_simplex = degenerate_simplex;
+        self
+    }
+
+    /// Set tolerances used as termination criteria
+    #[must_use]
+    pub fn stop_tols(mut self, stop_tols: StopTols) -> Self {
+        self.stop_tols = stop_tols;
+        self
+    }
+
+    /// Set bounds of x components: `lower[i] <= x[i] <= upper[i]`.
+    ///
+    /// Bounds are handled as additional constraints given to COBYLA, use
+    /// `f64::NEG_INFINITY` or `f64::INFINITY` for an unbounded component side.
+    /// The initial guess has to lie within the bounds, it defaults to the center of the
+    /// bounds when not given, all bounds being then required to be finite.
+    #[must_use]
+    pub fn with_bounds<V: Into<Vec<f64>>>(mut self, lower: V, upper: V) -> Self {
+        self.bounds = Some((lower.into(), upper.into()));
+        self
+    }
+
+    /// Set x components from named variables given as (name, initial value, optional
+    /// `(lower, upper)` bounds) tuples, replacing the initial guess and the bounds (see
+    /// [CobylaSolverBuilder::with_bounds]).
+    ///
+    /// Components without bounds are unbounded, no bounds are set when none is given.
+    /// Names should be unique, they are given to the state to report the best parameter
+    /// vector by name (see [CobylaState::best_param_named]).
+    ///
+    /// ```
+    /// use cobyla_argmin::CobylaSolver;
+    ///
+    /// let solver = CobylaSolver::builder()
+    ///     .with_named_variables([
+    ///         ("length", 1., Some((0.5, 2.))),
+    ///         ("angle", 0.1, None),
+    ///     ])
+    ///     .build();
+    /// assert_eq!(solver.config().x0, vec![1., 0.1]);
+    /// ```
+    #[must_use]
+    pub fn with_named_variables<S: Into<String>>(
+        mut self,
+        variables: impl IntoIterator<Item = (S, f64, Option<(f64, f64)>)>,
+    ) -> Self {
+        let mut names = Vec::new();
+        let mut x0 = Vec::new();
+        let (mut lower, mut upper) = (Vec::new(), Vec::new());
+        let mut bounded = false;
+        for (name, xi, bounds) in variables {
+            names.push(name.into());
+            x0.push(xi);
+            bounded |= bounds.is_some();
+            let (lo, up) = bounds.unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+            lower.push(lo);
+            upper.push(up);
+        }
+        self.x0 = x0;
+        self.bounds = bounded.then_some((lower, upper));
+        self.variable_names = Some(names);
+        self
+    }
+
+    /// Clamp the points proposed by COBYLA within the bounds before calling the cost
+    /// function (default: false), for cost functions which cannot be evaluated outside
+    /// of the bounds (see [CobylaSolverBuilder::with_bounds]).
+    ///
+    /// As bounds are handled as constraints, COBYLA may propose points slightly out of
+    /// them. With clamping, the cost function is evaluated at the projected point while
+    /// COBYLA keeps the unclamped one with its bound constraints violation: the returned
+    /// best parameter may then still be slightly out of the bounds. The simplex values
+    /// are those of the projected points, which can slightly bias the linear
+    /// approximations of the objective and constraints near the bounds.
+    ///
+    /// With [CobylaSolverBuilder::reject_out_of_bounds], only the initial guess and the
+    /// initial simplex vertices may be clamped, the other points being moved within the
+    /// bounds or rejected without evaluation.
+    #[must_use]
+    pub fn clamp_to_bounds(mut self, clamp_to_bounds: bool) -> Self {
+        self.clamp_to_bounds = clamp_to_bounds;
+        self
+    }
+
+    /// Reject the points proposed by COBYLA out of the bounds once the initial simplex is
+    /// built (default: false), so that the cost function is never evaluated out of them
+    /// (see [CobylaSolverBuilder::with_bounds]).
+    ///
+    /// A trust region step out of the bounds is not evaluated and does not count as a cost
+    /// function evaluation: COBYLA handles it as an unsuccessful step, keeping its simplex
+    /// and shrinking the trust region, while the state stays at the best point. The
+    /// rejection is recorded in [CobylaState::trust_region_step] (see
+    /// [CobylaSolverBuilder::keep_simplex]). A step improving the simplex geometry out of
+    /// the bounds is replaced by the opposite one, which improves it as well, or when the
+    /// latter is also out of them, by the one of both projected onto the bounds which
+    /// degrades the simplex the least.
+    ///
+    /// The initial guess and the initial simplex vertices are still evaluated where they
+    /// are, or clamped when [CobylaSolverBuilder::clamp_to_bounds] is set: both options do
+    /// not conflict and can be combined for cost functions which cannot be evaluated out
+    /// of the bounds. Without bounds, the option has no effect.
+    #[must_use]
+    pub fn reject_out_of_bounds(mut self, reject_out_of_bounds: bool) -> Self {
+        self.reject_out_of_bounds = reject_out_of_bounds;
+        self
+    }
+
+    /// Declare the number of constraints returned by the cost function after the objective
+    /// value (default: inferred from the evaluation at the initial guess).
+    ///
+    /// The COBYLA working arrays are then allocated and the constraint settings checked
+    /// before any cost function evaluation, so that a failure (see
+    /// [CobylaSolver::memory_footprint]) does not waste an expensive evaluation. The
+    /// optimization stops with an error when the cost function output does not match.
+    #[must_use]
+    pub fn with_num_constraints(mut self, num_constraints: usize) -> Self {
+        self.num_constraints = Some(num_constraints);
+        self
+    }
+
+    /// Set scaling of x components: COBYLA is run on the scaled variables `y = x / scaling`,
+    /// x being unscaled before each cost function call and for the returned best parameter.
+    ///
+    /// A scaling value should be of the order of magnitude of the corresponding x component
+    /// change. With scaling, [RhoBeg::All] value is the initial change of the scaled
+    /// variables (ie `x[i]` changes by `rhobeg * scaling[i]`) while [RhoBeg::Set] still gives
+    /// the initial changes of x components.
+    #[must_use]
+    pub fn with_scaling<V: Into<Vec<f64>>>(mut self, scaling: V) -> Self {
+        self.scaling = Some(scaling.into());
+        self
+    }
+
+    /// Set transforms of x components: COBYLA is run on the transformed variables
+    /// (eg `ln(x[i])` with [VariableTransform::Log]), x being transformed back before each
+    /// cost function call and for the returned best parameter. Transforms are applied
+    /// before scaling (see [CobylaSolverBuilder::with_scaling]), one per x component.
+    ///
+    /// Contrary to the constraints `x[i] >= 0` or to the bounds which COBYLA may violate
+    /// transiently, the domain of a transform (strictly positive values with
+    /// [VariableTransform::Log], within the finite bounds of the component with
+    /// [VariableTransform::Logit]) holds at each cost function call by construction,
+    /// the initial guess has to be within it.
+    ///
+    /// The initial and final changes of x ([CobylaSolverBuilder::rhobeg],
+    /// [CobylaSolverBuilder::rhoend]), the scaling and the absolute tolerances on x apply
+    /// to the transformed components, eg `rhobeg = 0.5` changes a log transformed
+    /// component by a factor `e^0.5`.
+    ///
+    /// ```
+    /// # use cobyla_argmin::{CobylaSolver, VariableTransform};
+    /// let solver = CobylaSolver::builder()
+    ///     .initial_param(vec![1., 0.5])
+    ///     .with_bounds(vec![0., 0.], vec![f64::INFINITY, 1.])
+    ///     .with_transforms(vec![VariableTransform::Log, VariableTransform::Logit])
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn with_transforms(mut self, transforms: Vec<VariableTransform>) -> Self {
+        self.transforms = Some(transforms);
+        self
+    }
+
+    /// Set scaling of the constraints returned by the cost function: COBYLA is given
+    /// `c[k] / scales[k]` for the `k`-th constraint (ie the `k+1`-th cost function output),
+    /// the objective is left unchanged.
+    ///
+    /// A scale value should be of the order of magnitude of the corresponding constraint
+    /// variation so that constraints of different units are weighted alike by COBYLA.
+    /// Scales should be finite and strictly positive, one per constraint.
+    ///
+    /// Constraint violations are then measured on the scaled constraints: the feasibility
+    /// tolerance (see [CobylaSolverBuilder::feasibility_tol]), the equality constraint
+    /// tolerances and the maximum constraint violations reported by the state (used by
+    /// [crate::CobylaState::is_feasible]) apply to scaled values, while the cost function
+    /// outputs recorded in the state are unscaled.
+    #[must_use]
+    pub fn with_constraint_scaling<V: Into<Vec<f64>>>(mut self, scales: V) -> Self {
+        self.constraint_scaling = Some(scales.into());
+        self
+    }
+
+    /// Set the convention of the inequality constraints computed by the cost function
+    /// (default: [ConstraintSense::GreaterEqual] ie `c(x) >= 0`).
+    ///
+    /// Constraints declared [ConstraintSense::LessEqual] (`c(x) <= 0`) are negated before
+    /// being given to COBYLA, the cost function outputs recorded in the state being left
+    /// unchanged. Constraints appended with [CobylaSolverBuilder::add_constraint] follow the
+    /// same convention.
+    #[must_use]
+    pub fn constraint_sense(mut self, sense: ConstraintSense) -> Self {
+        self.constraint_sense = sense;
+        self
+    }
+
+    /// Set the convention of each inequality constraint computed by the cost function
+    /// (`senses[k]` for the `k`-th constraint ie the `k+1`-th cost function output),
+    /// one per constraint, to mix `c(x) >= 0` and `c(x) <= 0` constraints
+    /// (see [CobylaSolverBuilder::constraint_sense]).
+    #[must_use]
+    pub fn with_constraint_senses(mut self, senses: Vec<ConstraintSense>) -> Self {
+        self.constraint_senses = Some(senses);
+        self
+    }
+
+    /// Declare equality constraints among the cost function output.
+    ///
+    /// By default, all values returned by the cost function except the first one (the objective)
+    /// are inequality constraints `c(x) >= 0`. Equality constraints `h(x) = 0` declared here
+    /// are expanded internally in two inequality constraints (see [EqualityConstraint]).
+    #[must_use]
+    pub fn with_equality_constraints(mut self, eq_constraints: Vec<EqualityConstraint>) -> Self {
+        self.eq_constraints = eq_constraints;
+        self
+    }
+
+    /// Add an inequality constraint `c(x) >= 0` computed from x components, its value
+    /// being appended to the cost function output after the ones of the previously added
+    /// constraints.
+    ///
+    /// A configured builder can thus be cloned and extended with the specific constraints
+    /// of each variant of a family of problems sharing the same cost function. Added
+    /// constraints are indexed after the cost function constraints (for
+    /// [CobylaSolverBuilder::with_equality_constraints] and
+    /// [CobylaSolverBuilder::with_constraint_scaling]) and are recorded in the state
+    /// along with them. Finite difference utilities of the state (see
+    /// [crate::CobylaState::estimate_jacobian]) only evaluate the cost function, hence
+    /// do not support them.
+    #[must_use]
+    pub fn add_constraint<F>(mut self, constraint: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        self.added_constraints
+            .push(AddedConstraint(Arc::new(constraint)));
+        self
+    }
+
+    /// Set level of printing of the COBYLA algorithm (default: [Verbosity::Summary])
+    #[must_use]
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.iprint = Some(verbosity.iprint());
+        self
+    }
+
+    /// Set level of printing of the COBYLA algorithm as the lower-level `iprint` integer
+    /// of the original code (0: no output, 1: final result, 2: each change of rho,
+    /// 3: each function evaluation), see [CobylaSolverBuilder::verbosity]
+    #[must_use]
+    pub fn iprint(mut self, iprint: i32) -> Self {
+        self.iprint = Some(iprint);
+        self
+    }
+
+    /// Set the writer of COBYLA printouts (default: stdout), for instance to integrate
+    /// them with a logging setup or to capture them.
+    /// Write errors are ignored as printouts are diagnostics only.
+    #[must_use]
+    pub fn output<W>(mut self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.output = Some(Output(Arc::new(Mutex::new(writer))));
+        self
+    }
+
+    /// Set the policy applied when the cost function returns NaN or infinite values
+    /// (default: [NonFinitePolicy::Abort])
+    #[must_use]
+    pub fn non_finite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite_policy = policy;
+        self
+    }
+
+    /// Set the policy applied when the cost function returns NaN constraint values
+    /// (default: [NanConstraintPolicy::NonFinite], ie same as other non-finite values).
+    ///
+    /// With [NanConstraintPolicy::Violation], replaced values are the ones recorded in
+    /// the state, the objective value being left unchanged.
+    #[must_use]
+    pub fn nan_constraint_policy(mut self, policy: NanConstraintPolicy) -> Self {
+        self.nan_constraint_policy = policy;
+        self
+    }
+
+    /// Set the wall-clock time budget of the optimization.
+    ///
+    /// Elapsed time is checked before each iteration, when exceeded the optimization stops
+    /// with [SuccessStatus::MaxTimeReached] status and the best point found so far.
+    #[must_use]
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    /// Set the budget of cost function evaluations of the optimization, should be
+    /// strictly positive.
+    ///
+    /// Contrary to argmin `max_iters`, it bounds the actual number of cost function calls
+    /// (cache hits being not counted, see [CobylaState::func_evals]): when the budget is
+    /// consumed the optimization stops with [SuccessStatus::MaxEvalReached] status and
+    /// the best point found so far, the stopped run can be continued with
+    /// [CobylaSolverBuilder::warm_start].
+    #[must_use]
+    pub fn max_fun_evals(mut self, max_fun_evals: u64) -> Self {
+        self.max_fun_evals = Some(max_fun_evals);
+        self
+    }
+
+    /// Set the objective value target: the optimization stops with
+    /// [SuccessStatus::StopValReached] status as soon as an evaluated point is feasible
+    /// with an objective value less than or equal to `stopval`.
+    ///
+    /// Feasibility is required: the maximum constraint violation of the point should be
+    /// less than the feasibility tolerance (see [CobylaSolverBuilder::feasibility_tol]),
+    /// hence an infeasible point below the target does not stop the optimization. The point
+    /// reaching the target is the returned best point.
+    #[must_use]
+    pub fn stopval(mut self, stopval: f64) -> Self {
+        self.stopval = Some(stopval);
+        self
+    }
+
+    /// Set the optimization sense of the objective (default: [Sense::Minimize]).
+    ///
+    /// With [Sense::Maximize], COBYLA minimizes the opposite of the objective while the
+    /// objective itself is reported in the state, given to callbacks and compared to
+    /// `stopval` (reached by greater or equal values), only COBYLA printouts showing
+    /// the opposite. Constraints keep the `c(x) >= 0` convention.
+    #[must_use]
+    pub fn sense(mut self, sense: Sense) -> Self {
+        self.sense = sense;
+        self
+    }
+
+    /// Returns the optimization sense of the objective
+    pub(crate) fn get_sense(&self) -> Sense {
+        self.sense
+    }
+
+    /// Set a stagnation criterion: the optimization stops with [SuccessStatus::Stagnated]
+    /// status when the best objective value improves by less than `epsilon` over
+    /// `iters` consecutive iterations (`iters` should be strictly positive and `epsilon`
+    /// positive).
+    ///
+    /// Contrary to `ftol_rel` and `ftol_abs` of [StopTols] which compare consecutive
+    /// evaluations, the improvement is checked over a window of iterations hence it is
+    /// less sensitive to noise. It is not checked during feasibility restoration.
+    #[must_use]
+    pub fn stagnation_window(mut self, iters: usize, epsilon: f64) -> Self {
+        self.stagnation_window = Some((iters, epsilon));
+        self
+    }
+
+    /// Set an interior criterion: the optimization stops with [SuccessStatus::FlatInterior]
+    /// status when all constraints (including bounds) are greater than `margin` at the best
+    /// point of the simplex while the norm of the objective gradient estimated by the
+    /// COBYLA linear model is less than `gtol` (`margin` should be positive and `gtol`
+    /// strictly positive).
+    ///
+    /// For problems whose optimum is interior, it saves the evaluations spent reducing
+    /// the trust region radius once the minimum is found. It is complementary to the
+    /// termination on the trust region radius (see [CobylaSolverBuilder::rhoend]) which
+    /// still applies, the gradient estimate being only as accurate as the simplex is
+    /// small. It is not checked during feasibility restoration.
+    #[must_use]
+    pub fn interior_stop(mut self, margin: f64, gtol: f64) -> Self {
+        self.interior_stop = Some((margin, gtol));
+        self
+    }
+
+    /// Set a callback consulted after each cost function evaluation with the evaluated x
+    /// and objective value: when it returns `true` the optimization stops with
+    /// [FailStatus::ForcedStop] status and the best point found so far.
+    ///
+    /// The run then returns normally: the final state holds the best parameter vector
+    /// of COBYLA simplex with its objective value and constraint values, eg to keep the
+    /// result of a long run interrupted by a flag the application sets on Ctrl-C.
+    ///
+    /// ```
+    /// use argmin::core::{CostFunction, Error, Executor, State};
+    /// use cobyla_argmin::{CobylaSolver, FailStatus};
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// struct ParaboloidProblem;
+    /// impl CostFunction for ParaboloidProblem {
+    ///     type Param = Vec<f64>;
+    ///     type Output = Vec<f64>;
+    ///
+    ///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+    ///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+    ///     }
+    /// }
+    ///
+    /// // Set by a signal handler of the application
+    /// let interrupted = Arc::new(AtomicBool::new(true));
+    /// let flag = interrupted.clone();
+    /// let solver = CobylaSolver::builder()
+    ///     .initial_param(vec![1., 1.])
+    ///     .stop_callback(move |_, _| flag.load(Ordering::Relaxed))
+    ///     .iprint(0)
+    ///     .build();
+    /// let res = Executor::new(ParaboloidProblem, solver).run().unwrap();
+    /// let state = res.state();
+    /// assert_eq!(state.termination_status(), Some(Err(FailStatus::ForcedStop)));
+    /// assert_eq!(state.get_best_param(), Some(&vec![1., 1.]));
+    /// assert_eq!(state.best_constraints(), &[1.]);
+    /// ```
+    #[must_use]
+    pub fn stop_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[f64], f64) -> bool + Send + 'static,
+    {
+        self.stop_callback = Some(StopCallback(Arc::new(Mutex::new(callback))));
+        self
+    }
+
+    /// Set a callback called after each iteration with the optimization progress
+    /// (see [ProgressInfo]), whether the best point is improved or not.
+    ///
+    /// Contrary to [CobylaSolverBuilder::stop_callback], it cannot stop the optimization.
+    #[must_use]
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&ProgressInfo) + Send + 'static,
+    {
+        self.progress_callback = Some(ProgressCallback(Arc::new(Mutex::new(callback))));
+        self
+    }
+
+    /// Set a selection of the reported best point applied to the evaluation log when the
+    /// optimization terminates: the selector is given the (x, cost function output) pairs
+    /// of all evaluated points (see [CobylaState::evaluations]) and returns the index of
+    /// the one to be reported as the best, instead of the one of COBYLA.
+    ///
+    /// It decouples the point optimized by COBYLA, which trades the objective against
+    /// the constraint violations, from the application notion of the best point. The
+    /// evaluation log is required (see [CobylaSolverBuilder::keep_evaluations]). The
+    /// selection is applied by the solver on the iteration ending the optimization,
+    /// whatever the reason: convergence or any other solver status (eg stop callback,
+    /// stagnation, objective target), maximum number of iterations, time budget (see
+    /// [CobylaSolverBuilder::max_time]) or target cost of the state. It is not applied
+    /// when the executor is interrupted from outside the solver (ctrl-c handling or
+    /// executor timeout).
+    ///
+    /// ```
+    /// use cobyla_argmin::CobylaSolver;
+    ///
+    /// // Lexicographic selection: least maximum constraint violation, then objective
+    /// let solver = CobylaSolver::builder()
+    ///     .keep_evaluations(true)
+    ///     .best_selector(|evaluations| {
+    ///         let key = |fx: &[f64]| {
+    ///             let violation = fx[1..].iter().fold(0f64, |v, c| v.max(-c));
+    ///             (violation, fx[0])
+    ///         };
+    ///         (0..evaluations.len())
+    ///             .min_by(|&i, &j| key(&evaluations[i].1).partial_cmp(&key(&evaluations[j].1)).unwrap())
+    ///             .unwrap()
+    ///     })
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn best_selector<F>(mut self, selector: F) -> Self
+    where
+        F: Fn(&[(Vec<f64>, Vec<f64>)]) -> usize + Send + Sync + 'static,
+    {
+        self.best_selector = Some(BestSelector(Arc::new(selector)));
+        self
+    }
+
+    /// Enable a cache of cost function evaluations holding at most `capacity` entries
+    /// (least recently used ones being evicted).
+    ///
+    /// Points whose components round to the same multiples of `tol` share the same
+    /// evaluation, use 0 to only reuse evaluations of exactly the same points.
+    /// Cache hits and misses are counted in the state.
+    #[must_use]
+    pub fn with_cache(mut self, capacity: usize, tol: f64) -> Self {
+        self.cache = Some(EvalCache::new(capacity, tol));
+        self
+    }
+
+    /// Set the number of repeated cost function evaluations at each point (default: 1),
+    /// the average of their outputs being given to COBYLA, to mitigate the noise of
+    /// stochastic objectives or constraints.
+    ///
+    /// Each point then costs `resample` cost function calls: all of them are counted in
+    /// [CobylaState::func_evals] and consume the budget of
+    /// [CobylaSolverBuilder::max_fun_evals], while the COBYLA `maxfun` budget of the
+    /// state and argmin `max_iters` still count points. The sample standard deviations
+    /// of the outputs are reported by [CobylaState::best_sample_std]. Cached evaluations
+    /// (see [CobylaSolverBuilder::with_cache]) hold the averaged outputs.
+    #[must_use]
+    pub fn resample(mut self, resample: usize) -> Self {
+        self.resample = Some(resample);
+        self
+    }
+
+    /// Record the history of evaluations (iteration, objective and maximum constraint
+    /// violation) and of the penalty parameter in the state, see [CobylaState::history]
+    /// and [CobylaState::penalty_history] (default: false)
+    #[must_use]
+    pub fn keep_history(mut self, keep_history: bool) -> Self {
+        self.keep_history = keep_history;
+        self
+    }
+
+    /// Record all evaluated points with their cost function output in the state,
+    /// see [CobylaState::evaluations] (default: false).
+    ///
+    /// Every cost function call is recorded (evaluation cache hits are not), the memory
+    /// used grows as `func_evals * (x dimension + output size)` values: the budget of
+    /// evaluations should be bounded accordingly for long runs on large problems.
+    #[must_use]
+    pub fn keep_evaluations(mut self, keep_evaluations: bool) -> Self {
+        self.keep_evaluations = keep_evaluations;
+        self
+    }
+
+    /// Record the vertices of the COBYLA simplex with their cost function output in the
+    /// state at each iteration, see [CobylaState::simplex] (default: false), along with the
+    /// outcome of the last trust region step, see [CobylaState::trust_region_step].
+    ///
+    /// This is a diagnostic of convergence issues (a collapsed simplex or a mis-sized
+    /// trust region typically), at the cost of copying `n + 1` vertices per iteration.
+    #[must_use]
+    pub fn keep_simplex(mut self, keep_simplex: bool) -> Self {
+        self.keep_simplex = keep_simplex;
+        self
+    }
+
+    /// Evaluate the vertices of a [SimplexInit::Custom] initial simplex other than the
+    /// initial guess in a single call to [CostFunction::bulk_cost] (default: false), which
+    /// problems evaluating many points at once more efficiently than one at a time should
+    /// implement. The argmin batch entry point is used rather than a dedicated trait: its
+    /// default implementation evaluates the points one at a time (or in parallel with the
+    /// argmin `rayon` feature), so that any cost function can be batched.
+    ///
+    /// Custom vertices are independent of each other and all batch evaluations are used.
+    /// Vertices of the [SimplexInit::AxisAligned] simplex are not batched as COBYLA moves
+    /// the next ones from a vertex improving the objective: setting this option without a
+    /// custom simplex is a configuration error (see [CobylaSolverBuilder::validate]). User
+    /// contexts of batch evaluations are not kept (see [CobylaState::context]). Warm
+    /// started runs are not batched.
+    #[must_use]
+    pub fn batch_initial_simplex(mut self, batch_initial_simplex: bool) -> Self {
+        self.batch_initial_simplex = batch_initial_simplex;
+        self
+    }
+
+    /// Catch panics of the cost function (default: true): a panicking evaluation stops
+    /// the optimization with [FailStatus::UnexpectedError] status, the best point found
+    /// so far and the panic message as termination reason, instead of unwinding through
+    /// the solver.
+    ///
+    /// Panics are only caught when the panic strategy is `unwind` (the default one),
+    /// they still abort the process with `panic = "abort"`. The panic hook is called as
+    /// usual, so the message is still printed by the default hook.
+    #[must_use]
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = Some(catch_panics);
+        self
+    }
+
+    /// Give the constraint values computed by the cost function at the current point to
+    /// observers as `c1`, `c2`, ... (the cost function output index) key-value pairs
+    /// (default: false).
+    ///
+    /// The maximum constraint violation at the current point is always given as
+    /// `max_violation`.
+    #[must_use]
+    pub fn observe_constraints(mut self, observe_constraints: bool) -> Self {
+        self.observe_constraints = observe_constraints;
+        self
+    }
+
+    /// Continue the optimization from the final state of a previous run, typically
+    /// stopped on its evaluation or iteration budget ([SuccessStatus::MaxEvalReached] or
+    /// [SuccessStatus::MaxItersReached]).
+    ///
+    /// Contrary to a new run from the best point of the previous one, the simplex and the
+    /// trust region radius of COBYLA are reused: the iterations are resumed where they were
+    /// stopped with the maximum number of iterations of the new executor as additional
+    /// budget. Other settings should be the same as the ones of the previous run.
+    /// Running the solver fails when the previous run is terminated otherwise
+    /// (convergence, failure, ...).
+    #[must_use]
+    pub fn warm_start<P: CobylaParam, C>(mut self, state: &CobylaState<P, C>) -> Self {
+        let context = state.cobyla_context.clone().map(|mut context| {
+            // Iterations stopped by argmin are waiting for the evaluation of the current point
+            if context.pending.is_none()
+                && let Some(x) = state.param.as_ref()
+            {
+                let x = x.components();
+                context.pending = Some(match context.scale.as_ref() {
+                    Some(s) => x.iter().zip(s).map(|(xi, si)| xi / si).collect(),
+                    None => x.to_vec(),
+                });
+            }
+            context
+        });
+        if let Some(x) = state.best_param.as_ref() {
+            self.x0 = x.components().into_owned();
+        }
+        self.warm_start = Some(context);
+        self
+    }
+
+    /// Run a feasibility restoration phase when the initial guess is infeasible
+    /// (default: false).
+    ///
+    /// The sum of constraint violations is first minimized until the maximum constraint
+    /// violation is less than the feasibility tolerance (see
+    /// [CobylaSolverBuilder::feasibility_tol]), the objective is then minimized from that
+    /// point. Cost function evaluations of that phase are counted in
+    /// [CobylaState::feasibility_evals].
+    #[must_use]
+    pub fn restore_feasibility(mut self, restore_feasibility: bool) -> Self {
+        self.restore_feasibility = restore_feasibility;
+        self
+    }
+
+    /// Only search a feasible point, the objective value returned by the cost function
+    /// being ignored (default: false).
+    ///
+    /// The feasibility restoration phase (see [CobylaSolverBuilder::restore_feasibility])
+    /// is run as the whole optimization: it stops with [SuccessStatus::Success] at the
+    /// first point whose maximum constraint violation is less than the feasibility
+    /// tolerance (see [CobylaSolverBuilder::feasibility_tol]), possibly the initial guess.
+    /// When COBYLA converges or the budget of cost function evaluations is consumed before
+    /// that, it stops with [FailStatus::Failure] at the least infeasible point.
+    #[must_use]
+    pub fn feasibility_only(mut self, feasibility_only: bool) -> Self {
+        self.feasibility_only = feasibility_only;
+        self
+    }
+
+    /// Set the maximum number of restarts when COBYLA stops because of rounding errors
+    /// in a degenerate simplex ([FailStatus::RoundoffLimited]) (default: 0).
+    ///
+    /// COBYLA is then restarted with a new axis aligned simplex built with the initial
+    /// change of x (see [CobylaSolverBuilder::rhobeg]) from the best point moved by
+    /// [RESTART_PERTURBATION] times the initial trust region radius, alternately along and
+    /// against each axis (the direction being reversed at each restart), so that the new
+    /// simplex does not collapse the same way. A component moved out of the bounds
+    /// (see [CobylaSolverBuilder::with_bounds]) is moved the other way, or clamped when
+    /// still out of them. The moved point costs one cost function evaluation, hence no
+    /// restart is done once the [CobylaSolverBuilder::max_fun_evals] budget is used up,
+    /// COBYLA being restarted from the best point itself when the output there is not
+    /// finite. The evaluation budget and the best point found so far are kept. The number
+    /// of restarts is given by [CobylaState::restarts], the termination status is the one
+    /// of the last run.
+    #[must_use]
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Return the best feasible evaluated point (see [CobylaState::best_feasible]) as the
+    /// best point when the one found by COBYLA violates the constraints by more than the
+    /// feasibility tolerance (default: false).
+    ///
+    /// The infeasible point of COBYLA is returned when no feasible point was evaluated.
+    #[must_use]
+    pub fn prefer_feasible(mut self, prefer_feasible: bool) -> Self {
+        self.prefer_feasible = prefer_feasible;
+        self
+    }
+
+    /// Only accept the convergence of COBYLA on x (trust region radius reaching its final
+    /// value, see [CobylaSolverBuilder::rhoend]) at a point satisfying the constraints up to
+    /// the feasibility tolerance (default: false).
+    ///
+    /// When the converged point is infeasible, COBYLA is restarted from it with the final
+    /// trust region radius reduced by 10 so that its linear approximations of the
+    /// constraints get more accurate, at most [FEASIBLE_STOP_REFINEMENTS] times.
+    /// The stop on the objective improvement (see [StopTols::ftol_rel]) is already only
+    /// triggered by feasible points.
+    #[must_use]
+    pub fn feasible_stop(mut self, feasible_stop: bool) -> Self {
+        self.feasible_stop = feasible_stop;
+        self
+    }
+
+    /// Polish an infeasible result by a final feasibility restoration (default: false).
+    ///
+    /// When the optimization succeeds at a point violating the constraints by more than
+    /// the feasibility tolerance (see [CobylaSolverBuilder::feasibility_tol]), the sum of
+    /// constraint violations is minimized from it without the objective, starting with
+    /// the final trust region radius so that the point only moves slightly, until
+    /// feasibility is reached or at most [POLISH_EVALS_PER_DIM] `* (n + 1)` evaluations
+    /// (beyond the budget of [CobylaSolverBuilder::max_fun_evals]). The least infeasible
+    /// point is returned with the termination status of the optimization, the number of
+    /// extra evaluations being given by [CobylaState::polish_evals]. It is a no-op when
+    /// the result is feasible or when iterations are suspended to be continued
+    /// (see [CobylaSolverBuilder::warm_start]).
+    #[must_use]
+    pub fn polish_feasibility(mut self, polish_feasibility: bool) -> Self {
+        self.polish_feasibility = polish_feasibility;
+        self
+    }
+
+    /// Set the maximum constraint violation ending the feasibility restoration phase
+    /// (default: [FEASIBILITY_TOL]), also used to tell feasible points for the objective
+    /// value target, the objective improvement and [CobylaSolverBuilder::feasible_stop]
+    #[must_use]
+    pub fn feasibility_tol(mut self, feasibility_tol: f64) -> Self {
+        self.feasibility_tol = Some(feasibility_tol);
+        self
+    }
+
+    /// Checks the whole configuration and returns all the invalid settings at once,
+    /// whereas running the solver fails on the first one.
+    ///
+    /// Settings depending on the cost function output size (constraint scaling and senses,
+    /// equality constraints) are only checked when the number of constraints is declared
+    /// (see [CobylaSolverBuilder::with_num_constraints]).
+    ///
+    /// ```
+    /// use cobyla_argmin::{CobylaSolver, RhoBeg};
+    ///
+    /// let errors = CobylaSolver::builder()
+    ///     .initial_param(vec![1., 1.])
+    ///     .rhobeg(RhoBeg::Set(vec![0.5]))
+    ///     .rho_reduction(1.5)
+    ///     .validate()
+    ///     .unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut solver = self.clone().build();
+        let mut errors = Vec::new();
+        if let Err(err) = solver.initial_guess_from_bounds() {
+            errors.push(err);
+        }
+        if solver.x0.is_empty() {
+            errors.push(invalid_parameter(
+                "initial guess should have at least one component",
+            ));
+        } else {
+            errors.extend(solver.settings_errors());
+            if let Some(reason) = solver.size_mismatch() {
+                errors.push(invalid_parameter(reason));
+            }
+            if let Err(err) = solver.warm_start_point() {
+                errors.push(err);
+            }
+            if let Some(nc) = solver.num_constraints {
+                errors.extend(
+                    [
+                        solver.check_constraint_scaling(nc + 1),
+                        solver.check_constraint_senses(nc + 1),
+                        solver.check_eq_constraints(nc + 1),
+                    ]
+                    .into_iter()
+                    .filter_map(Result::err),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into_iter().map(ConfigError::from).collect())
+        }
+    }
+
+    /// Build the solver
+    pub fn build(self) -> CobylaSolver {
+        CobylaSolver {
+            x0: self.x0,
+            rhobeg: self.rhobeg,
+            rhobeg_floor: self.rhobeg_floor.unwrap_or(RHOBEG_FLOOR),
+            rhoend: self.rhoend,
+            rho_reduction: self.rho_reduction.unwrap_or(RHO_REDUCTION),
+            initial_penalty: self.initial_penalty,
+            simplex_init: self.simplex_init,
+            degenerate_simplex: self.degenerate_simplex,
+            stop_tols: self.stop_tols,
+            bounds: self.bounds,
+            variable_names: self.variable_names,
+            scaling: self.scaling,
+            transforms: self.transforms,
+            constraint_scaling: self.constraint_scaling,
+            eq_constraints: self.eq_constraints,
+            constraint_sense: self.constraint_sense,
+            constraint_senses: self.constraint_senses,
+            added_constraints: self.added_constraints,
+            iprint: self.iprint,
+            non_finite_policy: self.non_finite_policy,
+            nan_constraint_policy: self.nan_constraint_policy,
+            substituted: false,
+            max_time: self.max_time,
+            max_fun_evals: self.max_fun_evals,
+            stopval: self.stopval,
+            sense: self.sense,
+            stagnation_window: self.stagnation_window,
+            interior_stop: self.interior_stop,
+            best_costs: VecDeque::new(),
+            incumbent: f64::INFINITY,
+            initial_objective: f64::NAN,
+            xtol_stop: false,
+            stop_callback: self.stop_callback,
+            best_selector: self.best_selector,
+            progress_callback: self.progress_callback,
+            output: self.output,
+            cache: self.cache,
+            resample: self.resample.unwrap_or(1),
+            sample_stds: Vec::new(),
+            keep_history: self.keep_history,
+            keep_evaluations: self.keep_evaluations,
+            keep_simplex: self.keep_simplex,
+            catch_panics: self.catch_panics.unwrap_or(true),
+            batch_initial_simplex: self.batch_initial_simplex,
+            clamp_to_bounds: self.clamp_to_bounds,
+            reject_out_of_bounds: self.reject_out_of_bounds,
+            num_constraints: self.num_constraints,
+            prefetched: Vec::new(),
+            observe_constraints: self.observe_constraints,
+            warm_start: self.warm_start,
+            restore_feasibility: self.restore_feasibility,
+            feasibility_only: self.feasibility_only,
+            max_restarts: self.max_restarts,
+            prefer_feasible: self.prefer_feasible,
+            feasible_stop: self.feasible_stop,
+            polish_feasibility: self.polish_feasibility,
+            refinements: 0,
+            polishing: None,
+            feasibility_tol: self.feasibility_tol.unwrap_or(FEASIBILITY_TOL),
+            seed: None,
+            restoration: None,
+            scale: None,
+            output_constraints: None,
+            start_point: None,
+            start: None,
+        }
+    }
+}
+
+/// Signature of the user callback requesting the optimization to stop
+type StopFn = dyn FnMut(&[f64], f64) -> bool + Send;
+
+/// User callback requesting the optimization to stop
+#[derive(Clone)]
+struct StopCallback(Arc<Mutex<StopFn>>);
+
+impl std::fmt::Debug for StopCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StopCallback")
+    }
+}
+
+/// Signature of an inequality constraint added to the cost function output
+type ConstraintFn = dyn Fn(&[f64]) -> f64 + Send + Sync;
+
+/// Inequality constraint added to the cost function output
+#[derive(Clone)]
+struct AddedConstraint(Arc<ConstraintFn>);
+
+impl std::fmt::Debug for AddedConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AddedConstraint")
+    }
+}
+
+/// Signature of the user selection of the best point among the evaluated ones
+type SelectorFn = dyn Fn(&[(Vec<f64>, Vec<f64>)]) -> usize + Send + Sync;
+
+/// User selection of the best point among the evaluated ones
+#[derive(Clone)]
+struct BestSelector(Arc<SelectorFn>);
+
+impl std::fmt::Debug for BestSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BestSelector")
+    }
+}
+
+/// Writer of COBYLA printouts
+#[derive(Clone)]
+struct Output(CobylaWriter);
+
+impl std::fmt::Debug for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Output")
+    }
+}
+
+/// Optimization progress given to the user callback after each iteration
+/// (see [CobylaSolverBuilder::on_progress])
+#[derive(Debug, Clone)]
+pub struct ProgressInfo<'a> {
+    /// Iteration number
+    pub iter: u64,
+    /// Current x components
+    pub x: &'a [f64],
+    /// Objective value at current x
+    pub objective: f64,
+    /// Maximum constraint violation at current x
+    pub max_violation: f64,
+    /// Current trust region radius
+    pub rho: f64,
+    /// Decrease of the best feasible objective value over the last iteration
+    /// (see [CobylaState::last_improvement])
+    pub improvement: f64,
+}
+
+/// Signature of the user callback monitoring the optimization progress
+type ProgressFn = dyn FnMut(&ProgressInfo) + Send;
+
+/// User callback monitoring the optimization progress
+#[derive(Clone)]
+struct ProgressCallback(Arc<Mutex<ProgressFn>>);
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+// Solver and state can be moved and shared across threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CobylaSolver>();
+    assert_send_sync::<CobylaState>();
+};
+
+/// Default minimum initial change of x components used with [RhoBeg::Relative]
+pub const RHOBEG_FLOOR: f64 = 1e-3;
+
+/// Default factor applied to the trust region radius when it is reduced
+pub const RHO_REDUCTION: f64 = 0.5;
+
+/// Default maximum constraint violation ending the feasibility restoration phase
+pub const FEASIBILITY_TOL: f64 = 1e-6;
+
+/// Number of cost function evaluations per x dimension (plus one) allowed to polish the
+/// feasibility of the result (see [CobylaSolverBuilder::polish_feasibility])
+pub const POLISH_EVALS_PER_DIM: u64 = 10;
+
+/// Fraction of the initial trust region radius by which the best point is moved to
+/// restart COBYLA from a degenerate simplex (see [CobylaSolverBuilder::max_restarts])
+pub const RESTART_PERTURBATION: f64 = 0.1;
+
+/// Maximum number of reductions of the final trust region radius to reach feasibility
+/// (see [CobylaSolverBuilder::feasible_stop])
+pub const FEASIBLE_STOP_REFINEMENTS: usize = 6;
+
+/// Least infeasible point found by the feasibility restoration phase
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+struct Restoration {
+    /// Parameter vector components
+    x: Vec<f64>,
+    /// Cost function output
+    fx: Vec<f64>,
+    /// Maximum constraint violation
+    violation: f64,
+}
+
+/// Computes scaling of x components such that the initial change is the same
+/// for all components, returns None when no scaling is required.
+fn compute_rescaling(dx: &[f64]) -> Option<Vec<f64>> {
+    if dx.iter().all(|&d| d == dx[0]) {
+        return None;
+    }
+    Some(dx.iter().map(|d| d / dx[0]).collect())
+}
+
+/// Logs at trace level the trust region step evaluated by the last COBYLA iteration, if
+/// any given the previous number of `steps`, and the reduction of the trust region radius
+/// from `rho`
+#[cfg(feature = "log")]
+fn log_step(ctx: &cobyla_context_t, steps: libc::c_int, rho: f64) {
+    if ctx.trial_step != steps {
+        log::trace!(
+            "trust region step {} {}: predicted reduction = {:e}, actual reduction = {:e}, ratio = {:e}",
+            ctx.trial_step,
+            if ctx.trial_rejected != 0 {
+                "rejected out of the bounds"
+            } else if ctx.trial_accepted != 0 {
+                "accepted"
+            } else {
+                "rejected"
+            },
+            ctx.trial_prerem,
+            ctx.trial_trured,
+            ctx.trial_trured / ctx.trial_prerem
+        );
+    }
+    if ctx.rho < rho {
+        log::trace!("trust region radius reduced from {rho:e} to {:e}", ctx.rho);
+    }
+}
+
+/// Returns the sum of the violations of constraints `c(x) >= 0` given their values
+fn total_violation(c: &[f64]) -> f64 {
+    c.iter().map(|ci| (-ci).max(0.)).sum()
+}
+
+/// Returns whether all cost function output values are finite
+fn is_finite(fx: &[f64]) -> bool {
+    fx.iter().all(|v| v.is_finite())
+}
+
+/// Returns the displacements of the simplex vertices from the first one as a column-major
+/// `n x n` matrix, vertices being first transformed by `f`
+fn simplex_displacements(vertices: &[Vec<f64>], f: impl Fn(&[f64]) -> Vec<f64>) -> Vec<f64> {
+    let v0 = f(&vertices[0]);
+    vertices[1..]
+        .iter()
+        .flat_map(|v| {
+            f(v).into_iter()
+                .zip(&v0)
+                .map(|(vi, v0i)| vi - v0i)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Checks that the displacements of the simplex vertices from the first one are not
+/// within the relative tolerance `tol` of the span of the previous ones (Gram-Schmidt
+/// orthogonalization), returns the index of the first degenerate vertex, which is moved
+/// orthogonally to the previous displacements instead when `perturb`
+fn orthogonalized(vertices: &mut [Vec<f64>], tol: f64, perturb: bool) -> Result<(), usize> {
+    let n = vertices[0].len();
+    let norm = |v: &[f64]| v.iter().map(|vi| vi * vi).sum::<f64>().sqrt();
+    let residual = |mut v: Vec<f64>, basis: &[Vec<f64>]| {
+        for u in basis {
+            let dot: f64 = v.iter().zip(u).map(|(vi, ui)| vi * ui).sum();
+            v.iter_mut().zip(u).for_each(|(vi, ui)| *vi -= dot * ui);
+        }
+        v
+    };
+    let displacement = |v: &[f64], v0: &[f64]| -> Vec<f64> {
+        v.iter().zip(v0).map(|(vi, v0i)| vi - v0i).collect()
+    };
+    let lengths: Vec<f64> = vertices[1..]
+        .iter()
+        .map(|v| norm(&displacement(v, &vertices[0])))
+        .filter(|len| *len > 0.)
+        .collect();
+    let mean_length = lengths.iter().sum::<f64>() / lengths.len().max(1) as f64;
+    let mut basis: Vec<Vec<f64>> = Vec::with_capacity(n);
+    for k in 1..vertices.len() {
+        let d = displacement(&vertices[k], &vertices[0]);
+        let length = norm(&d);
+        let r = residual(d, &basis);
+        let rnorm = norm(&r);
+        if rnorm > tol * length && rnorm > 0. {
+            basis.push(r.into_iter().map(|ri| ri / rnorm).collect());
+            continue;
+        }
+        if !perturb || mean_length == 0. {
+            return Err(k);
+        }
+        // Axis direction the farthest from the span of the previous displacements
+        let r = (0..n)
+            .map(|i| residual((0..n).map(|j| f64::from(i == j)).collect(), &basis))
+            .max_by(|a, b| norm(a).total_cmp(&norm(b)))
+            .ok_or(k)?;
+        let rnorm = norm(&r);
+        let u: Vec<f64> = r.into_iter().map(|ri| ri / rnorm).collect();
+        let step = if length > 0. { length } else { mean_length };
+        vertices[k] = vertices[0]
+            .iter()
+            .zip(&u)
+            .map(|(v0i, ui)| v0i + step * ui)
+            .collect();
+        basis.push(u);
+    }
+    Ok(())
+}
+
+/// Returns the inverse of the `n x n` column-major matrix `a` computed by Gauss-Jordan
+/// elimination with partial pivoting, None when the matrix is (numerically) singular
+fn invert(a: &[f64], n: usize) -> Option<Vec<f64>> {
+    let norm = a.iter().fold(0., |acc: f64, v| acc.max(v.abs()));
+    let tol = norm * n as f64 * f64::EPSILON;
+    let mut a = a.to_vec();
+    let mut inv = vec![0.; n * n];
+    for i in 0..n {
+        inv[i + n * i] = 1.;
+    }
+    for k in 0..n {
+        let p = (k..n).max_by(|&i, &j| a[i + n * k].abs().total_cmp(&a[j + n * k].abs()))?;
+        if a[p + n * k].is_nan() || a[p + n * k].abs() <= tol {
+            return None;
+        }
+        for j in 0..n {
+            a.swap(k + n * j, p + n * j);
+            inv.swap(k + n * j, p + n * j);
+        }
+        let pivot = a[k + n * k];
+        for j in 0..n {
+            a[k + n * j] /= pivot;
+            inv[k + n * j] /= pivot;
+        }
+        for i in (0..n).filter(|&i| i != k) {
+            let factor = a[i + n * k];
+            if factor != 0. {
+                for j in 0..n {
+                    a[i + n * j] -= factor * a[k + n * j];
+                    inv[i + n * j] -= factor * inv[k + n * j];
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// Panic of the cost function caught during an evaluation, with the termination reason
+#[derive(Debug)]
+struct CostPanic(String);
+
+impl std::fmt::Display for CostPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CostPanic {}
+
+/// Returns the message of a caught panic
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+/// Appends the values of the added constraints at x to the cost function output
+fn with_added_constraints(
+    constraints: &[AddedConstraint],
+    x: &[f64],
+    mut fx: Vec<f64>,
+) -> Vec<f64> {
+    fx.extend(constraints.iter().map(|c| (c.0)(x)));
+    fx
+}
+
+/// Returns the average of repeated cost function outputs with their sample standard
+/// deviations, None for a single output
+fn sample_statistics(
+    mut samples: Vec<Vec<f64>>,
+) -> Result<(Vec<f64>, Option<Vec<f64>>), argmin::core::Error> {
+    if samples.len() == 1 {
+        return Ok((samples.swap_remove(0), None));
+    }
+    let nout = samples[0].len();
+    if let Some(fx) = samples.iter().find(|fx| fx.len() != nout) {
+        return Err(invalid_parameter(format!(
+            "cost function output size ({}) should be the same for all resampled evaluations ({nout})",
+            fx.len()
+        )));
+    }
+    let k = samples.len() as f64;
+    let mean: Vec<f64> = (0..nout)
+        .map(|i| samples.iter().map(|fx| fx[i]).sum::<f64>() / k)
+        .collect();
+    let std = (0..nout)
+        .map(|i| {
+            let sq = samples.iter().map(|fx| (fx[i] - mean[i]).powi(2));
+            (sq.sum::<f64>() / (k - 1.)).sqrt()
+        })
+        .collect();
+    Ok((mean, Some(std)))
+}
+
+/// Whether two points are the same up to rounding errors
+fn same_point(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(ai, bi)| (ai - bi).abs() <= 1e-12 * ai.abs().max(1.))
+}
+
+/// Euclidean distance between two points
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(ai, bi)| (ai - bi).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Relative or absolute change test used by ftol criteria
+fn relstop(vold: f64, vnew: f64, reltol: f64, abstol: f64) -> bool {
+    if vold.is_infinite() {
+        return false;
+    }
+    (vnew - vold).abs() < abstol
+        || (vnew - vold).abs() < reltol * (vnew.abs() + vold.abs()) * 0.5
+        || (reltol > 0. && vnew == vold)
+}
+
+impl CobylaSolver {
+    /// Computes a single COBYLA iteration: gives the cost function output at current x
+    /// and evaluates the cost function at the next x unless the algorithm is done
+    fn cobyla_iteration<O, P, C>(
+        &mut self,
+        problem: &mut Problem<O>,
+        state: CobylaState<P, C>,
+    ) -> std::result::Result<(CobylaState<P, C>, Option<KV>), argmin::core::Error>
+    where
+        O: CostFunction<Param = P>,
+        O::Output: CobylaOutput<Context = C>,
+        P: CobylaParam,
+        C: Clone,
+    {
+        self.start.get_or_insert_with(Instant::now);
+        let mut x = self.scaled(&state.get_param().unwrap().components());
+        let mut state = state;
+        if let Some(ctx) = state.cobyla_context.as_mut() {
+            // Context data is heap allocated hence the pointer remains valid
+            // while the context is owned by the state
+            let ctx = ctx.as_mut_ptr();
+            // Current cost was evaluated at current x by the previous iteration (or init)
+            let cost = state.get_full_cost().unwrap().clone();
+            let mut c = self.cobyla_constraints(&state.get_param().unwrap().components(), &cost);
+            // While restoring feasibility the sum of violations is minimized without constraints
+            let restoring = self.restoration.is_some();
+            let polishing = self.polishing;
+            if let Some((iters, _)) = self.stagnation_window {
+                if restoring {
+                    self.best_costs.clear();
+                } else {
+                    if self.best_costs.len() > iters {
+                        self.best_costs.pop_front();
+                    }
+                    self.best_costs.push_back(self.incumbent);
+                }
+            }
+            let f = if restoring {
+                let f = total_violation(&c);
+                c.clear();
+                f
+            } else {
+                self.objective(cost[0])
+            };
+
+            // When COBYLA would stop on its evaluation budget, the iteration is computed
+            // beyond it to suspend iterations with the next point to be evaluated, hence
+            // allowing to continue them (see CobylaSolverBuilder::warm_start)
+            let (nfvals, maxfun) = unsafe { ((*ctx).nfvals, (*ctx).maxfun) };
+            let exhausted = polishing.is_none()
+                && self
+                    .max_fun_evals
+                    .is_some_and(|max_fun_evals| state.func_evals >= max_fun_evals);
+            let suspend = !restoring && (exhausted || nfvals + 1 >= maxfun);
+            if suspend {
+                unsafe { (*ctx).maxfun = nfvals + 2 };
+            }
+            #[cfg(feature = "log")]
+            let (steps, rho) = unsafe { ((*ctx).trial_step, (*ctx).rho) };
+            let status = with_output(self.output.as_ref().map(|o| &o.0), || unsafe {
+                cobyla_iterate(ctx, f, x.as_mut_ptr(), c.as_mut_ptr())
+            });
+            #[cfg(feature = "log")]
+            log_step(unsafe { &*ctx }, steps, rho);
+            state.rho = unsafe { (*ctx).rho };
+            state.penalty = unsafe { (*ctx).parmu };
+            state.record_penalty(state.get_iter() + 1);
+            let kv = KV::new();
+            if suspend {
+                unsafe { (*ctx).maxfun = maxfun };
+            }
+            if suspend && status == CobylaStatus::COBYLA_ITERATE as i32 {
+                let pending = x.clone();
+                let mut context = state.cobyla_context.take();
+                let status = Ok(if exhausted {
+                    SuccessStatus::MaxEvalReached
+                } else {
+                    SuccessStatus::MaxItersReached
+                });
+                let mut state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                if let Some(context) = context.as_mut() {
+                    context.pending = Some(pending);
+                }
+                state.cobyla_context = context;
+                return Ok((state, Some(kv)));
+            }
+            if let Some(polished) = polishing
+                && (state.polish_evals >= POLISH_EVALS_PER_DIM * (x.len() as u64 + 1)
+                    || status != CobylaStatus::COBYLA_ITERATE as i32)
+            {
+                // Feasibility is not reached: stop with the least infeasible point
+                let status = Ok(polished);
+                let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                self.restoration = None;
+                self.polishing = None;
+                return Ok((state, Some(kv)));
+            }
+            if self.feasibility_only
+                && restoring
+                && (exhausted || status != CobylaStatus::COBYLA_ITERATE as i32)
+            {
+                // No feasible point is found: stop with the least infeasible one
+                let status = Err(FailStatus::Failure);
+                let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                let reason = format!(
+                    "no feasible point found, least maximum constraint violation is {:e}",
+                    state.max_violation.unwrap_or(f64::INFINITY)
+                );
+                let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                return Ok((state, Some(kv)));
+            }
+            if let Some(r) = self
+                .restoration
+                .take_if(|_| status != CobylaStatus::COBYLA_ITERATE as i32)
+            {
+                // Feasibility is not restored up to the tolerance: the objective is
+                // minimized from the least infeasible point
+                let state = self.restart_from(state, r.x, r.fx, None, true);
+                return Ok((state, Some(kv)));
+            }
+            if exhausted && status == CobylaStatus::COBYLA_ITERATE as i32 {
+                // Evaluation budget is consumed while restoring feasibility
+                let status = Ok(SuccessStatus::MaxEvalReached);
+                let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                return Ok((state, Some(kv)));
+            }
+            if status != CobylaStatus::COBYLA_ITERATE as i32 {
+                // Algorithm is done and x is set to the best point: either the last
+                // evaluated one or the best vertex of the simplex. A penalized or rejected
+                // last point is never kept as the result.
+                let last = {
+                    let xl = state.get_param().unwrap().components();
+                    x == self.scaled(&xl) && !self.is_rejected(&xl)
+                };
+                let status = self.status_from(status);
+                if status == Err(FailStatus::RoundoffLimited)
+                    && state.restarts < self.max_restarts
+                    && !exhausted
+                {
+                    // The simplex has degenerated: COBYLA is restarted from the best point
+                    // moved by a fraction of rhobeg, alternately along and against each
+                    // axis, so that the new simplex does not degenerate the same way
+                    let best = self.best_point(ctx, &mut x, cost.len(), c.len());
+                    state.restarts += 1;
+                    let step = RESTART_PERTURBATION * unsafe { (*ctx).rhobeg };
+                    let mut signs: Vec<f64> = (0..x.len())
+                        .map(|j| match (j + state.restarts) % 2 {
+                            0 => 1.,
+                            _ => -1.,
+                        })
+                        .collect();
+                    let moved = |signs: &[f64]| {
+                        let xr: Vec<f64> =
+                            x.iter().zip(signs).map(|(xj, sj)| xj + sj * step).collect();
+                        self.unscaled(&xr)
+                    };
+                    let mut xr = moved(&signs);
+                    if let Some(xc) = self.projected(&xr).filter(|xc| *xc != xr) {
+                        // Components moved out of the bounds are moved the other way,
+                        // then clamped when still out of them
+                        signs
+                            .iter_mut()
+                            .zip(xr.iter().zip(&xc))
+                            .filter(|(_, (xj, xcj))| xj != xcj)
+                            .for_each(|(sj, _)| *sj = -*sj);
+                        xr = moved(&signs);
+                        xr = self.projected(&xr).unwrap_or(xr);
+                    }
+                    let xr = P::from_components(xr);
+                    let iter = state.get_iter() + 1;
+                    let (fx, context) = match self.evaluate(problem, &xr, iter, &mut state) {
+                        Ok((fx, context)) => (self.nan_constraints_replaced(fx), context),
+                        Err(err) => {
+                            let CostPanic(reason) = err.downcast::<CostPanic>()?;
+                            let status = Err(FailStatus::UnexpectedError);
+                            let state =
+                                self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                            let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                            return Ok((state, Some(kv)));
+                        }
+                    };
+                    if fx.len() != cost.len() {
+                        let reason = format!(
+                            "cost function output size ({}) at x = {:?} differs from the one of the first evaluation ({})",
+                            fx.len(),
+                            xr.components(),
+                            cost.len()
+                        );
+                        let status = Err(FailStatus::InvalidArgs);
+                        let state =
+                            self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                        let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                        return Ok((state, Some(kv)));
+                    }
+                    let xr = xr.components().into_owned();
+                    let violation = self.max_violation(&xr, &fx);
+                    state.record_history(iter, fx[0], violation);
+                    let state = if is_finite(&fx) {
+                        let g = self.objective(fx[0]);
+                        if violation <= self.feasibility_tol && g < self.incumbent {
+                            self.incumbent = g;
+                            state.best_feasible = Some((xr.clone(), fx.clone()));
+                        }
+                        self.restart_from(state, xr, fx, context, false)
+                    } else {
+                        // Non-finite output cannot be given to COBYLA: restart from the best
+                        // point
+                        self.restart_from(state, self.unscaled(&x), best, None, false)
+                    };
+                    return Ok((state, Some(kv)));
+                }
+                if self.feasible_stop
+                    && matches!(
+                        status,
+                        Ok(SuccessStatus::Success | SuccessStatus::XtolReached)
+                    )
+                    && self.refinements < FEASIBLE_STOP_REFINEMENTS
+                {
+                    // Converged point is the one returned below
+                    let (xb, best) = if last && !self.substituted {
+                        (
+                            state.get_param().unwrap().components().into_owned(),
+                            cost.clone(),
+                        )
+                    } else {
+                        let mut xb = x.clone();
+                        let best = self.best_point(ctx, &mut xb, cost.len(), c.len());
+                        (self.unscaled(&xb), best)
+                    };
+                    if self.max_violation(&xb, &best) > self.feasibility_tol {
+                        // The converged point is infeasible: COBYLA is restarted from it
+                        // with a smaller final trust region radius
+                        self.refinements += 1;
+                        let rhoend = unsafe { (*ctx).rhoend };
+                        let state = self.refine_from(state, xb, best, rhoend, 0.1 * rhoend);
+                        return Ok((state, Some(kv)));
+                    }
+                }
+                if status == Err(FailStatus::RoundoffLimited) {
+                    let step = match (state.param.as_ref(), state.prev_param.as_ref()) {
+                        (Some(x), Some(xp)) => distance(&x.components(), &xp.components()),
+                        _ => 0.,
+                    };
+                    state.roundoff = Some(RoundoffInfo {
+                        rho: state.rho,
+                        step,
+                    });
+                }
+                let state = if last && !self.substituted {
+                    state.cobyla_context = None;
+                    state.status = Some(status);
+                    self.feasible_result(state)
+                } else {
+                    self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status)
+                };
+                return Ok((state, Some(kv)));
+            }
+
+            if let Some((margin, gtol)) = self.interior_stop
+                && !restoring
+                && self.flat_interior(ctx, cost.len(), margin, gtol)
+            {
+                let status = Ok(SuccessStatus::FlatInterior);
+                let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                return Ok((state, Some(kv)));
+            }
+
+            let mut xu = self.unscaled(&x);
+            if unsafe { (*ctx).nfvals } > x.len() as libc::c_long && self.is_rejected(&xu) {
+                if unsafe { (*ctx).ibrnch } == 1 {
+                    // Trust region step out of the bounds is not evaluated: COBYLA handles
+                    // it as an unsuccessful one while the state stays at the best point
+                    unsafe { (*ctx).reject_trial = 1 };
+                    let (xb, fx) = match self.restoration.as_ref() {
+                        Some(r) => (r.x.clone(), r.fx.clone()),
+                        None => {
+                            let mut xb = x.clone();
+                            let fx = self.best_point(ctx, &mut xb, cost.len(), c.len());
+                            (self.unscaled(&xb), fx)
+                        }
+                    };
+                    let violation = self.max_violation(&xb, &fx);
+                    state.last_improvement = 0.;
+                    let mut state = state.param(P::from_components(xb)).cost(fx);
+                    state.context = None;
+                    state.max_violation = Some(violation);
+                    return Ok((state, Some(kv)));
+                }
+                xu = self.bounded_geometry_step(ctx, &mut x, xu);
+            }
+            let xp = P::from_components(xu);
+            let iter = state.get_iter() + 1;
+            let evals = state.func_evals;
+            let (mut fx, context) = match self.evaluate(problem, &xp, iter, &mut state) {
+                Ok(evaluation) => evaluation,
+                Err(err) => {
+                    // A panicking cost function stops the optimization with the best point
+                    let CostPanic(reason) = err.downcast::<CostPanic>()?;
+                    let status = Err(FailStatus::UnexpectedError);
+                    let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                    let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                    return Ok((state, Some(kv)));
+                }
+            };
+            if fx.len() != cost.len() {
+                // COBYLA working arrays are sized from the first evaluation: stop with the best point
+                let reason = format!(
+                    "cost function output size ({}) at x = {:?} differs from the one of the first evaluation ({})",
+                    fx.len(),
+                    xp.components(),
+                    cost.len()
+                );
+                let status = Err(FailStatus::InvalidArgs);
+                let state = self.stop_at_best(ctx, state, &mut x, cost.len(), c.len(), status);
+                let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                return Ok((state, Some(kv)));
+            }
+            fx = self.nan_constraints_replaced(fx);
+            if !is_finite(&fx) {
+                match self.non_finite_policy {
+                    NonFinitePolicy::Penalty(penalty) => fx = self.penalized(fx, penalty),
+                    NonFinitePolicy::Abort => {
+                        // Non-finite values are not given to COBYLA: stop with the best point
+                        let reason = format!(
+                            "non-finite cost function output {fx:?} at x = {:?}",
+                            xp.components()
+                        );
+                        let status = Err(FailStatus::NonFiniteCost);
+                        let state =
+                            self.stop_at_best(ctx, state, &mut x, fx.len(), c.len(), status);
+                        let state = state.terminate_with(TerminationReason::SolverExit(reason));
+                        return Ok((state, Some(kv)));
+                    }
+                }
+            }
+
+            let violation = self.max_violation(&xp.components(), &fx);
+            state.record_history(iter, fx[0], violation);
+            let incumbent = self.incumbent;
+            let g = self.objective(fx[0]);
+            let improved = violation <= self.feasibility_tol && g < incumbent;
+            if improved {
+                self.incumbent = g;
+                state.best_feasible = Some((xp.components().into_owned(), fx.clone()));
+            }
+            state.last_improvement = if improved { incumbent - g } else { 0. };
+            if polishing.is_some() {
+                state.polish_evals += state.func_evals - evals;
+            } else if self.restoration.is_some() {
+                state.feasibility_evals = state.func_evals;
+            }
+            if let Some(r) = self.restoration.as_mut()
+                && violation < r.violation
+            {
+                *r = Restoration {
+                    x: xp.components().into_owned(),
+                    fx: fx.clone(),
+                    violation,
+                };
+            }
+
+            if self.forced_stop(&xp.components(), fx[0]) {
+                let status = Err(FailStatus::ForcedStop);
+                let state = self.stop_at_best(ctx, state, &mut x, fx.len(), c.len(), status);
+                return Ok((state, Some(kv)));
+            }
+
+            if self.stopval_reached(fx[0], violation) {
+                let mut state = state.param(xp).cost(fx);
+                state.context = context;
+                state.max_violation = Some(violation);
+                state.cobyla_context = None;
+                state.status = Some(Ok(SuccessStatus::StopValReached));
+                return Ok((state, Some(kv)));
+            }
+
+            if restoring
+                && violation <= self.feasibility_tol
+                && (self.feasibility_only || polishing.is_some())
+            {
+                // Feasible point is found: stop with it
+                self.restoration = None;
+                self.polishing = None;
+                let mut state = state.param(xp).cost(fx);
+                state.context = context;
+                state.max_violation = Some(violation);
+                state.cobyla_context = None;
+                state.status = Some(Ok(polishing.unwrap_or(SuccessStatus::Success)));
+                return Ok((state, Some(kv)));
+            }
+
+            if restoring && violation <= self.feasibility_tol {
+                // Feasibility is restored: the objective is minimized from that point
+                self.restoration = None;
+                let x = xp.components().into_owned();
+                let state = self.restart_from(state, x, fx, context, true);
+                return Ok((state, Some(kv)));
+            }
+
+            if !restoring && improved && self.ftol_reached(incumbent, g) {
+                // Improvement of the best function value is small enough:
+                // stop with the evaluated point which is the new best one
+                let mut state = state.param(xp).cost(fx);
+                state.context = context;
+                state.max_violation = Some(violation);
+                state.cobyla_context = None;
+                state.status = Some(Ok(SuccessStatus::FtolReached));
+                return Ok((state, Some(kv)));
+            }
+
+            if self.stagnated() {
+                // Best function value improvement is too small over the window
+                let status = Ok(SuccessStatus::Stagnated);
+                let state = self.stop_at_best(ctx, state, &mut x, fx.len(), c.len(), status);
+                return Ok((state, Some(kv)));
+            }
+
+            let mut state = state.param(xp).cost(fx);
+            state.context = context;
+            state.max_violation = Some(violation);
+            return Ok((state, Some(kv)));
+        }
+
+        Ok((state, None))
+    }
+
+    /// Returns whether the improvement of the best feasible objective value from
+    /// `incumbent` to `g` satisfies one of the function value tolerances of [StopTols]
+    fn ftol_reached(&self, incumbent: f64, g: f64) -> bool {
+        let ftol = &self.stop_tols;
+        let f0 = self.initial_objective.abs();
+        let rel_abs_reached = (ftol.ftol_rel > 0. || ftol.ftol_abs > 0.)
+            && relstop(incumbent, g, ftol.ftol_rel, ftol.ftol_abs);
+        let init_reached =
+            f0.is_finite() && incumbent.is_finite() && incumbent - g < ftol.ftol_init * f0;
+        rel_abs_reached || init_reached
+    }
+
+    /// Returns whether all constraints are greater than `margin` at the best point of the
+    /// simplex while the norm of the objective gradient of the COBYLA linear model (in user
+    /// space) is less than `gtol`, see [CobylaSolverBuilder::interior_stop]
+    fn flat_interior(
+        &self,
+        ctx: *mut cobyla_context_t,
+        nout: usize,
+        margin: f64,
+        gtol: f64,
+    ) -> bool {
+        let (n, m, steps) = unsafe { ((*ctx).n as usize, (*ctx).m as usize, (*ctx).trial_step) };
+        if steps == 0 {
+            // Linear models are not computed before the first trust region step
+            return false;
+        }
+        // Last column of the linear model coefficients is the opposite of the objective gradient
+        let a = unsafe { std::slice::from_raw_parts((*ctx).a, n * (m + 1)) };
+        let norm = a[n * m..]
+            .iter()
+            .enumerate()
+            .map(|(i, ai)| (ai / self.scale.as_ref().map_or(1., |s| s[i])).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        if norm.is_nan() || norm >= gtol {
+            return false;
+        }
+        let mut x = vec![0.; n];
+        let fx = self.best_point(ctx, &mut x, nout, m);
+        self.cobyla_constraints(&self.unscaled(&x), &fx)
+            .iter()
+            .all(|ci| *ci > margin)
+    }
+
+    /// Returns whether the best objective value improvement over the stagnation window
+    /// is less than its epsilon
+    fn stagnated(&self) -> bool {
+        match (
+            self.stagnation_window,
+            self.best_costs.front(),
+            self.best_costs.back(),
+        ) {
+            (Some((iters, epsilon)), Some(first), Some(last)) => {
+                self.best_costs.len() > iters && first - last < epsilon
+            }
+            _ => false,
+        }
+    }
+
+    /// Calls the user progress callback with current state
+    fn report_progress<P: CobylaParam, C>(&self, state: &CobylaState<P, C>) {
+        let (Some(callback), Some(x), Some(cost)) = (
+            self.progress_callback.as_ref(),
+            state.param.as_ref(),
+            state.cost.as_ref(),
+        ) else {
+            return;
+        };
+        let x = x.components();
+        let info = ProgressInfo {
+            iter: state.iter,
+            x: &x,
+            objective: cost[0],
+            max_violation: state
+                .max_violation
+                .unwrap_or_else(|| max_constraint_violation(&cost[1..])),
+            rho: state.rho,
+            improvement: state.last_improvement,
+        };
+        let mut callback = callback.0.lock().unwrap_or_else(|e| e.into_inner());
+        callback(&info);
+    }
+
+    /// Records the vertices of the simplex in the state once they are all evaluated,
+    /// with the outcome of the last trust region step
+    fn record_simplex<P, C>(&self, state: &mut CobylaState<P, C>)
+    where
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let nout = state.cost.as_ref().map_or(0, Vec::len);
+        let Some(context) = state.cobyla_context.as_mut() else {
+            return;
+        };
+        let (n, m) = context.dims();
+        let ctx = context.as_mut_ptr();
+        if unsafe { (*ctx).nfvals } <= n as libc::c_long {
+            return;
+        }
+        let c = unsafe { &*ctx };
+        if c.trial_step != 0 {
+            state.trust_region_step = Some(TrustRegionStep {
+                predicted_reduction: c.trial_prerem,
+                actual_reduction: c.trial_trured,
+                ratio: c.trial_trured / c.trial_prerem,
+                accepted: c.trial_accepted != 0,
+                rejected: c.trial_rejected != 0,
+            });
+        }
+        let mut x = vec![0.; n];
+        let simplex = (0..=n)
+            .map(|j| {
+                let fx = self.vertex(ctx, j, &mut x, nout, m);
+                (self.unscaled(&x), fx)
+            })
+            .collect();
+        state.simplex = Some(simplex);
+    }
+
+    /// Adds the values of the current iteration (see [CobylaSolver] observed keys) and,
+    /// when requested, the constraint values at the current point to the key-value pairs
+    /// given to observers
+    fn observed_values<P, C>(&self, state: &CobylaState<P, C>, mut kv: KV) -> KV
+    where
+        CobylaState<P, C>: State<Float = f64>,
+    {
+        let constraints = state.constraints();
+        let violation = state
+            .max_violation
+            .unwrap_or_else(|| max_constraint_violation(constraints));
+        if let Some(cost) = state.cost.as_ref() {
+            kv.insert("cost", KvValue::Float(cost[0]));
+        }
+        kv.insert("best_cost", KvValue::Float(self.objective(self.incumbent)));
+        kv.insert("rho", KvValue::Float(state.rho));
+        kv.insert("max_violation", KvValue::Float(violation));
+        kv.insert("func_evals", KvValue::Uint(state.func_evals));
+        kv.insert("improvement", KvValue::Float(state.last_improvement));
+        if self.observe_constraints {
+            for (k, ck) in constraints.iter().enumerate() {
+                kv.insert(format!("c{}", k + 1), KvValue::Float(*ck));
+            }
+        }
+        kv
+    }
+}
+
+impl<O, P, C> Solver<O, CobylaState<P, C>> for CobylaSolver
+where
+    O: CostFunction<Param = P> + SyncAlias,
+    O::Output: CobylaOutput<Context = C> + SendAlias,
+    P: CobylaParam + SyncAlias,
+    C: Clone,
+{
+    fn name(&self) -> &str {
+        "COBYLA"
+    }
+
+    /// Initializes the algorithm.
+    ///
+    /// Executed before any iterations are performed and has access to the optimization problem
+    /// definition and the internal state of the solver.
+    /// Returns an updated `state` and optionally a `KV` which holds key-value pairs used in
+    /// [Observers](`argmin::core::observers::Observe`).
+    /// The default implementation returns the unaltered `state` and no `KV`.
+    #[allow(clippy::useless_conversion)]
+    fn init(
+        &mut self,
+        problem: &mut Problem<O>,
+        state: CobylaState<P, C>,
+    ) -> std::result::Result<(CobylaState<P, C>, Option<KV>), argmin::core::Error> {
+        self.start = Some(Instant::now());
+        self.initial_guess_from_bounds()?;
+        self.check_dim::<P>()?;
+        if self.x0.is_empty() {
+            let mut state = state;
+            state.status = Some(Err(FailStatus::InvalidArgs));
+            state.termination_status =
+                TerminationStatus::Terminated(TerminationReason::SolverExit(
+                    "initial guess should have at least one component".to_string(),
+                ));
+            return Ok((state, None));
+        }
+        if let Some(err) = self.settings_errors().into_iter().next() {
+            return Err(err);
+        }
+        self.best_costs.clear();
+        // Initial guess is copied once, the parameter vector built from it being then
+        // moved to the state
+        let (warm_start, x0) = match self.warm_start_point()? {
+            Some((context, x)) => (Some(context), x),
+            None => (None, self.x0.clone()),
+        };
+        let p0 = P::from_components(x0);
+        if let Some(cache) = self.cache.as_mut() {
+            cache.clear();
+        }
+        self.sample_stds.clear();
+        let mut state = state;
+        if self.keep_evaluations {
+            state.evaluations = Some(Vec::new());
+        }
+        let allocated = self.num_constraints.is_some() && warm_start.is_none();
+        if let (Some(nc), true) = (self.num_constraints, allocated) {
+            // Working arrays are allocated before the first evaluation
+            self.check_constraint_scaling(nc + 1)?;
+            self.check_constraint_senses(nc + 1)?;
+            self.check_eq_constraints(nc + 1)?;
+            self.create_context(&mut state, self.nb_constraints(nc + 1), true);
+            if state.status.is_some() {
+                return Ok((state, None));
+            }
+        }
+        let (mut fx0, context0) = match self.evaluate(problem, &p0, 0, &mut state) {
+            Ok(evaluation) => evaluation,
+            Err(err) => {
+                let CostPanic(reason) = err.downcast::<CostPanic>()?;
+                state.status = Some(Err(FailStatus::UnexpectedError));
+                state.termination_status =
+                    TerminationStatus::Terminated(TerminationReason::SolverExit(reason));
+                return Ok((state, None));
+            }
+        };
+        let x0 = p0.components();
+        if fx0.is_empty() {
+            return Err(invalid_parameter(
+                "cost function output should at least contain the objective value",
+            ));
+        }
+        if let Some(nc) = self.num_constraints
+            && fx0.len() != nc + 1
+        {
+            return Err(invalid_parameter(format!(
+                "cost function output size ({}) should be equal to 1 + the declared number of constraints ({nc})",
+                fx0.len()
+            )));
+        }
+        fx0 = self.nan_constraints_replaced(fx0);
+        self.check_constraint_scaling(fx0.len())?;
+        self.check_constraint_senses(fx0.len())?;
+        self.check_eq_constraints(fx0.len())?;
+        self.output_constraints = Some(fx0.len() - 1);
+        let m = self.nb_constraints(fx0.len());
+        let violation = self.max_violation(&x0, &fx0);
+        self.incumbent = if violation <= self.feasibility_tol {
+            self.objective(fx0[0])
+        } else {
+            f64::INFINITY
+        };
+        self.initial_objective = self.objective(fx0[0]);
+
+        let mut initial_state = state;
+        initial_state.variable_names = self.variable_names.clone();
+        initial_state.best_feasible =
+            (violation <= self.feasibility_tol).then(|| (x0.to_vec(), fx0.clone()));
+        self.restoration = None;
+        self.prefetched.clear();
+        self.refinements = 0;
+        self.polishing = None;
+        let resumed = warm_start.is_some();
+        match warm_start {
+            Some(context) => self.resume_context(&mut initial_state, context, m)?,
+            None if (self.restore_feasibility || self.feasibility_only)
+                && violation > self.feasibility_tol =>
+            {
+                // Feasibility is restored first by COBYLA without constraints
+                self.restoration = Some(Restoration {
+                    x: x0.to_vec(),
+                    fx: fx0.clone(),
+                    violation,
+                });
+                initial_state.feasibility_evals = initial_state.func_evals;
+                self.create_context(&mut initial_state, 0, true);
+            }
+            None if allocated => {}
+            None => self.create_context(&mut initial_state, m, true),
+        }
+        if !resumed {
+            self.set_start_point(&x0);
+        }
+        if !is_finite(&fx0) {
+            match self.non_finite_policy {
+                NonFinitePolicy::Penalty(penalty) => fx0 = self.penalized(fx0, penalty),
+                NonFinitePolicy::Abort => {
+                    let reason = format!("non-finite cost function output {fx0:?} at x0");
+                    initial_state.cobyla_context = None;
+                    initial_state.status = Some(Err(FailStatus::NonFiniteCost));
+                    initial_state =
+                        initial_state.terminate_with(TerminationReason::SolverExit(reason));
+                }
+            }
+        }
+
+        if initial_state.status.is_none()
+            && self.feasibility_only
+            && violation <= self.feasibility_tol
+        {
+            initial_state.cobyla_context = None;
+            initial_state.status = Some(Ok(SuccessStatus::Success));
+        }
+        if initial_state.status.is_none() && self.forced_stop(&x0, fx0[0]) {
+            initial_state.cobyla_context = None;
+            initial_state.status = Some(Err(FailStatus::ForcedStop));
+        }
+        if initial_state.status.is_none() && self.stopval_reached(fx0[0], violation) {
+            initial_state.cobyla_context = None;
+            initial_state.status = Some(Ok(SuccessStatus::StopValReached));
+        }
+        if self.batch_initial_simplex
+            && !resumed
+            && initial_state.status.is_none()
+            && let Err(err) = self.prefetch_vertices(problem, &mut initial_state)
+        {
+            let CostPanic(reason) = err.downcast::<CostPanic>()?;
+            initial_state.cobyla_context = None;
+            initial_state.status = Some(Err(FailStatus::UnexpectedError));
+            initial_state = initial_state.terminate_with(TerminationReason::SolverExit(reason));
+        }
+
+        if self.keep_history {
+            initial_state.history = Some(Vec::new());
+            initial_state.penalty_history = Some(Vec::new());
+        }
+        initial_state.record_history(0, fx0[0], violation);
+        initial_state.record_penalty(0);
+        initial_state.context = context0;
+        initial_state.max_violation = Some(violation);
+
+        initial_state.solver_time = self.start.map_or(Duration::ZERO, |start| start.elapsed());
+
+        let mut initial_state = self.selected_best(initial_state.param(p0).cost(fx0))?;
+        initial_state.sample_std = self.current_sample_std(&initial_state);
+        let kv = self.observed_values(&initial_state, KV::new());
+        Ok((initial_state, Some(kv)))
+    }
+
+    /// Computes a single iteration of the algorithm and has access to the optimization problem
+    /// definition and the internal state of the solver.
+    /// Returns an updated `state` and optionally a `KV` which holds key-value pairs used in
+    /// [Observers](`argmin::core::observers::Observe`).
+    fn next_iter(
+        &mut self,
+        problem: &mut Problem<O>,
+        state: CobylaState<P, C>,
+    ) -> std::result::Result<(CobylaState<P, C>, Option<KV>), argmin::core::Error> {
+        let (mut state, kv) = self.cobyla_iteration(problem, state)?;
+        if let Some(Ok(status)) = state.status
+            && self.polish_feasibility
+            && self.polishing.is_none()
+            && state.cobyla_context.is_none()
+            && state
+                .max_violation
+                .is_some_and(|violation| violation > self.feasibility_tol)
+        {
+            self.polish_from(&mut state, status);
+        }
+        let state = self.budget_terminated(state);
+        let mut state = self.selected_best(state)?;
+        state.sample_std = self.current_sample_std(&state);
+        if self.keep_simplex && self.restoration.is_none() {
+            self.record_simplex(&mut state);
+        }
+        state.solver_time = self.start.map_or(Duration::ZERO, |start| start.elapsed());
+        self.report_progress(&state);
+        let kv = self.observed_values(&state, kv.unwrap_or_default());
+        Ok((state, Some(kv)))
+    }
+
+    /// Used to implement stopping criteria, in particular criteria which are not covered by
+    /// ([`terminate_internal`](`Solver::terminate_internal`).
+    ///
+    /// This method has access to the internal state and returns an `TerminationReason`.
+    fn terminate(&mut self, state: &CobylaState<P, C>) -> TerminationStatus {
+        match state.status {
+            None => match (self.max_time, self.start) {
+                (Some(max_time), Some(start)) if start.elapsed() >= max_time => {
+                    TerminationStatus::Terminated(TerminationReason::Timeout)
+                }
+                _ => TerminationStatus::NotTerminated,
+            },
+            Some(Ok(
+                SuccessStatus::Success
+                | SuccessStatus::FtolReached
+                | SuccessStatus::XtolReached
+                | SuccessStatus::Stagnated
+                | SuccessStatus::FlatInterior,
+            )) => TerminationStatus::Terminated(TerminationReason::SolverConverged),
+            Some(Err(FailStatus::ForcedStop)) => {
+                TerminationStatus::Terminated(TerminationReason::Interrupt)
+            }
+            Some(Err(FailStatus::RoundoffLimited)) => {
+                let reason = match state.roundoff {
+                    Some(RoundoffInfo { rho, step }) => {
+                        format!("RoundoffLimited (rho = {rho:e}, last step = {step:e})")
+                    }
+                    None => "RoundoffLimited".to_string(),
+                };
+                TerminationStatus::Terminated(TerminationReason::SolverExit(reason))
+            }
+            Some(Ok(status)) => {
+                TerminationStatus::Terminated(TerminationReason::SolverExit(format!("{status:?}")))
+            }
+            Some(Err(status)) => {
+                TerminationStatus::Terminated(TerminationReason::SolverExit(format!("{status:?}")))
             }
         }
-        TerminationStatus::Terminated(argmin::core::TerminationReason::SolverExit(
-            "Unknown".to_string(),
-        ))
     }
 }
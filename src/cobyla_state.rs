@@ -1,10 +1,15 @@
-use crate::cobyla::cobyla_context_t;
+use crate::cobyla_context::CobylaContext;
+use crate::cobyla_error::CobylaError;
+use crate::cobyla_param::CobylaParam;
+use crate::{FailStatus, RoundoffInfo, SuccessStatus, TrustRegionStep, Verbosity};
 /// Implementation of `argmin::IterState` for Cobyla optimizer
 use argmin::core::{Problem, State, TerminationReason, TerminationStatus};
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::mem::ManuallyDrop;
+
+/// Default maximum number of iterations of [CobylaState::max_iters]
+pub const MAX_ITERS: u64 = 2000;
 
 /// Maintains the state from iteration to iteration of the [crate::CobylaSolver].
 ///
@@ -16,26 +21,35 @@ use std::mem::ManuallyDrop;
 /// * best parameter vector of current and previous iteration
 /// * cost function value (objective and constraint functions values) of current and previous iteration
 /// * current and previous best cost function value
+/// * maximum constraint violation of current and best cost function values
 /// * target cost function value
 /// * current iteration number
 /// * iteration number where the last best parameter vector was found
 /// * maximum number of iterations that will be executed
 /// * problem function evaluation counts
 /// * elapsed time
-/// * termination status
+/// * termination status (argmin and COBYLA ones)
 /// * COBYLA specific parameters: rhobeg, rhoend, iprint, maxfun
+/// * current trust region radius
+/// * number of cost function evaluations, solver and cost function evaluations times
+/// * evaluation cache hits and misses
+/// * history of evaluations (optional)
+/// * log of evaluated points and cost function outputs (optional)
+/// * user context of current and best evaluations (see [crate::Evaluation])
 ///
+/// The parameter vector type `P` is `Vec<f64>` by default (see [crate::CobylaParam]),
+/// the context type `C` is the one of the cost function output (see [crate::CobylaOutput]).
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
-pub struct CobylaState {
+pub struct CobylaState<P = Vec<f64>, C = ()> {
     /// Current parameter vector
-    pub param: Option<Vec<f64>>,
+    pub param: Option<P>,
     /// Previous parameter vector
-    pub prev_param: Option<Vec<f64>>,
+    pub prev_param: Option<P>,
     /// Current best parameter vector
-    pub best_param: Option<Vec<f64>>,
+    pub best_param: Option<P>,
     /// Previous best parameter vector
-    pub prev_best_param: Option<Vec<f64>>,
+    pub prev_best_param: Option<P>,
 
     /// Current cost function value
     pub cost: Option<Vec<f64>>,
@@ -45,6 +59,12 @@ pub struct CobylaState {
     pub best_cost: Option<Vec<f64>>,
     /// Previous best cost function value
     pub prev_best_cost: Option<Vec<f64>>,
+    /// Maximum constraint violation of the current cost function value (including
+    /// bounds and equality constraints), None when not computed
+    pub max_violation: Option<f64>,
+    /// Maximum constraint violation of the current best cost function value,
+    /// None when not computed
+    pub best_max_violation: Option<f64>,
     /// Target cost function value
     pub target_cost: f64,
 
@@ -69,12 +89,70 @@ pub struct CobylaState {
     pub iprint: i32,
     /// Cost function calls budget
     pub maxfun: i32,
+    /// Current trust region radius
+    pub rho: f64,
+    /// Current penalty parameter of the merit function
+    pub penalty: f64,
+    /// Number of cost function evaluations
+    pub func_evals: u64,
+    /// Number of cost function evaluations of the feasibility restoration phase
+    pub feasibility_evals: u64,
+    /// Number of cost function evaluations polishing the feasibility of the result
+    pub polish_evals: u64,
+    /// Wall-clock time elapsed since the solver initialization
+    pub solver_time: web_time::Duration,
+    /// Wall-clock time spent in cost function evaluations
+    pub eval_time: web_time::Duration,
+    /// History of evaluations as (iteration, objective, maximum constraint violation),
+    /// None when not recorded
+    pub history: Option<Vec<(u64, f64, f64)>>,
+    /// History of the penalty parameter as (iteration, penalty), None when not recorded
+    pub penalty_history: Option<Vec<(u64, f64)>>,
+    /// Evaluated parameter vectors with their cost function output, None when not recorded
+    pub evaluations: Option<Vec<(Vec<f64>, Vec<f64>)>>,
+    /// Sample standard deviations of the cost function outputs over the repeated
+    /// evaluations of the current parameter vector, None when not resampled
+    pub sample_std: Option<Vec<f64>>,
+    /// Sample standard deviations of the cost function outputs over the repeated
+    /// evaluations of the best parameter vector, None when not resampled
+    pub best_sample_std: Option<Vec<f64>>,
+    /// Number of evaluations retrieved from the evaluation cache
+    pub cache_hits: u64,
+    /// Number of evaluations not found in the evaluation cache
+    pub cache_misses: u64,
+    /// User context of the current cost function value, None when not available
+    pub context: Option<C>,
+    /// User context of the current best cost function value, None when not available
+    pub best_context: Option<C>,
+    /// Termination status set by COBYLA, None while running
+    pub status: Option<Result<SuccessStatus, FailStatus>>,
+    /// Diagnostics of a [FailStatus::RoundoffLimited] termination
+    pub roundoff: Option<RoundoffInfo>,
+    /// Number of restarts of COBYLA after a degenerate simplex
+    pub restarts: usize,
+    /// Decrease of the best feasible objective value over the last iteration
+    pub last_improvement: f64,
+    /// Vertices of the COBYLA simplex with their cost function output, None when not
+    /// recorded
+    pub simplex: Option<Vec<(Vec<f64>, Vec<f64>)>>,
+    /// Outcome of the last trust region step, None when not recorded
+    pub trust_region_step: Option<TrustRegionStep>,
+    /// Best feasible evaluated parameter vector components with their cost function output,
+    /// None when no feasible point was evaluated
+    pub best_feasible: Option<(Vec<f64>, Vec<f64>)>,
+    /// Names of x components, None when not given
+    /// (see [crate::CobylaSolverBuilder::with_named_variables])
+    pub variable_names: Option<Vec<String>>,
+    /// Initial changes of x components actually used by COBYLA, None when not started
+    pub effective_rhobeg: Option<Vec<f64>>,
+    /// Final changes of x components actually used by COBYLA, None when not started
+    pub effective_rhoend: Option<Vec<f64>>,
 
-    #[cfg_attr(feature = "serde1", serde(skip))]
-    pub cobyla_context: Option<ManuallyDrop<*mut cobyla_context_t>>,
+    /// COBYLA algorithm context (trust region radius, simplex and working arrays)
+    pub(crate) cobyla_context: Option<CobylaContext>,
 }
 
-impl CobylaState
+impl<P, C> CobylaState<P, C>
 where
     Self: State<Float = f64>,
 {
@@ -86,7 +164,7 @@ where
     /// ```
     /// # use argmin::core::{IterState, State};
     /// # use cobyla_argmin::CobylaState;
-    /// # let state = CobylaState::new();
+    /// # let state: CobylaState = CobylaState::new();
     /// # let param_old = vec![1.0f64, 2.0f64];
     /// # let state = state.param(param_old);
     /// # assert!(state.prev_param.is_none());
@@ -100,7 +178,7 @@ where
     /// # assert_eq!(state.param.as_ref().unwrap()[1].to_ne_bytes(), 3.0f64.to_ne_bytes());
     /// ```
     #[must_use]
-    pub fn param(mut self, param: Vec<f64>) -> Self {
+    pub fn param(mut self, param: P) -> Self {
         std::mem::swap(&mut self.prev_param, &mut self.param);
         self.param = Some(param);
         self
@@ -127,7 +205,8 @@ where
         self
     }
 
-    /// Set maximum number of iterations
+    /// Set maximum number of iterations (default: [MAX_ITERS]), the optimization stops
+    /// with [SuccessStatus::MaxItersReached] status when it is reached
     ///
     /// # Example
     ///
@@ -135,7 +214,7 @@ where
     /// # use cobyla_argmin::CobylaState;
     /// # use argmin::core::{State, ArgminFloat};
     /// # let state: CobylaState = CobylaState::new();
-    /// # assert_eq!(state.max_iters, std::u64::MAX);
+    /// # assert_eq!(state.max_iters, cobyla_argmin::MAX_ITERS);
     /// let state = state.max_iters(1000);
     /// # assert_eq!(state.max_iters, 1000);
     /// ```
@@ -169,6 +248,24 @@ where
         self
     }
 
+    /// Set level of printing as a [Verbosity], see [CobylaState::iprint] for the
+    /// lower-level `iprint` integer
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::{CobylaState, Verbosity};
+    /// # use argmin::core::State;
+    /// # let state: CobylaState = CobylaState::new();
+    /// let state = state.verbosity(Verbosity::Silent);
+    /// # assert_eq!(state.iprint, 0);
+    /// ```
+    #[must_use]
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.iprint = verbosity.iprint();
+        self
+    }
+
     /// Set the current cost function value. This shifts the stored cost function value to the
     /// previous cost function value.
     ///
@@ -228,6 +325,239 @@ where
         self.best_cost.as_ref()
     }
 
+    /// Returns constraint values at the current parameter vector (ie current cost function
+    /// output without the objective value), empty when not available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert!(state.constraints().is_empty());
+    /// # state.cost = Some(vec![12.0, 0.1, -0.2]);
+    /// let constraints = state.constraints();
+    /// # assert_eq!(constraints, &[0.1, -0.2]);
+    /// ```
+    pub fn constraints(&self) -> &[f64] {
+        self.cost.as_ref().and_then(|c| c.get(1..)).unwrap_or(&[])
+    }
+
+    /// Returns constraint values at the best parameter vector (ie best cost function
+    /// output without the objective value), empty when not available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert!(state.best_constraints().is_empty());
+    /// # state.best_cost = Some(vec![12.0, 0.1, -0.2]);
+    /// let constraints = state.best_constraints();
+    /// # assert_eq!(constraints, &[0.1, -0.2]);
+    /// ```
+    pub fn best_constraints(&self) -> &[f64] {
+        self.best_cost
+            .as_ref()
+            .and_then(|c| c.get(1..))
+            .unwrap_or(&[])
+    }
+
+    /// Returns the number of constraints of the cost function output, None when no cost
+    /// function value is available
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert_eq!(state.num_constraints(), None);
+    /// # state.best_cost = Some(vec![12.0, 0.1, -0.2]);
+    /// let num_constraints = state.num_constraints();
+    /// # assert_eq!(num_constraints, Some(2));
+    /// ```
+    pub fn num_constraints(&self) -> Option<usize> {
+        let cost = self.best_cost.as_ref().or(self.cost.as_ref())?;
+        Some(cost.len().saturating_sub(1))
+    }
+
+    /// Returns the maximum constraint violation `max(0, -c_i(x))` at the best parameter
+    /// vector, 0 when all constraints are satisfied.
+    ///
+    /// When computed by the solver, bounds and equality constraints (relaxed by their
+    /// tolerance) are taken into account, otherwise it is computed from the best
+    /// constraint values (see [CobylaState::best_constraints]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.best_cost = Some(vec![12.0, 0.1, -0.2]);
+    /// let violation = state.best_max_constraint_violation();
+    /// # assert_eq!(violation, 0.2);
+    /// # state.best_max_violation = Some(0.3);
+    /// # assert_eq!(state.best_max_constraint_violation(), 0.3);
+    /// ```
+    pub fn best_max_constraint_violation(&self) -> f64 {
+        self.best_max_violation
+            .unwrap_or_else(|| max_constraint_violation(self.best_constraints()))
+    }
+
+    /// Returns whether the best parameter vector satisfies the constraints up to the
+    /// given tolerance on the maximum constraint violation
+    /// (see [CobylaState::best_max_constraint_violation])
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.best_cost = Some(vec![12.0, 0.1, -1e-8]);
+    /// assert!(state.is_feasible(1e-6));
+    /// # assert!(!state.is_feasible(0.));
+    /// ```
+    pub fn is_feasible(&self, tol: f64) -> bool {
+        self.best_max_constraint_violation() <= tol
+    }
+
+    /// Returns the indices of the constraints active at the best parameter vector, ie
+    /// within the given tolerance of their boundary `|c_i(x)| <= tol`, in the order of the
+    /// constraint values (see [CobylaState::best_constraints]).
+    ///
+    /// Bounds are not taken into account, equality constraints are active when satisfied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.best_cost = Some(vec![12.0, 0.1, -1e-8, 2e-7]);
+    /// let active = state.active_constraints(1e-6);
+    /// # assert_eq!(active, vec![1, 2]);
+    /// ```
+    pub fn active_constraints(&self, tol: f64) -> Vec<usize> {
+        self.best_constraints()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.abs() <= tol)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the history of evaluations as (iteration, objective, maximum constraint
+    /// violation) tuples, iteration 0 being the initial guess evaluation.
+    /// Empty unless enabled with [crate::CobylaSolverBuilder::keep_history].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert!(state.history().is_empty());
+    /// # state.history = Some(vec![(0, 12.0, 0.1)]);
+    /// let history = state.history();
+    /// # assert_eq!(history, &[(0, 12.0, 0.1)]);
+    /// ```
+    pub fn history(&self) -> &[(u64, f64, f64)] {
+        self.history.as_deref().unwrap_or(&[])
+    }
+
+    /// Returns the history of the COBYLA penalty parameter of the merit function
+    /// (see [CobylaState::current_penalty]) as (iteration, penalty) tuples, iteration 0
+    /// being the initial guess evaluation: a penalty increase shows that the constraints
+    /// dominate the merit function. Empty unless enabled with
+    /// [crate::CobylaSolverBuilder::keep_history].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert!(state.penalty_history().is_empty());
+    /// # state.penalty_history = Some(vec![(0, 0.), (1, 2.5)]);
+    /// let penalties = state.penalty_history();
+    /// # assert_eq!(penalties, &[(0, 0.), (1, 2.5)]);
+    /// ```
+    pub fn penalty_history(&self) -> &[(u64, f64)] {
+        self.penalty_history.as_deref().unwrap_or(&[])
+    }
+
+    /// Returns the evaluated parameter vectors with their cost function output
+    /// (objective followed by constraint values, as returned by the cost function)
+    /// in the order of the cost function calls.
+    /// Empty unless enabled with [crate::CobylaSolverBuilder::keep_evaluations].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert!(state.evaluations().is_empty());
+    /// # state.evaluations = Some(vec![(vec![1.0, 2.0], vec![12.0, 0.1])]);
+    /// let evaluations = state.evaluations();
+    /// # assert_eq!(evaluations, &[(vec![1.0, 2.0], vec![12.0, 0.1])]);
+    /// ```
+    pub fn evaluations(&self) -> &[(Vec<f64>, Vec<f64>)] {
+        self.evaluations.as_deref().unwrap_or(&[])
+    }
+
+    /// Appends the cost function output at x to the evaluations log when recorded
+    pub(crate) fn record_evaluation(&mut self, x: &[f64], fx: &[f64]) {
+        if let Some(evaluations) = self.evaluations.as_mut() {
+            evaluations.push((x.to_vec(), fx.to_vec()));
+        }
+    }
+
+    /// Appends evaluation at given iteration to the history when recorded
+    pub(crate) fn record_history(&mut self, iter: u64, f: f64, violation: f64) {
+        if let Some(history) = self.history.as_mut() {
+            history.push((iter, f, violation));
+        }
+    }
+
+    /// Appends the current penalty parameter at given iteration to its history when recorded
+    pub(crate) fn record_penalty(&mut self, iter: u64) {
+        if let Some(history) = self.penalty_history.as_mut() {
+            history.push((iter, self.penalty));
+        }
+    }
+
+    /// Returns the user context attached to the current cost function value.
+    ///
+    /// It is the context of the last evaluation (see [crate::Evaluation]), None when
+    /// the cost function output has no context, when the evaluation was retrieved from
+    /// the evaluation cache or when the state was moved to a previously evaluated point.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState<Vec<f64>, String> = CobylaState::new();
+    /// # assert!(state.context().is_none());
+    /// # state.context = Some("data".to_string());
+    /// let context = state.context();
+    /// # assert_eq!(context.unwrap(), "data");
+    /// ```
+    pub fn context(&self) -> Option<&C> {
+        self.context.as_ref()
+    }
+
+    /// Returns the user context attached to the best cost function value, see
+    /// [CobylaState::context]
+    pub fn best_context(&self) -> Option<&C> {
+        self.best_context.as_ref()
+    }
+
     /// Returns the rho start value
     pub fn rhobeg(&self) -> f64 {
         self.rhobeg
@@ -243,15 +573,426 @@ where
         self.iprint
     }
 
-    /// Returns cost function calls budget
+    /// Returns cost function calls budget given to COBYLA: `max_iters` saturated to
+    /// `i32::MAX`
     pub fn get_maxfun(&self) -> i32 {
-        self.max_iters as i32
+        self.max_iters.min(i32::MAX as u64) as i32
+    }
+
+    /// Returns the current trust region radius of COBYLA.
+    ///
+    /// The radius is decreased from `rhobeg` down to `rhoend` along iterations, it is
+    /// expressed in the space where COBYLA is run (ie scaled x when scaling is used).
+    /// It is also given to observers with the `rho` key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.rho = 0.1;
+    /// let rho = state.current_rho();
+    /// # assert_eq!(rho, 0.1);
+    /// ```
+    pub fn current_rho(&self) -> f64 {
+        self.rho
+    }
+
+    /// Returns the current penalty parameter `mu` of the COBYLA merit function
+    /// (see [merit_value]), zero until COBYLA increases it unless an initial value is
+    /// given with [crate::CobylaSolverBuilder::initial_penalty].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.penalty = 2.;
+    /// let penalty = state.current_penalty();
+    /// # assert_eq!(penalty, 2.);
+    /// ```
+    pub fn current_penalty(&self) -> f64 {
+        self.penalty
+    }
+
+    /// Returns the value of the COBYLA merit function for the given objective and
+    /// constraint values with the current penalty parameter (see [merit_value]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.penalty = 2.;
+    /// let merit = state.merit_value(12., &[0.1, -0.2]);
+    /// # assert_eq!(merit, 12.4);
+    /// ```
+    pub fn merit_value(&self, objective: f64, constraints: &[f64]) -> f64 {
+        merit_value(objective, constraints, self.penalty)
+    }
+
+    /// Returns the number of cost function evaluations done so far
+    ///
+    /// Contrary to iterations counted by argmin, it is the actual budget consumed
+    /// by the optimization.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.func_evals = 12;
+    /// let func_evals = state.func_evals();
+    /// # assert_eq!(func_evals, 12);
+    /// ```
+    pub fn func_evals(&self) -> u64 {
+        self.func_evals
+    }
+
+    /// Returns the number of cost function evaluations of the feasibility restoration
+    /// phase (see [crate::CobylaSolverBuilder::restore_feasibility]), the other ones
+    /// being the ones of the optimization phase
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.feasibility_evals = 5;
+    /// let feasibility_evals = state.feasibility_evals();
+    /// # assert_eq!(feasibility_evals, 5);
+    /// ```
+    pub fn feasibility_evals(&self) -> u64 {
+        self.feasibility_evals
+    }
+
+    /// Returns the number of extra cost function evaluations polishing the feasibility
+    /// of the result (see [crate::CobylaSolverBuilder::polish_feasibility]), included
+    /// in [CobylaState::func_evals]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.polish_evals = 3;
+    /// let polish_evals = state.polish_evals();
+    /// # assert_eq!(polish_evals, 3);
+    /// ```
+    pub fn polish_evals(&self) -> u64 {
+        self.polish_evals
+    }
+
+    /// Returns the wall-clock time elapsed since the solver initialization, updated
+    /// at each iteration
+    pub fn solver_time(&self) -> web_time::Duration {
+        self.solver_time
+    }
+
+    /// Returns the wall-clock time spent in cost function evaluations, the remaining
+    /// part of [CobylaState::solver_time] being spent by the solver bookkeeping
+    pub fn eval_time(&self) -> web_time::Duration {
+        self.eval_time
+    }
+
+    /// Returns the average wall-clock time of a cost function evaluation,
+    /// None when no evaluation was done
+    pub fn mean_eval_time(&self) -> Option<web_time::Duration> {
+        u32::try_from(self.func_evals)
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| self.eval_time / n)
+    }
+
+    /// Returns the number of evaluations retrieved from the evaluation cache
+    /// (see [crate::CobylaSolverBuilder::with_cache])
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits
+    }
+
+    /// Returns the number of evaluations not found in the evaluation cache, hence
+    /// actually computed (see [crate::CobylaSolverBuilder::with_cache])
+    pub fn cache_misses(&self) -> u64 {
+        self.cache_misses
+    }
+
+    /// Returns the sample standard deviations of the cost function outputs (objective
+    /// followed by constraints) over the repeated evaluations of the best parameter vector,
+    /// None when not resampled (see [crate::CobylaSolverBuilder::resample])
+    pub fn best_sample_std(&self) -> Option<&[f64]> {
+        self.best_sample_std.as_deref()
+    }
+
+    /// Returns the COBYLA termination status or None if optimization is not terminated.
+    ///
+    /// When optimization is stopped by argmin rather than COBYLA, the argmin termination
+    /// reason is converted: maximum iterations as `MaxItersReached`, target cost as
+    /// `StopValReached`, timeout as `MaxTimeReached` and interruption as `ForcedStop`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::{CobylaState, SuccessStatus};
+    /// # use argmin::core::{State, TerminationReason};
+    /// # let state: CobylaState = CobylaState::new();
+    /// # assert_eq!(state.termination_status(), None);
+    /// let state = state.terminate_with(TerminationReason::MaxItersReached);
+    /// assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::MaxItersReached)));
+    /// ```
+    pub fn termination_status(&self) -> Option<Result<SuccessStatus, FailStatus>> {
+        if self.status.is_some() {
+            return self.status;
+        }
+        match self.get_termination_reason()? {
+            TerminationReason::MaxItersReached => Some(Ok(SuccessStatus::MaxItersReached)),
+            TerminationReason::TargetCostReached => Some(Ok(SuccessStatus::StopValReached)),
+            TerminationReason::Timeout => Some(Ok(SuccessStatus::MaxTimeReached)),
+            TerminationReason::Interrupt => Some(Err(FailStatus::ForcedStop)),
+            TerminationReason::SolverConverged => Some(Ok(SuccessStatus::Success)),
+            TerminationReason::SolverExit(_) => Some(Err(FailStatus::Failure)),
+        }
+    }
+
+    /// Returns the failed termination status as a [CobylaError], None when the optimization
+    /// is not terminated or succeeded
+    pub fn termination_error(&self) -> Option<CobylaError> {
+        self.termination_status()?.err().map(CobylaError::from)
+    }
+
+    /// Returns the trust region radius and last step length when COBYLA stopped because
+    /// of rounding errors ([FailStatus::RoundoffLimited]), None otherwise
+    pub fn roundoff(&self) -> Option<RoundoffInfo> {
+        self.roundoff
+    }
+
+    /// Returns the number of restarts of COBYLA after a degenerate simplex
+    /// (see [crate::CobylaSolverBuilder::max_restarts])
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// Returns the decrease of the best feasible objective value over the last iteration:
+    /// zero when the iteration did not improve it, infinite when the first feasible point
+    /// was found at that iteration
+    pub fn last_improvement(&self) -> f64 {
+        self.last_improvement
+    }
+
+    /// Returns the `n + 1` vertices of the COBYLA simplex with their cost function output
+    /// (objective followed by constraints) at the last iteration where it was complete,
+    /// the best vertex being the last one, when recorded (see
+    /// [crate::CobylaSolverBuilder::keep_simplex]).
+    ///
+    /// The simplex of the feasibility restoration phase is not recorded.
+    pub fn simplex(&self) -> Option<&[(Vec<f64>, Vec<f64>)]> {
+        self.simplex.as_deref()
+    }
+
+    /// Returns the predicted and actual reductions of the last trust region step of
+    /// COBYLA and whether it was accepted, when recorded along with the simplex (see
+    /// [crate::CobylaSolverBuilder::keep_simplex]). None before the first trust region
+    /// step, ie while the initial simplex is evaluated.
+    pub fn trust_region_step(&self) -> Option<TrustRegionStep> {
+        self.trust_region_step
+    }
+
+    /// Returns the initial changes of x components actually used by COBYLA, None when
+    /// COBYLA was not started (or resumed with [crate::CobylaSolverBuilder::warm_start]).
+    ///
+    /// They are computed from [crate::RhoBeg] (with its floor for [crate::RhoBeg::Relative])
+    /// and the scaling of x components: COBYLA runs with a single trust region radius in a
+    /// scaled space and the change of `x[j]` is that radius times the scale of `x[j]`.
+    pub fn effective_rhobeg(&self) -> Option<&[f64]> {
+        self.effective_rhobeg.as_deref()
+    }
+
+    /// Returns the final changes of x components actually used by COBYLA (see
+    /// [CobylaState::effective_rhobeg]), computed from `rhoend` and the tolerances on x
+    /// (see [crate::StopTols])
+    pub fn effective_rhoend(&self) -> Option<&[f64]> {
+        self.effective_rhoend.as_deref()
+    }
+
+    /// Returns the parameter vector components and the objective value of the best
+    /// evaluated point satisfying the constraints up to the feasibility tolerance
+    /// (see [crate::CobylaSolverBuilder::feasibility_tol]), None when no feasible point
+    /// was evaluated.
+    ///
+    /// Contrary to the best point of COBYLA which minimizes its merit function and may
+    /// be slightly infeasible, this point is strictly feasible but its objective may be
+    /// marginally worse (see [crate::CobylaSolverBuilder::prefer_feasible]).
+    pub fn best_feasible(&self) -> Option<(Vec<f64>, f64)> {
+        self.best_feasible
+            .as_ref()
+            .map(|(x, fx)| (x.clone(), fx[0]))
     }
 }
 
-impl State for CobylaState {
+impl<P: CobylaParam, C> CobylaState<P, C>
+where
+    Self: State<Float = f64>,
+{
+    /// Returns a human readable summary of the optimization result: termination status,
+    /// best point, objective value and constraint violation, final trust region radius
+    /// and evaluation counts (same as the `Display` implementation).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let state: CobylaState = CobylaState::new();
+    /// let summary = state.summary();
+    /// assert!(summary.contains("not terminated"));
+    /// ```
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the components of the best parameter vector with their names when variables
+    /// are named (see [crate::CobylaSolverBuilder::with_named_variables]), empty otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # state.variable_names = Some(vec!["length".to_string(), "angle".to_string()]);
+    /// # state.best_param = Some(vec![1.5, 0.2]);
+    /// let best = state.best_param_named();
+    /// # assert_eq!(best, vec![("length".to_string(), 1.5), ("angle".to_string(), 0.2)]);
+    /// ```
+    pub fn best_param_named(&self) -> Vec<(String, f64)> {
+        match (self.variable_names.as_ref(), self.best_param.as_ref()) {
+            (Some(names), Some(x)) => names
+                .iter()
+                .cloned()
+                .zip(x.components().iter().copied())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the index of the x component of the given name, None when not found
+    /// or when variables are not named
+    /// (see [crate::CobylaSolverBuilder::with_named_variables])
+    pub fn variable_index(&self, name: &str) -> Option<usize> {
+        self.variable_names.as_ref()?.iter().position(|n| n == name)
+    }
+
+    /// Returns the number of variables, None when no parameter vector is available
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cobyla_argmin::CobylaState;
+    /// # use argmin::core::State;
+    /// # let mut state: CobylaState = CobylaState::new();
+    /// # assert_eq!(state.dim(), None);
+    /// # state.best_param = Some(vec![1.5, 0.2]);
+    /// let dim = state.dim();
+    /// # assert_eq!(dim, Some(2));
+    /// ```
+    pub fn dim(&self) -> Option<usize> {
+        let x = self.best_param.as_ref().or(self.param.as_ref())?;
+        Some(x.components().len())
+    }
+}
+
+impl<P: CobylaParam, C> std::fmt::Display for CobylaState<P, C>
+where
+    Self: State<Float = f64>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "COBYLA result:")?;
+        match self.termination_status() {
+            Some(Ok(status)) => writeln!(f, "    status:          {status:?}")?,
+            Some(Err(status)) => writeln!(f, "    status:          {status:?} (failure)")?,
+            None => writeln!(f, "    status:          not terminated")?,
+        }
+        if let Some(TerminationReason::SolverExit(reason)) = self.get_termination_reason() {
+            writeln!(f, "    reason:          {reason}")?;
+        }
+        match self.best_param.as_ref() {
+            Some(x) => writeln!(f, "    best param:      {:?}", x.components())?,
+            None => writeln!(f, "    best param:      none")?,
+        }
+        writeln!(f, "    best objective:  {}", self.get_best_cost())?;
+        if self.best_constraints().is_empty() && self.best_max_violation.is_none() {
+            writeln!(f, "    max violation:   none (unconstrained)")?;
+        } else {
+            writeln!(
+                f,
+                "    max violation:   {:e}",
+                self.best_max_constraint_violation()
+            )?;
+        }
+        writeln!(f, "    final rho:       {:e}", self.rho)?;
+        if let (Some(rhobeg), Some(rhoend)) = (&self.effective_rhobeg, &self.effective_rhoend) {
+            writeln!(f, "    rhobeg:          {rhobeg:?}")?;
+            writeln!(f, "    rhoend:          {rhoend:?}")?;
+        }
+        writeln!(f, "    iterations:      {}", self.iter)?;
+        if self.feasibility_evals > 0 {
+            writeln!(
+                f,
+                "    func evals:      {} ({} restoring feasibility)",
+                self.func_evals, self.feasibility_evals
+            )?;
+        } else {
+            writeln!(f, "    func evals:      {}", self.func_evals)?;
+        }
+        if self.cache_hits > 0 {
+            writeln!(f, "    cache hits:      {}", self.cache_hits)?;
+        }
+        writeln!(f, "    solver time:     {:?}", self.solver_time)?;
+        match self.mean_eval_time() {
+            Some(mean) => writeln!(
+                f,
+                "    eval time:       {:?} ({:?} per evaluation)",
+                self.eval_time, mean
+            )?,
+            None => writeln!(f, "    eval time:       {:?}", self.eval_time)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns the maximum violation of constraints `c(x) >= 0` given their values
+pub(crate) fn max_constraint_violation(c: &[f64]) -> f64 {
+    c.iter().fold(0., |acc: f64, &ci| acc.max(-ci))
+}
+
+/// Returns the value of the merit function used by COBYLA to compare points:
+/// `objective + penalty * max(0, -c_i)` given the constraint values `c_i`.
+///
+/// COBYLA applies it to the values it is run with: the objective to be minimized (ie
+/// negated when maximizing, see [crate::Sense]) and the constraints `c_i(x) >= 0` after
+/// the conversions of the solver (constraint senses and scaling, equality constraints
+/// as two inequalities and bounds as additional constraints). Without such settings,
+/// they are the cost function output values.
+///
+/// ```
+/// use cobyla_argmin::merit_value;
+///
+/// // a feasible point is better than an infeasible one of lower objective value
+/// // as soon as the penalty is large enough
+/// assert!(merit_value(1., &[0.5], 10.) < merit_value(0., &[-0.2], 10.));
+/// ```
+pub fn merit_value(objective: f64, constraints: &[f64], penalty: f64) -> f64 {
+    objective + penalty * max_constraint_violation(constraints)
+}
+
+impl<P: Clone, C: Clone> State for CobylaState<P, C> {
     /// Type of parameter vector
-    type Param = Vec<f64>;
+    type Param = P;
     /// Floating point precision
     type Float = f64;
 
@@ -278,7 +1019,7 @@ impl State for CobylaState {
     /// # assert_eq!(state.target_cost, f64::NEG_INFINITY);
     /// # assert_eq!(state.iter, 0);
     /// # assert_eq!(state.last_best_iter, 0);
-    /// # assert_eq!(state.max_iters, std::u64::MAX);
+    /// # assert_eq!(state.max_iters, cobyla_argmin::MAX_ITERS);
     /// # assert_eq!(state.counts, HashMap::new());
     /// # assert_eq!(state.time.unwrap(), web_time::Duration::new(0, 0));
     /// # assert_eq!(state.termination_status, TerminationStatus::NotTerminated);
@@ -294,11 +1035,13 @@ impl State for CobylaState {
             prev_cost: None,
             best_cost: None,
             prev_best_cost: None,
+            max_violation: None,
+            best_max_violation: None,
             target_cost: f64::NEG_INFINITY,
 
             iter: 0,
             last_best_iter: 0,
-            max_iters: u64::MAX,
+            max_iters: MAX_ITERS,
             counts: HashMap::new(),
             time: Some(web_time::Duration::new(0, 0)),
             termination_status: TerminationStatus::NotTerminated,
@@ -307,6 +1050,32 @@ impl State for CobylaState {
             rhoend: 1e-4,
             iprint: 1,
             maxfun: 2000,
+            rho: 0.,
+            penalty: 0.,
+            func_evals: 0,
+            feasibility_evals: 0,
+            polish_evals: 0,
+            solver_time: web_time::Duration::ZERO,
+            eval_time: web_time::Duration::ZERO,
+            history: None,
+            penalty_history: None,
+            evaluations: None,
+            sample_std: None,
+            best_sample_std: None,
+            cache_hits: 0,
+            cache_misses: 0,
+            context: None,
+            best_context: None,
+            status: None,
+            roundoff: None,
+            restarts: 0,
+            last_improvement: 0.,
+            simplex: None,
+            trust_region_step: None,
+            variable_names: None,
+            best_feasible: None,
+            effective_rhobeg: None,
+            effective_rhoend: None,
 
             cobyla_context: None,
         }
@@ -343,6 +1112,9 @@ impl State for CobylaState {
             }
             std::mem::swap(&mut self.prev_best_cost, &mut self.best_cost);
             self.best_cost = Some(cost.clone());
+            self.best_context = self.context.clone();
+            self.best_max_violation = self.max_violation;
+            self.best_sample_std = self.sample_std.clone();
             self.last_best_iter = self.iter;
         }
     }
@@ -363,7 +1135,7 @@ impl State for CobylaState {
     /// # assert_eq!(param.as_ref().unwrap()[0].to_ne_bytes(), 1.0f64.to_ne_bytes());
     /// # assert_eq!(param.as_ref().unwrap()[1].to_ne_bytes(), 2.0f64.to_ne_bytes());
     /// ```
-    fn get_param(&self) -> Option<&Vec<f64>> {
+    fn get_param(&self) -> Option<&P> {
         self.param.as_ref()
     }
 
@@ -385,7 +1157,7 @@ impl State for CobylaState {
     /// # assert_eq!(best_param.as_ref().unwrap()[0].to_ne_bytes(), 1.0f64.to_ne_bytes());
     /// # assert_eq!(best_param.as_ref().unwrap()[1].to_ne_bytes(), 2.0f64.to_ne_bytes());
     /// ```
-    fn get_best_param(&self) -> Option<&Vec<f64>> {
+    fn get_best_param(&self) -> Option<&P> {
         self.best_param.as_ref()
     }
 
@@ -533,7 +1305,7 @@ impl State for CobylaState {
     /// ```
     /// # use argmin::core::{State, ArgminFloat, TerminationStatus};
     /// # use cobyla_argmin::CobylaState;
-    /// # let mut state = CobylaState::new();
+    /// # let mut state: CobylaState = CobylaState::new();
     /// let termination_status = state.get_termination_status();
     /// # assert_eq!(*termination_status, TerminationStatus::NotTerminated);
     /// ```
@@ -548,7 +1320,7 @@ impl State for CobylaState {
     /// ```
     /// # use argmin::core::{State, ArgminFloat, TerminationReason};
     /// # use cobyla_argmin::CobylaState;
-    /// # let mut state = CobylaState::new();
+    /// # let mut state: CobylaState = CobylaState::new();
     /// let termination_reason = state.get_termination_reason();
     /// # assert_eq!(termination_reason, None);
     /// ```
@@ -0,0 +1,132 @@
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_problem::CobylaOutput;
+use crate::cobyla_solver::CobylaSolver;
+use crate::cobyla_state::CobylaState;
+
+use argmin::core::{
+    CostFunction, Error, Problem, SendAlias, Solver, State, SyncAlias, TerminationStatus,
+};
+
+/// Step by step execution of [CobylaSolver], an alternative to the argmin `Executor`
+/// which runs the whole optimization at once.
+///
+/// Each call to [CobylaStepper::step] computes one iteration (the first one initializing
+/// the solver) with the same termination rules as the `Executor` (solver termination,
+/// maximum number of iterations, target cost), so that COBYLA can be driven from a larger
+/// control loop with custom stopping rules, UI updates, ... Observers, checkpointing and
+/// the `Executor` timeout are not available.
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, State};
+/// use cobyla_argmin::{CobylaSolver, CobylaStepper};
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+///     }
+/// }
+///
+/// let solver = CobylaSolver::builder().initial_param(vec![1., 1.]).iprint(0).build();
+/// let mut stepper = CobylaStepper::new(ParaboloidProblem, solver)
+///     .configure(|state| state.max_iters(200));
+/// loop {
+///     let step = stepper.step().unwrap();
+///     // Custom stopping rule
+///     if step.terminated || step.state.get_best_cost() < 10.01 {
+///         break;
+///     }
+/// }
+/// assert!(stepper.state().get_best_cost() < 10.01);
+/// ```
+pub struct CobylaStepper<O, P = Vec<f64>, C = ()> {
+    problem: Problem<O>,
+    solver: CobylaSolver,
+    state: Option<CobylaState<P, C>>,
+    initialized: bool,
+}
+
+const STATE_LOST: &str = "state is lost after a failed step";
+
+/// Result of a [CobylaStepper::step]
+#[derive(Debug)]
+pub struct StepResult<'a, P, C> {
+    /// State after the step
+    pub state: &'a CobylaState<P, C>,
+    /// Whether the optimization is terminated, further steps leaving the state unchanged
+    pub terminated: bool,
+}
+
+impl<O, P, C> CobylaStepper<O, P, C>
+where
+    O: CostFunction<Param = P> + SyncAlias,
+    O::Output: CobylaOutput<Context = C> + SendAlias,
+    P: CobylaParam + SyncAlias,
+    C: Clone,
+{
+    /// Constructor given the problem and the solver
+    pub fn new(problem: O, solver: CobylaSolver) -> Self {
+        CobylaStepper {
+            problem: Problem::new(problem),
+            solver,
+            state: Some(CobylaState::new()),
+            initialized: false,
+        }
+    }
+
+    /// Configures the initial state (maximum number of iterations, ...) as with
+    /// argmin `Executor::configure`
+    #[must_use]
+    pub fn configure<F: FnOnce(CobylaState<P, C>) -> CobylaState<P, C>>(mut self, init: F) -> Self {
+        self.state = self.state.take().map(init);
+        self
+    }
+
+    /// Computes the next iteration, the first step initializing the solver, and returns
+    /// the resulting state with whether the optimization is terminated.
+    ///
+    /// The state is lost when the step fails: the stepper should not be used anymore.
+    pub fn step(&mut self) -> Result<StepResult<'_, P, C>, Error> {
+        let mut state = self.state.take().expect(STATE_LOST);
+        if !state.terminated() {
+            state = if self.initialized {
+                let (mut state, _) = self.solver.next_iter(&mut self.problem, state)?;
+                state.func_counts(&self.problem);
+                state.update();
+                state.increment_iter();
+                state
+            } else {
+                self.initialized = true;
+                let (mut state, _) = self.solver.init(&mut self.problem, state)?;
+                state.update();
+                state.func_counts(&self.problem);
+                state
+            };
+            if !state.terminated()
+                && let TerminationStatus::Terminated(reason) =
+                    Solver::<O, _>::terminate_internal(&mut self.solver, &state)
+            {
+                state = state.terminate_with(reason);
+            }
+        }
+        let state = self.state.insert(state);
+        Ok(StepResult {
+            terminated: state.terminated(),
+            state,
+        })
+    }
+
+    /// Returns the current state
+    pub fn state(&self) -> &CobylaState<P, C> {
+        self.state.as_ref().expect(STATE_LOST)
+    }
+
+    /// Returns the problem, the solver and the final state
+    pub fn into_parts(mut self) -> (O, CobylaSolver, CobylaState<P, C>) {
+        let problem = self.problem.take_problem().unwrap();
+        (problem, self.solver, self.state.take().expect(STATE_LOST))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::Verbosity;
+use crate::cobyla_param::CobylaParam;
+use crate::cobyla_problem::CobylaOutput;
+use crate::cobyla_solver::CobylaSolver;
+use crate::cobyla_state::CobylaState;
+
+use argmin::core::{CostFunction, Executor, SendAlias, State, SyncAlias};
+
+/// Runs [CobylaSolver] with default settings from `x0` on the given problem and asserts
+/// the result is a feasible point within `tol` of the analytic optimum, then returns the
+/// final state for further checks.
+///
+/// The best parameter vector is compared component-wise to `expected_x` and the best
+/// objective value to `expected_f`, either of them being skipped when `None`. The maximum
+/// constraint violation of the best point has to be lower than `tol` as well (see
+/// [CobylaState::is_feasible]). Use [assert_solver_converges_to] to check a run with
+/// custom solver settings.
+///
+/// Available with the `testing` feature.
+///
+/// ```
+/// use argmin::core::{CostFunction, Error};
+/// use cobyla_argmin::assert_converges_to;
+///
+/// struct ParaboloidProblem;
+/// impl CostFunction for ParaboloidProblem {
+///     type Param = Vec<f64>;
+///     type Output = Vec<f64>;
+///
+///     // Minimize 10*(x0+1)^2 + x1^2 subject to x0 >= 0
+///     fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(vec![10. * (x[0] + 1.).powf(2.) + x[1].powf(2.), x[0]])
+///     }
+/// }
+///
+/// assert_converges_to(ParaboloidProblem, vec![1., 1.], Some(&[0., 0.]), Some(10.), 1e-2);
+/// ```
+///
+/// # Panics
+///
+/// When the optimization fails or the result is not within `tol` of the expected optimum.
+#[track_caller]
+pub fn assert_converges_to<O, P>(
+    problem: O,
+    x0: P,
+    expected_x: Option<&[f64]>,
+    expected_f: Option<f64>,
+    tol: f64,
+) -> CobylaState<P, <O::Output as CobylaOutput>::Context>
+where
+    O: CostFunction<Param = P> + SyncAlias,
+    O::Output: CobylaOutput + SendAlias,
+    <O::Output as CobylaOutput>::Context: Clone,
+    P: CobylaParam + SyncAlias,
+{
+    let solver = CobylaSolver::builder()
+        .initial_param(x0)
+        .verbosity(Verbosity::Silent)
+        .build();
+    assert_solver_converges_to(problem, solver, expected_x, expected_f, tol)
+}
+
+/// Runs the given solver (to be set with an initial guess) on the problem and asserts the
+/// result is a feasible point within `tol` of the analytic optimum as
+/// [assert_converges_to] does, then returns the final state.
+///
+/// Available with the `testing` feature.
+///
+/// # Panics
+///
+/// When the optimization fails or the result is not within `tol` of the expected optimum.
+#[track_caller]
+pub fn assert_solver_converges_to<O, P>(
+    problem: O,
+    solver: CobylaSolver,
+    expected_x: Option<&[f64]>,
+    expected_f: Option<f64>,
+    tol: f64,
+) -> CobylaState<P, <O::Output as CobylaOutput>::Context>
+where
+    O: CostFunction<Param = P> + SyncAlias,
+    O::Output: CobylaOutput + SendAlias,
+    <O::Output as CobylaOutput>::Context: Clone,
+    P: CobylaParam + SyncAlias,
+{
+    let state: CobylaState<P, _> = match Executor::new(problem, solver).run() {
+        Ok(res) => res.state,
+        Err(err) => panic!("optimization failed: {err}"),
+    };
+
+    let Some(best_param) = state.best_param.as_ref() else {
+        panic!("best parameter vector is not available")
+    };
+    let x = best_param.components();
+    if let Some(expected_x) = expected_x {
+        assert_eq!(
+            x.len(),
+            expected_x.len(),
+            "best parameter vector has {} components, expected {}",
+            x.len(),
+            expected_x.len()
+        );
+        for (i, (xi, ei)) in x.iter().zip(expected_x).enumerate() {
+            assert!(
+                (xi - ei).abs() <= tol,
+                "best x[{i}] = {xi} is not within {tol} of expected {ei} (best x = {x:?})"
+            );
+        }
+    }
+    let f = state.get_best_cost();
+    if let Some(expected_f) = expected_f {
+        assert!(
+            (f - expected_f).abs() <= tol,
+            "best objective value {f} is not within {tol} of expected {expected_f} (best x = {x:?})"
+        );
+    }
+    let violation = state.best_max_constraint_violation();
+    assert!(
+        state.is_feasible(tol),
+        "best point is infeasible, maximum constraint violation {violation} is greater than {tol} (best x = {x:?})"
+    );
+    state
+}
@@ -1,13 +1,42 @@
 #![doc = include_str!("../README.md")]
 
 mod cobyla;
+mod cobyla_cache;
+mod cobyla_config;
+mod cobyla_context;
+mod cobyla_error;
+mod cobyla_gradient;
+mod cobyla_minimize;
+mod cobyla_multistart;
+mod cobyla_param;
+mod cobyla_problem;
 mod cobyla_solver;
 mod cobyla_state;
+mod cobyla_stepper;
+#[cfg(feature = "testing")]
+mod cobyla_testing;
+pub use crate::cobyla_config::*;
+pub use crate::cobyla_error::*;
+pub use crate::cobyla_gradient::*;
+pub use crate::cobyla_minimize::*;
+pub use crate::cobyla_multistart::*;
+pub use crate::cobyla_param::*;
+pub use crate::cobyla_problem::*;
 pub use crate::cobyla_solver::*;
 pub use crate::cobyla_state::*;
+pub use crate::cobyla_stepper::*;
+#[cfg(feature = "testing")]
+pub use crate::cobyla_testing::*;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
 
 /// Failed termination status of the optimization process
-#[derive(Debug, Clone, Copy)]
+///
+/// New statuses may be added in minor releases: matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum FailStatus {
     Failure,
     InvalidArgs,
@@ -15,53 +44,401 @@ pub enum FailStatus {
     RoundoffLimited,
     ForcedStop,
     UnexpectedError,
+    NonFiniteCost,
 }
 
 /// Successful termination status of the optimization process
-#[derive(Debug, Clone, Copy)]
+///
+/// New statuses may be added in minor releases: matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum SuccessStatus {
     Success,
     StopValReached,
     FtolReached,
     XtolReached,
+    /// Budget of cost function evaluations reached
+    /// (see [crate::CobylaSolverBuilder::max_fun_evals])
     MaxEvalReached,
+    /// Maximum number of iterations reached (see [crate::CobylaState::max_iters])
+    MaxItersReached,
     MaxTimeReached,
+    Stagnated,
+    /// No constraint is active and the objective is flat at the best point
+    /// (see [crate::CobylaSolverBuilder::interior_stop])
+    FlatInterior,
+}
+
+/// Diagnostics of a [FailStatus::RoundoffLimited] termination: COBYLA stops when rounding
+/// errors make its simplex inverse inaccurate, which happens when the points it has
+/// evaluated become nearly degenerate (typically on noisy or badly scaled problems).
+/// The best point found so far is kept but it may not be a local minimum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct RoundoffInfo {
+    /// Trust region radius when rounding errors were detected
+    /// (expressed in the space where COBYLA is run, see [crate::CobylaState::current_rho])
+    pub rho: f64,
+    /// Length of the last step, ie distance between the last two evaluated points
+    pub step: f64,
+}
+
+/// Outcome of the last trust region step of COBYLA (see [crate::CobylaState::trust_region_step]).
+///
+/// Reductions are the ones of the merit function of COBYLA, ie the objective (minimized)
+/// plus the penalty parameter times the maximum constraint violation, in the space
+/// where COBYLA is run (with scaled constraints). A ratio close to 1 tells the linear
+/// models are good local approximations, a small or negative ratio that the trust region
+/// is too large for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct TrustRegionStep {
+    /// Reduction of the merit function predicted by the linear models
+    pub predicted_reduction: f64,
+    /// Actual reduction of the merit function
+    pub actual_reduction: f64,
+    /// Trust region ratio `actual_reduction / predicted_reduction`
+    pub ratio: f64,
+    /// Whether the step reduced the merit function, its point replacing a simplex vertex
+    pub accepted: bool,
+    /// Whether the step was rejected without evaluation, its point being out of the bounds
+    /// (see [crate::CobylaSolverBuilder::reject_out_of_bounds]): the actual reduction and
+    /// the ratio are then NaN
+    pub rejected: bool,
 }
 
 /// Tolerances used as termination criteria.
 /// For all, condition is disabled if value is not strictly positive.
+///
+/// Tolerances on function value are checked when a new best feasible point is found
+/// (up to [crate::CobylaSolverBuilder::feasibility_tol]): worsening exploratory steps
+/// do not trigger termination.
+///
+/// Tolerances on x are not checked directly but used to compute the final trust
+/// region radius of COBYLA which is the maximum of `rhoend` and of the x tolerances
+/// combined according to `xtol_combine`: by default ([TolCombine::Any]) the largest of
+/// `xtol_rel * rhobeg` and `xtol_abs` components (`xtol_rel` components being considered
+/// likewise when given per dimension) is the one which triggers termination, with
+/// [TolCombine::All] the smallest is, so that all criteria are satisfied.
 /// ```rust
-/// # use crate::cobyla_argmin::StopTols;
+/// # use crate::cobyla_argmin::{StopTols, XtolRel};
 /// let stop_tol = StopTols {
 ///     ftol_rel: 1e-4,
+///     xtol_rel: XtolRel::set([1e-2, 1e-3, 1e-3]),  // size should be equal to x dim
 ///     xtol_abs: vec![1e-3; 3],   // size should be equal to x dim
 ///     ..StopTols::default()      // default stop conditions are disabled
 /// };  
 /// ```
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub struct StopTols {
-    /// Relative tolerance on function value, algorithm stops when `func(x)` changes by less than `ftol_rel * func(x)`
+    /// Relative tolerance on function value, algorithm stops when the best feasible `func(x)`
+    /// is improved by less than `ftol_rel * func(x)`
     pub ftol_rel: f64,
-    /// Absolute tolerance on function value, algorithm stops when `func(x)` change is less than `ftol_rel`
+    /// Absolute tolerance on function value, algorithm stops when the best feasible `func(x)`
+    /// is improved by less than `ftol_abs`
     pub ftol_abs: f64,
+    /// Tolerance on function value relative to the initial one, algorithm stops when the
+    /// best feasible `func(x)` is improved by less than `ftol_init * |func(x0)|`.
+    ///
+    /// Contrary to `ftol_rel` whose reference is the current value, it does not misbehave
+    /// when `func(x)` gets close to zero or changes sign. It is disabled when `func(x0)` is
+    /// zero or not finite. All function value tolerances are checked at each improvement,
+    /// the first one satisfied stopping the algorithm with [SuccessStatus::FtolReached].
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub ftol_init: f64,
     /// Relative tolerance on optimization parameters, algorithm stops when all `x[i]` changes by less than `xtol_rel * x[i]`
-    pub xtol_rel: f64,
+    /// (either a single value or one per dimension, see [XtolRel])
+    pub xtol_rel: XtolRel,
     /// Relative tolerance on optimization parameters, algorithm stops when `x[i]` changes by less than `xtol_abs[i]`
     pub xtol_abs: Vec<f64>,
+    /// Combination of the enabled tolerances on x (`xtol_rel` and `xtol_abs` components,
+    /// default: [TolCombine::Any])
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub xtol_combine: TolCombine,
+}
+
+/// Combination of the tolerances on x (see [StopTols::xtol_combine])
+/// ```rust
+/// # use crate::cobyla_argmin::{StopTols, TolCombine, XtolRel};
+/// // Stop when x changes by less than 1e-6 relatively and 1e-8 absolutely
+/// let stop_tol = StopTols {
+///     xtol_rel: XtolRel::All(1e-6),
+///     xtol_abs: vec![1e-8; 2],
+///     xtol_combine: TolCombine::All,
+///     ..StopTols::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum TolCombine {
+    /// Algorithm stops as soon as one of the criteria on x is satisfied (OR)
+    #[default]
+    Any,
+    /// Algorithm stops when all criteria on x are satisfied, for all components (AND)
+    All,
+}
+
+/// Relative tolerance on optimization parameters (see [StopTols::xtol_rel])
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum XtolRel {
+    /// Used when all x components have the same relative tolerance
+    All(f64),
+    /// Used to set the tolerances of the components with the given x-dim-sized vector,
+    /// a single value vector being handled as [XtolRel::All]
+    Set(Vec<f64>),
+}
+
+impl Default for XtolRel {
+    /// Disabled relative tolerance
+    fn default() -> Self {
+        XtolRel::All(0.)
+    }
+}
+
+impl From<f64> for XtolRel {
+    fn from(tol: f64) -> Self {
+        XtolRel::All(tol)
+    }
+}
+
+impl XtolRel {
+    /// Returns [XtolRel::Set] variant given x components tolerances as a vector or an array
+    ///
+    /// ```rust
+    /// # use crate::cobyla_argmin::XtolRel;
+    /// let xtol_rel = XtolRel::set([1e-2, 1e-4]);
+    /// ```
+    pub fn set<V: Into<Vec<f64>>>(tols: V) -> Self {
+        XtolRel::Set(tols.into())
+    }
+}
+
+/// Declaration of an equality constraint `h(x) = 0` computed by the cost function.
+///
+/// As COBYLA only handles inequality constraints `c(x) >= 0`, the equality is relaxed into
+/// `-tol <= h(x) <= tol` which gives two inequality constraints `h(x) + tol >= 0` and
+/// `tol - h(x) >= 0`.
+/// ```rust
+/// # use crate::cobyla_argmin::EqualityConstraint;
+/// // cost function returns vec![f(x), c1(x), h1(x)], h1 being an equality constraint
+/// let eq = EqualityConstraint { index: 2, tol: 1e-6 };
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct EqualityConstraint {
+    /// Index of the constraint value in the cost function output (index 0 being the objective)
+    pub index: usize,
+    /// Tolerance on the equality, should be strictly positive
+    pub tol: f64,
 }
 
 /// An enum for specifying the initial change of x which correspond to the `rhobeg`
-/// argument of the original Powell's algorithm (hence the name)
+/// argument of the original Powell's algorithm (hence the name).
+///
+/// Changes should be finite and strictly positive, the optimization stops otherwise
+/// with [FailStatus::InvalidArgs] status.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub enum RhoBeg {
     /// Used when all x components changes are specified with a single given value
     All(f64),
     /// Used to set the components with the given x-dim-sized vector
     Set(Vec<f64>),
+    /// Used to set the components relatively to the initial guess: `factor * |x0[i]|`,
+    /// with a minimum value (see [crate::CobylaSolverBuilder::rhobeg_floor]) for
+    /// components of the initial guess close to zero
+    Relative(f64),
+}
+
+impl RhoBeg {
+    /// Returns [RhoBeg::Set] variant given x components changes as a vector or an array
+    ///
+    /// ```rust
+    /// # use crate::cobyla_argmin::RhoBeg;
+    /// let rhobeg = RhoBeg::set([0.5, 0.1]);
+    /// ```
+    pub fn set<V: Into<Vec<f64>>>(dx: V) -> Self {
+        RhoBeg::Set(dx.into())
+    }
+}
+
+/// Construction of the initial simplex of COBYLA
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum SimplexInit {
+    /// Vertices are the initial guess and the initial guess moved along each axis
+    /// by the initial change of x (see [RhoBeg])
+    #[default]
+    AxisAligned,
+    /// The `n + 1` vertices (of dimension `n`) given by the user which should be affinely
+    /// independent (see [DegenerateSimplex]). The first vertex is used as the initial guess, the initial change of x
+    /// (see [RhoBeg]) should be of the order of the simplex edge lengths as it remains
+    /// the initial trust region radius.
+    Custom(Vec<Vec<f64>>),
+}
+
+/// Handling of a degenerate custom initial simplex (see [SimplexInit::Custom] and
+/// [crate::CobylaSolverBuilder::degenerate_simplex]).
+///
+/// A vertex is degenerate when the component of its displacement from the first vertex
+/// orthogonal to the displacements of the previous vertices is within the given relative
+/// tolerance of its length: coincident vertices or vertices aligned with previous ones
+/// are degenerate with any tolerance, a tolerance of about `1e-8` also catches nearly
+/// collinear vertices which spoil the linear approximations of COBYLA.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum DegenerateSimplex {
+    /// The optimization stops with an error naming the first degenerate vertex
+    Error(f64),
+    /// Degenerate vertices are moved orthogonally to the displacements of the previous
+    /// ones, keeping their distance to the first vertex (the mean edge length from the
+    /// first vertex for vertices coincident with it)
+    Perturb(f64),
+}
+
+impl Default for DegenerateSimplex {
+    /// Only exactly degenerate simplices are rejected
+    fn default() -> Self {
+        DegenerateSimplex::Error(0.)
+    }
+}
+
+/// Optimization sense of the objective (see [crate::CobylaSolverBuilder::sense])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum Sense {
+    /// Objective is minimized
+    #[default]
+    Minimize,
+    /// Objective is maximized
+    Maximize,
+}
+
+/// Convention of the inequality constraints computed by the cost function
+/// (see [crate::CobylaSolverBuilder::constraint_sense])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum ConstraintSense {
+    /// Constraint is satisfied when `c(x) >= 0` (COBYLA convention)
+    #[default]
+    GreaterEqual,
+    /// Constraint is satisfied when `c(x) <= 0` (SciPy `minimize` convention for
+    /// `NonlinearConstraint` upper bounds, nlopt convention)
+    LessEqual,
+}
+
+impl ConstraintSense {
+    /// Returns the factor turning a constraint of this sense into a `c(x) >= 0` one
+    pub(crate) fn sign(self) -> f64 {
+        match self {
+            ConstraintSense::GreaterEqual => 1.,
+            ConstraintSense::LessEqual => -1.,
+        }
+    }
+}
+
+/// Transform of an x component, COBYLA being run on the transformed value
+/// (see [crate::CobylaSolverBuilder::with_transforms])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum VariableTransform {
+    /// COBYLA is run on `x[i]` itself
+    #[default]
+    Identity,
+    /// COBYLA is run on `ln(x[i])`, `x[i]` staying strictly positive
+    Log,
+    /// COBYLA is run on `ln((x[i] - lower) / (upper - x[i]))`, `x[i]` staying within its
+    /// bounds which should be finite
+    Logit,
+}
+
+impl VariableTransform {
+    /// Returns the transformed value of x component given its bounds
+    pub(crate) fn apply(self, x: f64, (lower, upper): (f64, f64)) -> f64 {
+        match self {
+            VariableTransform::Identity => x,
+            VariableTransform::Log => x.ln(),
+            VariableTransform::Logit => ((x - lower) / (upper - x)).ln(),
+        }
+    }
+
+    /// Returns the x component value given its transformed value and its bounds
+    pub(crate) fn invert(self, u: f64, (lower, upper): (f64, f64)) -> f64 {
+        match self {
+            VariableTransform::Identity => u,
+            VariableTransform::Log => u.exp(),
+            VariableTransform::Logit => lower + (upper - lower) / (1. + (-u).exp()),
+        }
+    }
+}
+
+/// Level of printing of the COBYLA algorithm, mapped to the `iprint` levels of the
+/// original Powell's code (see [crate::CobylaSolverBuilder::verbosity])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum Verbosity {
+    /// No output (`iprint = 0`)
+    Silent,
+    /// Final result (`iprint = 1`)
+    #[default]
+    Summary,
+    /// Progress at each change of the trust region radius (`iprint = 2`)
+    PerIteration,
+    /// Each cost function evaluation (`iprint = 3`)
+    Debug,
+}
+
+impl Verbosity {
+    /// Returns the corresponding `iprint` level
+    pub fn iprint(self) -> i32 {
+        match self {
+            Verbosity::Silent => 0,
+            Verbosity::Summary => 1,
+            Verbosity::PerIteration => 2,
+            Verbosity::Debug => 3,
+        }
+    }
+}
+
+/// Policy applied when the cost function returns non-finite (NaN or infinite) values
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum NonFinitePolicy {
+    /// Optimization is stopped with [FailStatus::NonFiniteCost] status, the best point
+    /// found so far is kept
+    #[default]
+    Abort,
+    /// A non-finite objective value is replaced by the given (large) penalty value (its
+    /// opposite when maximizing) and a non-finite constraint value by its opposite (hence
+    /// violated) so that the optimizer steers away from that region
+    Penalty(f64),
+}
+
+/// Policy applied when a constraint value returned by the cost function is NaN (feasibility
+/// undefined at that point), independently of the objective value
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum NanConstraintPolicy {
+    /// NaN constraint values are handled by the [NonFinitePolicy] like any non-finite value
+    #[default]
+    NonFinite,
+    /// A NaN constraint value is replaced by the opposite of the given (large, strictly
+    /// positive) violation so that the point is strongly infeasible and the optimizer
+    /// steers away from that region. A NaN objective value is still handled by the
+    /// [NonFinitePolicy].
+    Violation(f64),
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::CobylaSolver;
+    use crate::{
+        CobylaError, CobylaParam, CobylaSolver, CobylaSolverBuilder, EqualityConstraint,
+        FailStatus, MinimizeOptions, NanConstraintPolicy, NonFinitePolicy, RhoBeg, Sense,
+        SimplexInit, StopTols, SuccessStatus, Verbosity, XtolRel, minimize,
+    };
     use approx::assert_abs_diff_eq;
     use argmin::core::{CostFunction, Error, Executor, State};
 
@@ -98,4 +475,3994 @@ mod tests {
         assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
         assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
     }
+
+    #[test]
+    fn test_paraboloid_from_slice() {
+        let x0 = [1., 1.];
+        let res = Executor::new(ParaboloidProblem, CobylaSolver::new_from_slice(&x0))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_rho_observer() {
+        use crate::CobylaState;
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default, Clone)]
+        struct RhoObserver(Arc<Mutex<Vec<f64>>>);
+
+        impl Observe<CobylaState> for RhoObserver {
+            fn observe_iter(&mut self, state: &CobylaState, kv: &KV) -> Result<(), Error> {
+                let rho = kv.get("rho").and_then(|v| v.get_float()).unwrap();
+                assert_eq!(rho, state.current_rho());
+                self.0.lock().unwrap().push(rho);
+                Ok(())
+            }
+        }
+
+        let observer = RhoObserver::default();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .rhobeg(RhoBeg::All(0.5))
+            .rhoend(1e-4)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .add_observer(observer.clone(), ObserverMode::Always)
+            .run()
+            .unwrap();
+
+        let rhos = observer.0.lock().unwrap();
+        assert_eq!(rhos.len() as u64, res.state().get_iter());
+        assert_eq!(rhos[0], 0.5);
+        assert!(rhos.windows(2).all(|w| w[1] <= w[0]));
+        assert_abs_diff_eq!(1e-4, res.state().current_rho(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_paraboloid_kv_observer() {
+        use crate::CobylaState;
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default, Clone)]
+        struct KvObserver(Arc<Mutex<Vec<f64>>>);
+
+        impl Observe<CobylaState> for KvObserver {
+            fn observe_init(
+                &mut self,
+                _name: &str,
+                state: &CobylaState,
+                kv: &KV,
+            ) -> Result<(), Error> {
+                self.observe_iter(state, kv)
+            }
+
+            fn observe_iter(&mut self, state: &CobylaState, kv: &KV) -> Result<(), Error> {
+                let float = |key: &str| kv.get(key).and_then(|v| v.get_float()).unwrap();
+                assert_eq!(float("cost"), state.get_cost());
+                assert_eq!(float("rho"), state.current_rho());
+                assert_eq!(float("improvement"), state.last_improvement());
+                assert!(float("max_violation") >= 0.);
+                let func_evals = kv.get("func_evals").and_then(|v| v.get_uint());
+                assert_eq!(func_evals, Some(state.func_evals()));
+                self.0.lock().unwrap().push(float("best_cost"));
+                Ok(())
+            }
+        }
+
+        let observer = KvObserver::default();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .add_observer(observer.clone(), ObserverMode::Always)
+            .run()
+            .unwrap();
+
+        let best_costs = observer.0.lock().unwrap();
+        assert_eq!(best_costs.len() as u64, res.state().get_iter() + 1);
+        assert_eq!(best_costs[0], paraboloid(&[1., 1.], &mut ()));
+        assert!(best_costs.windows(2).all(|w| w[1] <= w[0]));
+        assert_abs_diff_eq!(10., best_costs[best_costs.len() - 1], epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_constraints_observer() {
+        use crate::CobylaState;
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        /// Observed maximum constraint violation and first constraint value
+        type Observed = Vec<(f64, Option<f64>)>;
+
+        #[derive(Default, Clone)]
+        struct ViolationObserver(Arc<Mutex<Observed>>);
+
+        impl Observe<CobylaState> for ViolationObserver {
+            fn observe_init(
+                &mut self,
+                _name: &str,
+                state: &CobylaState,
+                kv: &KV,
+            ) -> Result<(), Error> {
+                self.observe_iter(state, kv)
+            }
+
+            fn observe_iter(&mut self, state: &CobylaState, kv: &KV) -> Result<(), Error> {
+                let violation = kv.get("max_violation").and_then(|v| v.get_float()).unwrap();
+                assert_eq!(Some(violation), state.max_violation);
+                let c1 = kv.get("c1").and_then(|v| v.get_float());
+                assert!(kv.get("c2").is_none());
+                self.0.lock().unwrap().push((violation, c1));
+                Ok(())
+            }
+        }
+
+        for observe_constraints in [false, true] {
+            let observer = ViolationObserver::default();
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![-2., 1.])
+                .observe_constraints(observe_constraints)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .add_observer(observer.clone(), ObserverMode::Always)
+                .run()
+                .unwrap();
+
+            let values = observer.0.lock().unwrap();
+            // Initial guess violates x0 >= 0
+            assert_eq!(values[0].0, 2.);
+            assert!(values.last().unwrap().0 < 1e-6);
+            for (violation, c1) in values.iter() {
+                match c1 {
+                    Some(c1) => assert_eq!(*violation, (-c1).max(0.)),
+                    None => assert!(!observe_constraints),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_history() {
+        let run = |keep_history: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .keep_history(keep_history)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+        };
+
+        assert!(run(false).state().history().is_empty());
+
+        let res = run(true);
+        let history = res.state().history();
+        assert_eq!(history.len() as u64, res.state().func_evals());
+        assert_eq!(history[0], (0, 41., 0.));
+        assert!(history.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert!(history.iter().all(|h| h.2 >= 0.));
+        let best = history.iter().map(|h| h.1).fold(f64::INFINITY, f64::min);
+        assert!(best <= res.state().get_best_cost() + 1e-3);
+    }
+
+    #[test]
+    fn test_circle_penalty_history() {
+        let run = |keep_history: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .keep_history(keep_history)
+                .iprint(0)
+                .build();
+            Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+        };
+
+        assert!(run(false).state().penalty_history().is_empty());
+
+        let res = run(true);
+        let state = res.state();
+        let penalties = state.penalty_history();
+        assert_eq!(penalties.len() as u64, state.get_iter() + 1);
+        assert_eq!(penalties[0].0, 0);
+        assert!(penalties.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert_eq!(penalties.last().unwrap().1, state.current_penalty());
+        assert!(penalties.iter().any(|&(_, penalty)| penalty > 0.));
+    }
+
+    #[test]
+    fn test_paraboloid_cache() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_cache(100, 1e-2)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        assert!(state.cache_hits() > 0);
+        assert_eq!(state.cache_misses(), state.func_evals());
+        assert_eq!(state.func_evals(), state.get_func_counts()["cost_count"]);
+        assert!(state.cache_hits() + state.cache_misses() <= state.get_iter() + 1);
+    }
+
+    #[test]
+    fn test_noisy_paraboloid_resample() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        /// Paraboloid objective with alternating +/- 0.1 noise
+        struct NoisyParaboloidProblem(AtomicU64);
+        impl CostFunction for NoisyParaboloidProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                let calls = self.0.fetch_add(1, Ordering::Relaxed);
+                let noise = if calls.is_multiple_of(2) { 0.1 } else { -0.1 };
+                Ok(vec![
+                    10. * (x[0] + 1.).powf(2.) + x[1].powf(2.) + noise,
+                    x[0],
+                ])
+            }
+        }
+
+        let run = |builder: CobylaSolverBuilder| {
+            let solver = builder
+                .initial_param(vec![1., 1.])
+                .keep_evaluations(true)
+                .iprint(0)
+                .build();
+            Executor::new(NoisyParaboloidProblem(AtomicU64::new(0)), solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+        };
+
+        let res = run(CobylaSolver::builder().resample(2)).unwrap();
+        let state = res.state();
+        assert_eq!(state.func_evals(), 2 * state.evaluations().len() as u64);
+        assert_eq!(state.func_evals(), state.get_func_counts()["cost_count"]);
+        assert_abs_diff_eq!(state.get_best_cost(), 10., epsilon = 1e-3);
+        assert_abs_diff_eq!(
+            state.best_sample_std().unwrap()[..],
+            [0.1 * 2f64.sqrt(), 0.][..],
+            epsilon = 1e-9
+        );
+
+        let res = run(CobylaSolver::builder().resample(2).max_fun_evals(20)).unwrap();
+        assert_eq!(res.state().func_evals(), 20);
+        let simplex = SimplexInit::Custom(vec![vec![1., 1.], vec![1.5, 1.2], vec![0.8, 1.6]]);
+        let builder = || {
+            CobylaSolver::builder()
+                .resample(2)
+                .simplex_init(simplex.clone())
+        };
+        let evals = run(builder()).unwrap().state().func_evals();
+        let res = run(builder().batch_initial_simplex(true)).unwrap();
+        assert_eq!(res.state().func_evals(), evals);
+        let res = run(CobylaSolver::builder()).unwrap();
+        assert_eq!(res.state().best_sample_std(), None);
+
+        let err = run(CobylaSolver::builder().resample(0)).err().unwrap();
+        assert!(err.to_string().contains("resampled evaluations"));
+    }
+
+    #[test]
+    fn test_eval_cache_lru() {
+        use crate::cobyla_cache::EvalCache;
+
+        let mut cache = EvalCache::new(2, 0.);
+        cache.insert(&[1.], vec![1.]);
+        cache.insert(&[2.], vec![2.]);
+        assert_eq!(cache.get(&[1.]), Some(vec![1.]));
+        cache.insert(&[3.], vec![3.]);
+        assert_eq!(cache.get(&[2.]), None);
+        assert_eq!(cache.get(&[1.]), Some(vec![1.]));
+        assert_eq!(cache.get(&[3.]), Some(vec![3.]));
+
+        let mut cache = EvalCache::new(2, 0.1);
+        cache.insert(&[1.01], vec![1.]);
+        assert_eq!(cache.get(&[0.99]), Some(vec![1.]));
+        assert_eq!(cache.get(&[1.1]), None);
+    }
+
+    #[test]
+    fn test_paraboloid_constrained_problem() {
+        use crate::{Constrained, ConstrainedProblem};
+
+        struct TypedParaboloidProblem;
+
+        impl ConstrainedProblem for TypedParaboloidProblem {
+            type Param = Vec<f64>;
+
+            fn objective(&self, x: &Self::Param) -> Result<f64, Error> {
+                Ok(paraboloid(x, &mut ()))
+            }
+
+            fn constraints(&self, x: &Self::Param) -> Result<Vec<f64>, Error> {
+                Ok(vec![x[0]])
+            }
+        }
+
+        let res = Executor::new(
+            Constrained(TypedParaboloidProblem),
+            CobylaSolver::new(vec![1., 1.]),
+        )
+        .configure(|state| state.max_iters(100).iprint(0))
+        .run()
+        .unwrap();
+        let expected = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            res.state().get_best_param(),
+            expected.state().get_best_param()
+        );
+        assert_eq!(
+            res.state().get_full_best_cost(),
+            expected.state().get_full_best_cost()
+        );
+    }
+
+    #[test]
+    fn test_disk_screened_problem() {
+        use crate::{ConstrainedProblem, Screened};
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        /// Minimize (x0-3)^2 + (x1-3)^2 subject to 1 - x0^2 - x1^2 >= 0
+        #[derive(Default)]
+        struct DiskProblem {
+            objectives: AtomicU64,
+        }
+
+        impl ConstrainedProblem for DiskProblem {
+            type Param = Vec<f64>;
+
+            fn objective(&self, x: &Self::Param) -> Result<f64, Error> {
+                self.objectives.fetch_add(1, Ordering::Relaxed);
+                Ok((x[0] - 3.).powf(2.) + (x[1] - 3.).powf(2.))
+            }
+
+            fn constraints(&self, x: &Self::Param) -> Result<Vec<f64>, Error> {
+                Ok(vec![1. - x[0].powf(2.) - x[1].powf(2.)])
+            }
+        }
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 0.])
+            .rhobeg(RhoBeg::All(1.))
+            .iprint(0)
+            .build();
+        let res = Executor::new(Screened::new(DiskProblem::default(), 0.1), solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        let screened = res.problem().problem.as_ref().unwrap();
+        let objectives = screened.inner().objectives.load(Ordering::Relaxed);
+        assert!(screened.skipped_objectives() > 0);
+        assert_eq!(
+            objectives + screened.skipped_objectives(),
+            res.state().func_evals()
+        );
+        let x = 0.5f64.sqrt();
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [x, x][..],
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_evaluation_context() {
+        use crate::{CobylaState, Evaluation};
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        struct ContextParaboloidProblem;
+
+        impl CostFunction for ContextParaboloidProblem {
+            type Param = Vec<f64>;
+            type Output = Evaluation<Vec<f64>>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(Evaluation {
+                    objective: paraboloid(x, &mut ()),
+                    constraints: vec![x[0]],
+                    context: x.clone(),
+                })
+            }
+        }
+
+        #[derive(Default, Clone)]
+        struct ContextObserver(Arc<Mutex<u64>>);
+
+        impl Observe<CobylaState<Vec<f64>, Vec<f64>>> for ContextObserver {
+            fn observe_iter(
+                &mut self,
+                state: &CobylaState<Vec<f64>, Vec<f64>>,
+                _kv: &KV,
+            ) -> Result<(), Error> {
+                if let Some(context) = state.context() {
+                    assert_eq!(Some(context), state.get_param());
+                    assert_eq!(state.best_context(), state.get_best_param());
+                    *self.0.lock().unwrap() += 1;
+                }
+                Ok(())
+            }
+        }
+
+        let observer = ContextObserver::default();
+        let res = Executor::new(ContextParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .add_observer(observer.clone(), ObserverMode::Always)
+            .run()
+            .unwrap();
+        let expected = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+
+        assert!(*observer.0.lock().unwrap() > 0);
+        assert_eq!(
+            res.state().get_best_param(),
+            expected.state().get_best_param()
+        );
+        assert_eq!(
+            res.state().get_full_best_cost(),
+            expected.state().get_full_best_cost()
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_warm_start() {
+        let run = |solver: CobylaSolver, max_iters: u64| {
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(max_iters).iprint(0))
+                .run()
+                .unwrap()
+        };
+        let first = run(CobylaSolver::new(vec![1., 1.]), 15);
+        assert_eq!(
+            first.state().termination_status(),
+            Some(Ok(SuccessStatus::MaxItersReached))
+        );
+
+        let solver = CobylaSolver::builder().warm_start(first.state()).build();
+        let warm = run(solver, 200);
+        assert_eq!(
+            warm.state().termination_status(),
+            Some(Ok(SuccessStatus::Success))
+        );
+        let cold = run(
+            CobylaSolver::new(first.state().get_best_param().unwrap().clone()),
+            200,
+        );
+        assert_eq!(
+            cold.state().termination_status(),
+            Some(Ok(SuccessStatus::Success))
+        );
+        assert!(warm.state().func_evals() < cold.state().func_evals());
+
+        // Warm start continues exactly the iterations of an uninterrupted run
+        let full = run(CobylaSolver::new(vec![1., 1.]), 200);
+        assert_eq!(
+            first.state().func_evals() + warm.state().func_evals(),
+            full.state().func_evals()
+        );
+        assert_eq!(warm.state().get_best_param(), full.state().get_best_param());
+
+        // Converged run cannot be continued
+        let solver = CobylaSolver::builder().warm_start(warm.state()).build();
+        assert!(
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200).iprint(0))
+                .run()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_best_constraints() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        let constraints = state.best_constraints();
+        assert_eq!(constraints.len(), 1);
+        assert_eq!(constraints[0], state.get_best_param().unwrap()[0]);
+        assert_abs_diff_eq!(0., constraints[0], epsilon = 1e-2);
+        assert!(state.best_max_constraint_violation() < 1e-4);
+        assert!(state.is_feasible(1e-4));
+    }
+
+    #[test]
+    fn test_circle_polish_feasibility() {
+        let run = |polish_feasibility: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhoend(1e-3)
+                .polish_feasibility(polish_feasibility)
+                .iprint(0)
+                .build();
+            Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(false);
+        let expected = res.state();
+        assert!(!expected.is_feasible(1e-6));
+        assert_eq!(expected.polish_evals(), 0);
+
+        let res = run(true);
+        let state = res.state();
+        assert!(state.is_feasible(1e-6));
+        assert!(state.polish_evals() > 0);
+        assert_eq!(
+            state.func_evals(),
+            expected.func_evals() + state.polish_evals()
+        );
+        assert_eq!(state.termination_status(), expected.termination_status());
+        assert_abs_diff_eq!(
+            state.get_best_cost(),
+            expected.get_best_cost(),
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn test_circle_best_selector() {
+        // Feasible points first, then lowest objective
+        let feasible_first = |evaluations: &[(Vec<f64>, Vec<f64>)]| {
+            let key = |fx: &Vec<f64>| (fx[1] < -1e-6, fx[0]);
+            (0..evaluations.len())
+                .min_by(|&i, &j| {
+                    let (ki, kj) = (key(&evaluations[i].1), key(&evaluations[j].1));
+                    ki.partial_cmp(&kj).unwrap()
+                })
+                .unwrap()
+        };
+        let run = |builder: CobylaSolverBuilder| {
+            let solver = builder
+                .initial_param(vec![1., 1.])
+                .rhoend(1e-3)
+                .keep_evaluations(true)
+                .iprint(0)
+                .build();
+            Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+        };
+
+        let res = run(CobylaSolver::builder()).unwrap();
+        let expected = res.state();
+        assert!(!expected.is_feasible(1e-6));
+        let res = run(CobylaSolver::builder().best_selector(feasible_first)).unwrap();
+        let state = res.state();
+        assert!(state.is_feasible(1e-6));
+        let (x, f) = expected.best_feasible().unwrap();
+        assert_eq!(state.get_best_param().unwrap(), &x);
+        assert_eq!(state.get_best_cost(), f);
+        assert_eq!(state.func_evals(), expected.func_evals());
+
+        // selection is applied whatever ends the optimization
+        use argmin::core::TerminationReason;
+        for (max_iters, target_cost, reason) in [
+            (
+                15,
+                f64::NEG_INFINITY,
+                TerminationReason::SolverExit("MaxItersReached".to_string()),
+            ),
+            (200, -1.2, TerminationReason::TargetCostReached),
+        ] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .keep_evaluations(true)
+                .best_selector(feasible_first)
+                .iprint(0)
+                .build();
+            let res = Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(max_iters).target_cost(target_cost))
+                .run()
+                .unwrap();
+            let state = res.state();
+            assert_eq!(state.get_termination_reason(), Some(&reason));
+            let (x, fx) = &state.evaluations()[feasible_first(state.evaluations())];
+            assert_eq!(state.get_best_param(), Some(x));
+            assert_eq!(state.get_best_cost(), fx[0]);
+        }
+
+        let err = run(CobylaSolver::builder().best_selector(|evaluations| evaluations.len()))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("best selector index"));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_paraboloid_assert_converges_to() {
+        let state = crate::assert_converges_to(
+            ParaboloidProblem,
+            vec![1., 1.],
+            Some(&[0., 0.]),
+            Some(10.),
+            1e-2,
+        );
+        assert!(state.termination_status().unwrap().is_ok());
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .rhoend(1e-3)
+            .iprint(0)
+            .build();
+        crate::assert_solver_converges_to(CircleProblem, solver, None, Some(-2f64.sqrt()), 1e-2);
+
+        let result = std::panic::catch_unwind(|| {
+            crate::assert_converges_to(
+                ParaboloidProblem,
+                vec![1., 1.],
+                Some(&[-1., 0.]),
+                None,
+                1e-2,
+            )
+        });
+        let err = result.err().unwrap();
+        assert!(err.downcast_ref::<String>().unwrap().contains("best x[0]"));
+    }
+
+    #[test]
+    fn test_builder_validate() {
+        let builder = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_bounds(vec![-2., 3.], vec![2., 2.])
+            .rhobeg(RhoBeg::Set(vec![0.5, 0.5, 0.5]))
+            .with_scaling(vec![1.])
+            .max_fun_evals(0)
+            .with_num_constraints(1)
+            .with_constraint_scaling(vec![1., 2.]);
+        let errors = builder.validate().unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
+        assert_eq!(messages.len(), 5, "{messages:?}");
+        assert!(messages[0].contains("bound 3 is greater than upper bound 2 for x[1]"));
+        assert!(messages[1].contains("scaling size (1)"));
+        assert!(messages[2].contains("evaluations should be strictly positive"));
+        assert!(messages[3].contains("rhobeg size (3)"));
+        assert!(messages[4].contains("constraint scaling size (2)"));
+
+        // Running fails on the first error
+        let err = Executor::new(ParaboloidProblem, builder.build())
+            .run()
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), errors[0].to_string());
+
+        let builder = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_num_constraints(1);
+        assert!(builder.validate().is_ok());
+    }
+
+    #[test]
+    fn test_paraboloid_named_variables() {
+        let solver = CobylaSolver::builder()
+            .with_named_variables([("a", 1., Some((-2., 2.))), ("b", 1., None)])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        let state = res.state();
+        let best = state.best_param_named();
+        assert_eq!(best.len(), 2);
+        assert_eq!((best[0].0.as_str(), best[1].0.as_str()), ("a", "b"));
+        assert_abs_diff_eq!(0., best[0].1, epsilon = 1e-2);
+        assert_abs_diff_eq!(0., best[1].1, epsilon = 1e-2);
+        assert_eq!(state.variable_index("b"), Some(1));
+        assert_eq!(state.variable_index("c"), None);
+
+        let solver = CobylaSolver::builder()
+            .with_named_variables([("a", 1., None), ("a", 1., None)])
+            .build();
+        let err = Executor::new(ParaboloidProblem, solver)
+            .run()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("\"a\" of x[1] is already used"));
+    }
+
+    #[test]
+    fn test_log_logit_transforms() {
+        use crate::VariableTransform;
+
+        struct LogProblem;
+        impl CostFunction for LogProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            // Minimum at [2, 0.25], only defined for x0 > 0
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(vec![x[0] - 2. * x[0].ln() + (x[1] - 0.25).powi(2)])
+            }
+        }
+
+        let run = |x0: Vec<f64>| {
+            let solver = CobylaSolver::builder()
+                .initial_param(x0)
+                .with_bounds(vec![f64::NEG_INFINITY, 0.], vec![f64::INFINITY, 1.])
+                .with_transforms(vec![VariableTransform::Log, VariableTransform::Logit])
+                .rhoend(1e-6)
+                .keep_evaluations(true)
+                .iprint(0)
+                .build();
+            Executor::new(LogProblem, solver)
+                .configure(|state| state.max_iters(500))
+                .run()
+        };
+
+        let res = run(vec![0.05, 0.9]).unwrap();
+        let state = res.state();
+        assert!(
+            state
+                .evaluations()
+                .iter()
+                .all(|(x, _)| x[0] > 0. && 0. < x[1] && x[1] < 1.)
+        );
+        assert_eq!(state.evaluations()[0].0, vec![0.05, 0.9]);
+        let x = state.get_best_param().unwrap();
+        assert_abs_diff_eq!(x[0], 2., epsilon = 1e-3);
+        assert_abs_diff_eq!(x[1], 0.25, epsilon = 1e-3);
+
+        let err = run(vec![-1., 0.5]).err().unwrap();
+        assert!(
+            err.to_string()
+                .contains("strictly positive with a log transform")
+        );
+        let err = run(vec![1., 1.]).err().unwrap();
+        assert!(err.to_string().contains("strictly within its bounds"));
+    }
+
+    #[test]
+    fn test_paraboloid_active_constraints() {
+        // x0 >= 0 and x1 >= 1 are active at the solution [0, 1], x1 <= 2 is not
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.5])
+            .add_constraint(|x| x[1] - 1.)
+            .add_constraint(|x| 2. - x[1])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        assert_eq!(state.active_constraints(1e-4), vec![0, 1]);
+        assert_eq!(state.active_constraints(2.), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_paraboloid_bounds_violation() {
+        // Stopped early, the best point is not feasible yet
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![-1., 1.])
+            .with_bounds(vec![f64::NEG_INFINITY, 0.9], vec![f64::INFINITY; 2])
+            .rhobeg(RhoBeg::All(0.1))
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(3))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        let x = state.get_best_param().unwrap();
+        let expected = (-x[0]).max(0.9 - x[1]).max(0.);
+        assert!(expected > 0.);
+        assert_eq!(state.best_max_constraint_violation(), expected);
+        assert!(!state.is_feasible(1e-4));
+    }
+
+    #[test]
+    fn test_paraboloid_gradient_estimate() {
+        use crate::FiniteDifference;
+
+        let res = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+        let state = res.state();
+        let x = state.get_best_param().unwrap();
+        let expected = [20. * (x[0] + 1.), 2. * x[1]];
+
+        let grad = state
+            .estimate_gradient(&ParaboloidProblem, 1e-6, FiniteDifference::Central)
+            .unwrap();
+        assert_abs_diff_eq!(expected[0], grad[0], epsilon = 1e-6);
+        assert_abs_diff_eq!(expected[1], grad[1], epsilon = 1e-6);
+        let grad = state
+            .estimate_gradient(&ParaboloidProblem, 1e-6, FiniteDifference::Forward)
+            .unwrap();
+        assert_abs_diff_eq!(expected[0], grad[0], epsilon = 1e-4);
+        assert_abs_diff_eq!(expected[1], grad[1], epsilon = 1e-4);
+
+        let jac = state
+            .estimate_jacobian(&ParaboloidProblem, 1e-6, FiniteDifference::Central)
+            .unwrap();
+        assert_eq!(jac.len(), 2);
+        assert_abs_diff_eq!(1., jac[1][0], epsilon = 1e-6);
+        assert_abs_diff_eq!(0., jac[1][1], epsilon = 1e-6);
+
+        assert!(
+            state
+                .estimate_gradient(&ParaboloidProblem, 0., FiniteDifference::Central)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_richardson_gradient_estimate() {
+        use crate::{CobylaState, FiniteDifference, GradientEstimator};
+
+        struct ExpSinProblem;
+        impl CostFunction for ExpSinProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(vec![x[0].exp() + x[1].sin(), 1. - x[0] * x[1]])
+            }
+        }
+
+        let mut state: CobylaState = CobylaState::new();
+        state.best_param = Some(vec![0.5, 0.3]);
+        state.best_cost = Some(ExpSinProblem.cost(&vec![0.5, 0.3]).unwrap());
+        let expected = [0.5f64.exp(), 0.3f64.cos()];
+
+        let estimator = GradientEstimator {
+            scheme: FiniteDifference::Richardson,
+            step: 1e-2,
+            ..GradientEstimator::default()
+        };
+        let grad = estimator.gradient(&state, &ExpSinProblem).unwrap();
+        assert_abs_diff_eq!(grad[..], expected[..], epsilon = 1e-11);
+        let jac = estimator.jacobian(&state, &ExpSinProblem).unwrap();
+        assert_abs_diff_eq!(jac[1][..], [-0.3, -0.5][..], epsilon = 1e-11);
+
+        // Plain central differences with the same step are far less accurate
+        let grad = state
+            .estimate_gradient(&ExpSinProblem, 1e-2, FiniteDifference::Central)
+            .unwrap();
+        assert!((grad[0] - expected[0]).abs() > 1e-6);
+
+        // A single level is a central difference
+        let single = GradientEstimator {
+            levels: 1,
+            ..estimator.clone()
+        };
+        assert_eq!(single.gradient(&state, &ExpSinProblem).unwrap(), grad);
+
+        let invalid = GradientEstimator {
+            step_ratio: 1.,
+            ..estimator
+        };
+        assert!(invalid.gradient(&state, &ExpSinProblem).is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_constraint_info() {
+        use crate::{ConstraintInfo, FiniteDifference};
+
+        let res = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+        let info = res
+            .state()
+            .constraint_info(&ParaboloidProblem, 1e-6, 1e-6, FiniteDifference::Central)
+            .unwrap();
+
+        assert_eq!(info.len(), 1);
+        let ConstraintInfo {
+            value,
+            active,
+            gradient,
+        } = &info[0];
+        assert_eq!(*value, res.state().best_constraints()[0]);
+        assert!(*active);
+        assert_abs_diff_eq!(gradient[..], [1., 0.][..], epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_paraboloid_restore_feasibility() {
+        let run = |restore_feasibility: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![-5., 1.])
+                .restore_feasibility(restore_feasibility)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(true);
+        let state = res.state();
+        assert!(state.feasibility_evals() > 0);
+        assert!(state.feasibility_evals() < state.func_evals());
+        assert!(state.is_feasible(1e-4));
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[1], epsilon = 1e-2);
+
+        let res = run(false);
+        assert_eq!(res.state().feasibility_evals(), 0);
+
+        // Feasible initial guess: no restoration phase
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .restore_feasibility(true)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        assert_eq!(res.state().feasibility_evals(), 0);
+    }
+
+    /// Constant objective with points constrained within unit disks of the given centers
+    struct DisksProblem(Vec<[f64; 2]>);
+
+    impl CostFunction for DisksProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            let mut fx = vec![0.];
+            fx.extend(
+                self.0
+                    .iter()
+                    .map(|c| 1. - (x[0] - c[0]).powi(2) - (x[1] - c[1]).powi(2)),
+            );
+            Ok(fx)
+        }
+    }
+
+    #[test]
+    fn test_disks_feasibility_only() {
+        use argmin::core::TerminationReason;
+
+        let run = |centers: Vec<[f64; 2]>, x0: Vec<f64>| {
+            let solver = CobylaSolver::builder()
+                .initial_param(x0)
+                .feasibility_only(true)
+                .iprint(0)
+                .build();
+            Executor::new(DisksProblem(centers), solver)
+                .configure(|state| state.max_iters(500))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(vec![[3., 3.], [3., 4.5]], vec![0., 0.]);
+        let state = res.state();
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        assert!(state.is_feasible(1e-6));
+        assert_eq!(state.feasibility_evals(), state.func_evals());
+
+        // Feasible initial guess is returned right away
+        let res = run(vec![[3., 3.]], vec![3.5, 3.]);
+        let state = res.state();
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        assert_eq!(state.func_evals(), 1);
+        assert_eq!(state.get_best_param().unwrap(), &vec![3.5, 3.]);
+
+        // Disjoint disks: the least infeasible point is returned
+        let res = run(vec![[3., 3.], [-3., -3.]], vec![1., 0.]);
+        let state = res.state();
+        assert_eq!(state.termination_status(), Some(Err(FailStatus::Failure)));
+        assert!(!state.is_feasible(1e-6));
+        assert!(matches!(
+            state.get_termination_reason(),
+            Some(TerminationReason::SolverExit(reason)) if reason.starts_with("no feasible point")
+        ));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_paraboloid_ndarray() {
+        use ndarray::{Array1, array};
+
+        struct NdarrayParaboloidProblem;
+
+        impl CostFunction for NdarrayParaboloidProblem {
+            type Param = Array1<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                let fx = paraboloid(x.as_slice().unwrap(), &mut ());
+                Ok(vec![fx, x[0]])
+            }
+        }
+
+        let res = Executor::new(NdarrayParaboloidProblem, CobylaSolver::new(array![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+        let expected = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+
+        let best: &Array1<f64> = res.state().get_best_param().unwrap();
+        assert_eq!(best.to_vec(), *expected.state().get_best_param().unwrap());
+        assert_eq!(
+            res.state().get_best_cost(),
+            expected.state().get_best_cost()
+        );
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_paraboloid_nalgebra() {
+        use nalgebra::{DVector, dvector};
+
+        struct NalgebraParaboloidProblem;
+
+        impl CostFunction for NalgebraParaboloidProblem {
+            type Param = DVector<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                let fx = paraboloid(x.as_slice(), &mut ());
+                Ok(vec![fx, x[0]])
+            }
+        }
+
+        let res = Executor::new(
+            NalgebraParaboloidProblem,
+            CobylaSolver::new(dvector![1., 1.]),
+        )
+        .configure(|state| state.max_iters(100).iprint(0))
+        .run()
+        .unwrap();
+        let expected = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+
+        let best: &DVector<f64> = res.state().get_best_param().unwrap();
+        let expected_best = expected.state().get_best_param().unwrap();
+        assert_eq!(best.len(), expected_best.len());
+        for (xi, ei) in best.iter().zip(expected_best) {
+            assert_abs_diff_eq!(*xi, *ei, epsilon = 1e-6);
+        }
+        assert_abs_diff_eq!(
+            res.state().get_best_cost(),
+            expected.state().get_best_cost(),
+            epsilon = 1e-6
+        );
+    }
+
+    /// Paraboloid problem for any parameter type recording the evaluated points
+    struct RecordingParaboloidProblem<P>(std::sync::Arc<std::sync::Mutex<Vec<P>>>);
+
+    impl<P: CobylaParam> CostFunction for RecordingParaboloidProblem<P> {
+        type Param = P;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            self.0.lock().unwrap().push(x.clone());
+            let x = x.components();
+            Ok(vec![paraboloid(&x, &mut ()), x[0]])
+        }
+    }
+
+    /// Checks the best parameter is given back exactly as one of the evaluated points,
+    /// COBYLA being run in a scaled space
+    fn check_best_param<P>(x0: P)
+    where
+        P: CobylaParam + PartialEq + std::fmt::Debug + Send + Sync,
+    {
+        for max_fun_evals in [3, 200] {
+            let evaluated = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let solver = CobylaSolver::builder()
+                .initial_param(x0.clone())
+                .rhobeg(RhoBeg::set([0.3, 0.7]))
+                .max_fun_evals(max_fun_evals)
+                .iprint(0)
+                .build();
+            let res = Executor::new(RecordingParaboloidProblem(evaluated.clone()), solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap();
+            let best: &P = res.state().get_best_param().unwrap();
+            assert_eq!(best.components().len(), 2);
+            assert!(evaluated.lock().unwrap().contains(best));
+            if max_fun_evals == 3 {
+                // Initial guess remains the best vertex of the simplex
+                assert_eq!(*best, x0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_best_param_types() {
+        check_best_param(vec![0.1, 0.7]);
+        check_best_param([0.1, 0.7]);
+        #[cfg(feature = "ndarray")]
+        check_best_param(ndarray::array![0.1, 0.7]);
+        #[cfg(feature = "nalgebra")]
+        check_best_param(nalgebra::dvector![0.1, 0.7]);
+    }
+
+    #[test]
+    fn test_paraboloid_scalar_output() {
+        struct UnconstrainedParaboloidProblem;
+
+        impl CostFunction for UnconstrainedParaboloidProblem {
+            type Param = Vec<f64>;
+            type Output = f64;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(10. * (x[0] + 1.).powi(2) + x[1].powi(2))
+            }
+        }
+
+        let options = MinimizeOptions {
+            verbosity: Verbosity::Silent,
+            ..MinimizeOptions::default()
+        };
+        let report = minimize(UnconstrainedParaboloidProblem, vec![1., 1.], options).unwrap();
+        assert_abs_diff_eq!(-1., report.best_param[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., report.best_param[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., report.best_objective, epsilon = 1e-4);
+        assert!(report.constraints.is_empty());
+    }
+
+    #[test]
+    fn test_paraboloid_array() {
+        struct ArrayParaboloidProblem;
+
+        impl CostFunction for ArrayParaboloidProblem {
+            type Param = [f64; 2];
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(vec![paraboloid(x, &mut ()), x[0]])
+            }
+        }
+
+        let solver = CobylaSolver::builder()
+            .initial_param([1., 1.])
+            .rhobeg(RhoBeg::set([0.5, 0.5]))
+            .with_bounds([-2., -2.], [2., 2.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ArrayParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_bounds(vec![-2., -2.], vec![2., 2.])
+            .iprint(0)
+            .build();
+        let expected = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let best: &[f64; 2] = res.state().get_best_param().unwrap();
+        assert_eq!(&best[..], &expected.state().get_best_param().unwrap()[..]);
+        assert_eq!(
+            res.state().get_best_cost(),
+            expected.state().get_best_cost()
+        );
+
+        // Initial guess dimension should match the array size
+        let solver = CobylaSolver::new(vec![1., 1., 1.]);
+        assert!(
+            Executor::new(ArrayParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100).iprint(0))
+                .run()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_builder() {
+        let problem = ParaboloidProblem;
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .rhobeg(RhoBeg::Set(vec![0.5, 0.25]))
+            .stop_tols(StopTols {
+                ftol_rel: 1e-8,
+                ..StopTols::default()
+            })
+            .iprint(0)
+            .build();
+
+        let res = Executor::new(problem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_relative_rhobeg() {
+        let run = |x0: Vec<f64>, rhobeg: RhoBeg| {
+            let solver = CobylaSolver::builder()
+                .initial_param(x0)
+                .rhobeg(rhobeg)
+                .rhobeg_floor(0.05)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(vec![1., 0.], RhoBeg::Relative(0.1));
+        let expected = run(vec![1., 0.], RhoBeg::Set(vec![0.1, 0.05]));
+        assert_eq!(
+            res.state().get_best_param(),
+            expected.state().get_best_param()
+        );
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 0.])
+            .rhobeg(RhoBeg::Relative(0.1))
+            .rhobeg_floor(0.)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_zero_rhobeg() {
+        use argmin::core::TerminationReason;
+
+        for (rhobeg, i) in [
+            (RhoBeg::All(0.), 0),
+            (RhoBeg::Set(vec![0.1, 0.]), 1),
+            (RhoBeg::Set(vec![-0.1, 0.1]), 0),
+        ] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhobeg(rhobeg)
+                .iprint(0)
+                .build();
+            let res = Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap();
+            let state = res.state();
+            assert_eq!(
+                state.termination_status(),
+                Some(Err(FailStatus::InvalidArgs))
+            );
+            assert_eq!(state.func_evals(), 1);
+            assert!(matches!(
+                state.get_termination_reason(),
+                Some(TerminationReason::SolverExit(reason)) if reason.contains(&format!("x[{i}]"))
+            ));
+        }
+
+        // the floor applies to zero components of the initial guess
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 0.])
+            .rhobeg(RhoBeg::Relative(0.1))
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+    }
+
+    /// Minimize x0 + x1 subject to x0^2 + x1^2 <= 1: COBYLA ends slightly outside the circle
+    struct CircleProblem;
+
+    impl CostFunction for CircleProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![x[0] + x[1], 1. - x[0] * x[0] - x[1] * x[1]])
+        }
+    }
+
+    #[test]
+    fn test_circle_prefer_feasible() {
+        let run = |prefer_feasible: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhoend(1e-3)
+                .prefer_feasible(prefer_feasible)
+                .iprint(0)
+                .build();
+            Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+        let res = run(false);
+        let state = res.state();
+        assert!(!state.is_feasible(1e-6));
+        let (x, f) = state.best_feasible().unwrap();
+        assert!(CircleProblem.cost(&x).unwrap()[1] >= -1e-6);
+        assert!(f > state.get_best_cost());
+        assert_abs_diff_eq!(-2f64.sqrt(), f, epsilon = 1e-3);
+
+        let res = run(true);
+        let state = res.state();
+        assert!(state.is_feasible(1e-6));
+        assert_eq!(state.get_best_param(), Some(&x));
+        assert_eq!(state.get_best_cost(), f);
+    }
+
+    #[test]
+    fn test_circle_feasible_stop() {
+        let run = |feasible_stop: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhoend(1e-3)
+                .feasible_stop(feasible_stop)
+                .iprint(0)
+                .build();
+            Executor::new(CircleProblem, solver)
+                .configure(|state| state.max_iters(500))
+                .run()
+                .unwrap()
+        };
+        let res = run(false);
+        assert!(!res.state().is_feasible(1e-6));
+        let evals = res.state().func_evals();
+
+        let res = run(true);
+        let state = res.state();
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        assert!(state.is_feasible(1e-6));
+        assert!(state.func_evals() > evals);
+        assert_abs_diff_eq!(-2f64.sqrt(), state.get_best_cost(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_paraboloid_constraint_senses() {
+        use crate::ConstraintSense::{GreaterEqual, LessEqual};
+
+        /// Minimize 10*(x0+1)^2 + (x1-1)^2 subject to x0 >= 0 and x1 - 0.5 <= 0
+        struct MixedParaboloidProblem;
+
+        impl CostFunction for MixedParaboloidProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(vec![
+                    10. * (x[0] + 1.).powi(2) + (x[1] - 1.).powi(2),
+                    x[0],
+                    x[1] - 0.5,
+                ])
+            }
+        }
+
+        let run = |builder: crate::CobylaSolverBuilder| {
+            let solver = builder.initial_param(vec![1., 0.]).iprint(0).build();
+            Executor::new(MixedParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+        };
+        let res =
+            run(CobylaSolver::builder().with_constraint_senses(vec![GreaterEqual, LessEqual]))
+                .unwrap();
+        let state = res.state();
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[0], epsilon = 1e-3);
+        assert_abs_diff_eq!(0.5, state.get_best_param().unwrap()[1], epsilon = 1e-3);
+        assert_abs_diff_eq!(10.25, state.get_best_cost(), epsilon = 1e-3);
+        assert!(state.is_feasible(1e-6));
+        // Constraint values are recorded with the user convention
+        assert_abs_diff_eq!(0., state.best_constraints()[1], epsilon = 1e-3);
+
+        // All constraints declared <= 0: x0 <= 0 is then inactive at the optimum (-1, 0.5)
+        let res = run(CobylaSolver::builder().constraint_sense(LessEqual)).unwrap();
+        assert_abs_diff_eq!(
+            -1.,
+            res.state().get_best_param().unwrap()[0],
+            epsilon = 1e-3
+        );
+        assert_abs_diff_eq!(0.25, res.state().get_best_cost(), epsilon = 1e-3);
+
+        assert!(run(CobylaSolver::builder().with_constraint_senses(vec![LessEqual])).is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_effective_rhobeg() {
+        let run = |rhobeg: RhoBeg| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![2., 0.])
+                .rhobeg(rhobeg)
+                .rhoend(1e-4)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+        let res = run(RhoBeg::Set(vec![0.5, 0.1]));
+        let state = res.state();
+        let (rhobeg, rhoend) = (state.effective_rhobeg(), state.effective_rhoend());
+        assert_abs_diff_eq!(0.5, rhobeg.unwrap()[0], epsilon = 1e-12);
+        assert_abs_diff_eq!(0.1, rhobeg.unwrap()[1], epsilon = 1e-12);
+        assert_abs_diff_eq!(1e-4, rhoend.unwrap()[0], epsilon = 1e-12);
+        assert_abs_diff_eq!(2e-5, rhoend.unwrap()[1], epsilon = 1e-12);
+        assert!(state.summary().contains("rhobeg:"));
+
+        // Zero component of the initial guess gets the relative rhobeg floor
+        let res = run(RhoBeg::Relative(0.1));
+        let rhobeg = res.state().effective_rhobeg().unwrap();
+        assert_abs_diff_eq!(0.2, rhobeg[0], epsilon = 1e-12);
+        assert_abs_diff_eq!(crate::RHOBEG_FLOOR, rhobeg[1], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_paraboloid_stepper() {
+        let solver = || {
+            CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .iprint(0)
+                .build()
+        };
+        let res = Executor::new(ParaboloidProblem, solver())
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        let mut stepper = crate::CobylaStepper::new(ParaboloidProblem, solver())
+            .configure(|state| state.max_iters(200));
+        let mut steps = 0;
+        while !stepper.step().unwrap().terminated {
+            steps += 1;
+        }
+        let step = stepper.step().unwrap();
+        assert!(step.terminated);
+        assert_eq!(step.state.get_iter(), steps);
+        let (_, _, state) = stepper.into_parts();
+        assert_eq!(state.get_iter(), res.state().get_iter());
+        assert_eq!(state.func_evals(), res.state().func_evals());
+        assert_eq!(state.get_best_param(), res.state().get_best_param());
+        assert_eq!(state.termination_status(), res.state().termination_status());
+    }
+
+    #[test]
+    fn test_paraboloid_typed_errors() {
+        let run = |builder: crate::CobylaSolverBuilder| {
+            Executor::new(
+                ParaboloidProblem,
+                builder.initial_param(vec![1., 1.]).iprint(0).build(),
+            )
+            .configure(|state| state.max_iters(200))
+            .run()
+        };
+        let err = run(CobylaSolver::builder().rho_reduction(2.))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<CobylaError>(),
+            Some(CobylaError::InvalidParameter(text)) if text.contains("rho reduction")
+        ));
+
+        let res = run(CobylaSolver::builder().rhobeg(RhoBeg::All(0.))).unwrap();
+        assert!(matches!(
+            res.state().termination_error(),
+            Some(CobylaError::InvalidArgs)
+        ));
+        let res = run(CobylaSolver::builder()).unwrap();
+        assert!(res.state().termination_error().is_none());
+    }
+
+    #[test]
+    fn test_paraboloid_trust_region_step() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .keep_simplex(true)
+            .iprint(0)
+            .build();
+        let mut stepper = crate::CobylaStepper::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200));
+        let mut steps = vec![];
+        loop {
+            let step = stepper.step().unwrap();
+            steps.push(step.state.trust_region_step());
+            if step.terminated {
+                break;
+            }
+        }
+        // initial simplex evaluations are not trust region steps
+        assert!(steps[..3].iter().all(Option::is_none));
+        let steps: Vec<_> = steps.into_iter().flatten().collect();
+        assert!(!steps.is_empty());
+        for step in steps.iter() {
+            assert_eq!(step.ratio, step.actual_reduction / step.predicted_reduction);
+            if step.accepted {
+                assert!(step.actual_reduction > 0.);
+            }
+        }
+        assert!(steps.iter().any(|step| step.accepted));
+        assert!(steps.iter().any(|step| !step.accepted));
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        assert!(res.state().trust_region_step().is_none());
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_paraboloid_log_steps() {
+        use std::sync::Mutex;
+        use std::thread::ThreadId;
+
+        /// Logger recording trace messages with the thread logging them
+        struct RecordingLogger(Mutex<Vec<(ThreadId, String)>>);
+
+        impl log::Log for RecordingLogger {
+            fn enabled(&self, metadata: &log::Metadata) -> bool {
+                metadata.level() <= log::Level::Trace
+            }
+
+            fn log(&self, record: &log::Record) {
+                let message = (std::thread::current().id(), record.args().to_string());
+                self.0.lock().unwrap().push(message);
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: RecordingLogger = RecordingLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        let thread = std::thread::current().id();
+        let messages: Vec<String> = LOGGER
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _)| *id == thread)
+            .map(|(_, message)| message.clone())
+            .collect();
+        assert!(messages[0].starts_with("trust region step 1 "));
+        assert!(messages.iter().any(|m| m.contains(" accepted: ")));
+        assert!(messages.iter().any(|m| m.contains(" rejected: ")));
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.starts_with("trust region radius reduced"))
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_merit_value() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .keep_simplex(true)
+            .iprint(0)
+            .build();
+        let mut stepper = crate::CobylaStepper::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200));
+        let mut penalized = false;
+        loop {
+            let step = stepper.step().unwrap();
+            let state = step.state;
+            penalized |= state.current_penalty() > 0.;
+            if let Some(simplex) = state.simplex() {
+                // the best vertex is the last one for the merit function
+                let merits: Vec<f64> = simplex
+                    .iter()
+                    .map(|(_, fx)| state.merit_value(fx[0], &fx[1..]))
+                    .collect();
+                let best = merits[merits.len() - 1];
+                assert!(merits.iter().all(|&merit| best <= merit + 1e-12));
+            }
+            if step.terminated {
+                break;
+            }
+        }
+        assert!(penalized);
+    }
+
+    #[test]
+    fn test_memory_footprint() {
+        use crate::cobyla_context::CobylaContext;
+
+        // working arrays of n * (3n + 2m + 11) + 4m + 6 floats
+        let footprint = CobylaSolver::memory_footprint(2, 1);
+        assert!(footprint >= 8 * (2 * 19 + 10));
+        assert!(footprint < 8 * (2 * 19 + 10) + 1024);
+        assert!(CobylaSolver::memory_footprint(1000, 10) > 24_000_000);
+        assert_eq!(CobylaSolver::memory_footprint(usize::MAX, 0), usize::MAX);
+
+        // huge dimension: the allocation fails without aborting
+        assert!(matches!(
+            CobylaContext::new(100_000_000, 0, 1., 0.1, 0, 10),
+            Err(FailStatus::OutOfMemory)
+        ));
+        assert!(matches!(
+            CobylaContext::new(2, 1, 1., 0.1, 0, 10),
+            Ok(context) if context.dims() == (2, 1)
+        ));
+    }
+
+    #[test]
+    fn test_paraboloid_num_constraints() {
+        let run = |builder: crate::CobylaSolverBuilder| {
+            let solver = builder.initial_param(vec![1., 1.]).iprint(0).build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+        };
+
+        let expected = run(CobylaSolver::builder()).unwrap();
+        let res = run(CobylaSolver::builder().with_num_constraints(1)).unwrap();
+        assert_eq!(
+            res.state().get_best_param(),
+            expected.state().get_best_param()
+        );
+        assert_eq!(res.state().func_evals(), expected.state().func_evals());
+
+        let err = run(CobylaSolver::builder().with_num_constraints(2))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<CobylaError>(),
+            Some(CobylaError::InvalidParameter(text)) if text.contains("declared number")
+        ));
+
+        // invalid settings are detected before any evaluation
+        let res = run(CobylaSolver::builder()
+            .with_num_constraints(1)
+            .rhobeg(RhoBeg::All(0.)))
+        .unwrap();
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Err(FailStatus::InvalidArgs))
+        );
+        assert_eq!(res.state().func_evals(), 0);
+    }
+
+    #[test]
+    fn test_paraboloid_dims() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .add_constraint(|x: &[f64]| 2. - x[1])
+            .iprint(0)
+            .build();
+        assert_eq!(solver.dim(), 2);
+        assert_eq!(solver.num_constraints(), None);
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+        assert_eq!(res.solver().dim(), 2);
+        assert_eq!(res.solver().num_constraints(), Some(2));
+        assert_eq!(res.state().dim(), Some(2));
+        assert_eq!(res.state().num_constraints(), Some(2));
+
+        let solver = CobylaSolver::builder().with_num_constraints(1).build();
+        assert_eq!(solver.num_constraints(), Some(1));
+    }
+
+    #[test]
+    fn test_paraboloid_keep_simplex() {
+        let run = |keep_simplex: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .with_constraint_scaling(vec![2.])
+                .keep_simplex(keep_simplex)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+        assert!(run(false).state().simplex().is_none());
+
+        let res = run(true);
+        let simplex = res.state().simplex().unwrap();
+        assert_eq!(simplex.len(), 3);
+        for (x, fx) in simplex {
+            let expected = ParaboloidProblem.cost(x).unwrap();
+            assert_abs_diff_eq!(fx[..], expected[..], epsilon = 1e-10);
+        }
+        // vertices have collapsed around the solution
+        let (best, _) = simplex.last().unwrap();
+        assert_abs_diff_eq!(best[..], [0., 0.][..], epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_rhoend() {
+        let run = |rhoend: f64| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhoend(rhoend)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+        let coarse = run(1e-2);
+        let fine = run(1e-6);
+
+        assert!(coarse.state().get_iter() < fine.state().get_iter());
+        assert_abs_diff_eq!(10., coarse.state().get_best_cost(), epsilon = 1e-1);
+        assert_abs_diff_eq!(10., fine.state().get_best_cost(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_paraboloid_rho_reduction() {
+        let run = |builder: crate::CobylaSolverBuilder| {
+            Executor::new(
+                ParaboloidProblem,
+                builder.initial_param(vec![1., 1.]).build(),
+            )
+            .configure(|state| state.max_iters(200).iprint(0))
+            .run()
+        };
+        let default = run(CobylaSolver::builder()).unwrap();
+        let aggressive = run(CobylaSolver::builder().rho_reduction(0.1)).unwrap();
+        for res in [&default, &aggressive] {
+            assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+            assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+        }
+        assert_ne!(
+            default.state().func_evals(),
+            aggressive.state().func_evals()
+        );
+
+        for factor in [0., 1., -0.5, f64::NAN] {
+            assert!(run(CobylaSolver::builder().rho_reduction(factor)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_initial_penalty() {
+        let run = |builder: crate::CobylaSolverBuilder| {
+            Executor::new(
+                ParaboloidProblem,
+                builder.initial_param(vec![-2., 1.]).build(),
+            )
+            .configure(|state| state.max_iters(200).iprint(0))
+            .run()
+        };
+        let default = run(CobylaSolver::builder()).unwrap();
+        let res = run(CobylaSolver::builder().initial_penalty(100.)).unwrap();
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+        assert!(res.state().is_feasible(1e-6));
+        assert_ne!(default.state().func_evals(), res.state().func_evals());
+
+        for penalty in [0., -1., f64::INFINITY, f64::NAN] {
+            assert!(run(CobylaSolver::builder().initial_penalty(penalty)).is_err());
+        }
+    }
+
+    /// Maximize 3 - (x0 - 1)^2 - (x1 + 2)^2 subject to x0 <= 0.5
+    struct ConcaveProblem;
+
+    impl CostFunction for ConcaveProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![
+                3. - (x[0] - 1.).powi(2) - (x[1] + 2.).powi(2),
+                0.5 - x[0],
+            ])
+        }
+    }
+
+    #[test]
+    fn test_concave_maximize() {
+        let run = |builder: crate::CobylaSolverBuilder| {
+            Executor::new(
+                ConcaveProblem,
+                builder
+                    .initial_param(vec![0., 0.])
+                    .sense(Sense::Maximize)
+                    .build(),
+            )
+            .configure(|state| state.max_iters(200).iprint(0))
+            .run()
+            .unwrap()
+        };
+        let res = run(CobylaSolver::builder());
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [0.5, -2.][..],
+            epsilon = 1e-2
+        );
+        assert_abs_diff_eq!(2.75, res.state().get_best_cost(), epsilon = 1e-3);
+        assert!(res.state().is_feasible(1e-6));
+
+        // the target is reached by greater objective values
+        let res = run(CobylaSolver::builder().stopval(2.));
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::StopValReached))
+        );
+        assert!(res.state().get_best_cost() >= 2.);
+        assert!(res.state().get_best_cost() < 2.75 - 1e-3);
+    }
+
+    #[test]
+    fn test_paraboloid_func_evals() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let func_evals = res.state().func_evals();
+        assert!(func_evals > 0);
+        assert_eq!(func_evals, res.state().get_func_counts()["cost_count"]);
+        assert!(func_evals <= res.state().get_iter() + 1);
+    }
+
+    #[test]
+    fn test_paraboloid_termination_status() {
+        let run = |stop_tols: StopTols, max_iters: u64| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .stop_tols(stop_tols)
+                .iprint(0)
+                .build();
+            let res = Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(max_iters))
+                .run()
+                .unwrap();
+            res.state().termination_status()
+        };
+
+        assert_eq!(
+            run(StopTols::default(), 100),
+            Some(Ok(SuccessStatus::Success))
+        );
+        assert_eq!(
+            run(StopTols::default(), 5),
+            Some(Ok(SuccessStatus::MaxItersReached))
+        );
+        let xtol = StopTols {
+            xtol_rel: 1e-2.into(),
+            ..StopTols::default()
+        };
+        assert_eq!(run(xtol, 100), Some(Ok(SuccessStatus::XtolReached)));
+        let xtol = StopTols {
+            xtol_rel: XtolRel::set([1e-2, 1e-4]),
+            ..StopTols::default()
+        };
+        assert_eq!(run(xtol, 100), Some(Ok(SuccessStatus::XtolReached)));
+        // Final radius is set by rhoend which is larger than the x tolerance one
+        let xtol = StopTols {
+            xtol_rel: 1e-10.into(),
+            ..StopTols::default()
+        };
+        assert_eq!(run(xtol, 100), Some(Ok(SuccessStatus::Success)));
+        let ftol = StopTols {
+            ftol_abs: 1e-1,
+            ..StopTols::default()
+        };
+        assert_eq!(run(ftol, 100), Some(Ok(SuccessStatus::FtolReached)));
+    }
+
+    #[test]
+    fn test_paraboloid_xtol_combine() {
+        use crate::TolCombine;
+
+        // Relative tolerance gives a final radius of 0.05, absolute one of 1e-4
+        let run = |xtol_combine: TolCombine| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .rhoend(1e-8)
+                .stop_tols(StopTols {
+                    xtol_rel: XtolRel::All(0.1),
+                    xtol_abs: vec![1e-4; 2],
+                    xtol_combine,
+                    ..StopTols::default()
+                })
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(500))
+                .run()
+                .unwrap()
+        };
+
+        let any = run(TolCombine::default());
+        let all = run(TolCombine::All);
+        for res in [&any, &all] {
+            assert_eq!(
+                res.state().termination_status(),
+                Some(Ok(SuccessStatus::XtolReached))
+            );
+        }
+        assert_abs_diff_eq!(any.state().effective_rhoend().unwrap()[0], 0.05);
+        assert_abs_diff_eq!(all.state().effective_rhoend().unwrap()[0], 1e-4);
+        assert!(all.state().func_evals() > any.state().func_evals());
+        let any_error = any.state().get_best_param().unwrap()[1].abs();
+        let all_error = all.state().get_best_param().unwrap()[1].abs();
+        assert!(all_error < 1e-3);
+        assert!(all_error < any_error);
+    }
+
+    /// Unconstrained paraboloid, bounds are given to the solver
+    struct UnconstrainedParaboloid;
+
+    impl CostFunction for UnconstrainedParaboloid {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![paraboloid(x, &mut ())])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_interior_stop() {
+        let run = |interior_stop: Option<(f64, f64)>| {
+            let mut builder = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .with_bounds(vec![-5., -5.], vec![5., 5.])
+                .rhoend(1e-8)
+                .iprint(0);
+            if let Some((margin, gtol)) = interior_stop {
+                builder = builder.interior_stop(margin, gtol);
+            }
+            Executor::new(UnconstrainedParaboloid, builder.build())
+                .configure(|state| state.max_iters(500))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(Some((0.1, 1e-2)));
+        let state = res.state();
+        assert_eq!(
+            state.termination_status(),
+            Some(Ok(SuccessStatus::FlatInterior))
+        );
+        assert_abs_diff_eq!(-1., state.get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[1], epsilon = 1e-2);
+        let expected = run(None);
+        assert!(state.func_evals() < expected.state().func_evals());
+
+        // Active bound at the optimum
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_bounds(vec![-0.5, -5.], vec![5., 5.])
+            .interior_stop(0.1, 1e-2)
+            .iprint(0)
+            .build();
+        let res = Executor::new(UnconstrainedParaboloid, solver)
+            .configure(|state| state.max_iters(500))
+            .run()
+            .unwrap();
+        assert_ne!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::FlatInterior))
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_added_constraints() {
+        let base = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .add_constraint(|x| x[0])
+            .iprint(0);
+        let run = |builder: crate::CobylaSolverBuilder| {
+            Executor::new(UnconstrainedParaboloid, builder.build())
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(base.clone());
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [0., 0.][..],
+            epsilon = 1e-2
+        );
+        assert_eq!(res.state().get_full_best_cost().unwrap().len(), 2);
+
+        // variant: x1 >= 1 in addition
+        let res = run(base.add_constraint(|x| x[1] - 1.));
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [0., 1.][..],
+            epsilon = 1e-2
+        );
+        assert_abs_diff_eq!(11., res.state().get_best_cost(), epsilon = 1e-2);
+        assert_eq!(res.state().get_full_best_cost().unwrap().len(), 3);
+        assert!(res.state().is_feasible(1e-6));
+    }
+
+    #[test]
+    fn test_paraboloid_bounds() {
+        let solver = |x0| {
+            CobylaSolver::builder()
+                .initial_param(x0)
+                .with_bounds(vec![0., f64::NEG_INFINITY], vec![f64::INFINITY, 2.])
+                .iprint(0)
+                .build()
+        };
+        let res = Executor::new(UnconstrainedParaboloid, solver(vec![3., 1.]))
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+        assert_eq!(res.state().get_full_best_cost().unwrap().len(), 1);
+
+        // Initial guess out of the bounds is rejected
+        let err = Executor::new(UnconstrainedParaboloid, solver(vec![-3., 1.]))
+            .configure(|state| state.max_iters(100))
+            .run()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<CobylaError>(),
+            Some(CobylaError::InvalidParameter(msg)) if msg.contains("out of bounds")
+        ));
+    }
+
+    #[test]
+    fn test_paraboloid_bounds_center() {
+        let solver = CobylaSolver::builder()
+            .with_bounds(vec![0., -1.], vec![2., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(UnconstrainedParaboloid, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
+
+        let solver = CobylaSolver::builder()
+            .with_bounds(vec![0., f64::NEG_INFINITY], vec![2., 1.])
+            .build();
+        let res = Executor::new(UnconstrainedParaboloid, solver).run();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_inconsistent_bounds() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_bounds(vec![0., 1.], vec![1., 0.])
+            .build();
+        let res = Executor::new(UnconstrainedParaboloid, solver).run();
+        assert!(res.is_err());
+    }
+
+    /// Paraboloid which cannot be evaluated for x0 < 0
+    struct NonNegativeParaboloid;
+
+    impl CostFunction for NonNegativeParaboloid {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            assert!(x[0] >= 0., "negative x0 = {}", x[0]);
+            Ok(vec![paraboloid(x, &mut ())])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_clamp_to_bounds() {
+        let run = |clamp: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .with_bounds(vec![0., f64::NEG_INFINITY], vec![f64::INFINITY, 2.])
+                .clamp_to_bounds(clamp)
+                .iprint(0)
+                .build();
+            Executor::new(NonNegativeParaboloid, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+        };
+
+        // optimum on the x0 = 0 bound: COBYLA steps out of it, the cost function panics
+        let res = run(false);
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Err(FailStatus::UnexpectedError))
+        );
+        let res = run(true);
+        assert!(res.state().termination_status().unwrap().is_ok());
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., res.state().get_best_param().unwrap()[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., res.state().get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_reject_out_of_bounds() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_bounds(vec![0., f64::NEG_INFINITY], vec![f64::INFINITY, 2.])
+            .reject_out_of_bounds(true)
+            .iprint(0)
+            .build();
+        let res = Executor::new(NonNegativeParaboloid, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        // steps out of the x0 = 0 bound are rejected: the cost function never panics
+        let state = res.state();
+        assert!(state.termination_status().unwrap().is_ok());
+        assert!(state.func_evals() <= state.get_iter());
+        let best = state.get_best_param().unwrap();
+        assert!(best[0] >= 0.);
+        assert_abs_diff_eq!(0., best[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0., best[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., state.get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_reject_out_of_bounds_observed() {
+        use crate::CobylaState;
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        /// Checks the reported points are within the bounds x0 >= 0 and x1 <= upper,
+        /// counting the rejected trust region steps
+        #[derive(Clone)]
+        struct BoundsObserver(f64, Arc<Mutex<usize>>);
+
+        impl Observe<CobylaState> for BoundsObserver {
+            fn observe_iter(&mut self, state: &CobylaState, _kv: &KV) -> Result<(), Error> {
+                let in_bounds = |x: &Vec<f64>| x[0] >= 0. && x[1] <= self.0;
+                assert!(in_bounds(state.get_param().unwrap()));
+                assert!(in_bounds(state.get_best_param().unwrap()));
+                if state.trust_region_step().is_some_and(|step| step.rejected) {
+                    *self.1.lock().unwrap() += 1;
+                }
+                Ok(())
+            }
+        }
+
+        let run = |x0: Vec<f64>, upper: f64| {
+            let solver = CobylaSolver::builder()
+                .initial_param(x0)
+                .with_bounds(vec![0., f64::NEG_INFINITY], vec![f64::INFINITY, upper])
+                .reject_out_of_bounds(true)
+                .keep_simplex(true)
+                .iprint(0)
+                .build();
+            let observer = BoundsObserver(upper, Arc::default());
+            let res = Executor::new(NonNegativeParaboloid, solver)
+                .configure(|state| state.max_iters(200))
+                .add_observer(observer.clone(), ObserverMode::Always)
+                .run()
+                .unwrap();
+            let rejected = *observer.1.lock().unwrap();
+            (res.state().clone(), rejected)
+        };
+
+        // trust region steps beyond x0 = 0 are rejected
+        let (state, rejected) = run(vec![1., 1.], 2.);
+        assert!(state.termination_status().unwrap().is_ok());
+        assert!(rejected > 0);
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., state.get_best_cost(), epsilon = 1e-2);
+
+        // minimum at the corner of the bounds, where the geometry steps are reversed
+        let (state, _) = run(vec![1., -1.], -0.5);
+        assert!(state.termination_status().unwrap().is_ok());
+        let best = state.get_best_param().unwrap();
+        assert_abs_diff_eq!(0., best[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(-0.5, best[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10.25, state.get_best_cost(), epsilon = 1e-2);
+    }
+
+    /// Minimize paraboloid(x) subject to x0 - x1 = 1
+    struct EqualityProblem;
+
+    impl CostFunction for EqualityProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![paraboloid(x, &mut ()), x[0] - x[1] - 1.])
+        }
+    }
+
+    /// Paraboloid with very different magnitudes of x components
+    struct BadlyScaledProblem;
+
+    impl CostFunction for BadlyScaledProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![(x[0] - 1.).powi(2) + ((x[1] - 2e-6) / 1e-6).powi(2)])
+        }
+    }
+
+    #[test]
+    fn test_badly_scaled_scaling() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 0.])
+            .with_scaling(vec![1., 1e-6])
+            .rhoend(1e-6)
+            .iprint(0)
+            .build();
+        let res = Executor::new(BadlyScaledProblem, solver)
+            .configure(|state| state.max_iters(500))
+            .run()
+            .unwrap();
+
+        let best = res.state().get_best_param().unwrap();
+        assert_abs_diff_eq!(1., best[0], epsilon = 1e-4);
+        assert_abs_diff_eq!(2e-6, best[1], epsilon = 1e-10);
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::Success))
+        );
+    }
+
+    #[test]
+    fn test_badly_scaled_invalid_scaling() {
+        for scaling in [vec![1., 0.], vec![1.]] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![0., 0.])
+                .with_scaling(scaling)
+                .iprint(0)
+                .build();
+            let res = Executor::new(BadlyScaledProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run();
+            assert!(res.is_err());
+        }
+    }
+
+    /// Paraboloid problem with constraints of very different magnitudes
+    struct UnitsParaboloidProblem;
+
+    impl CostFunction for UnitsParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            let fx = paraboloid(x, &mut ());
+            Ok(vec![fx, 1e6 * x[0], 1e-3 * (1. - x[1])])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_constraint_scaling() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_constraint_scaling(vec![1e6, 1e-3])
+            .iprint(0)
+            .build();
+        let res = Executor::new(UnitsParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        let x = state.get_best_param().unwrap();
+        assert_abs_diff_eq!(0., x[0], epsilon = 1e-3);
+        assert_abs_diff_eq!(0., x[1], epsilon = 1e-3);
+        // Cost function output is unscaled
+        let fx = state.get_full_best_cost().unwrap();
+        assert_abs_diff_eq!(1e6 * x[0], fx[1], epsilon = 1e-6);
+        assert_abs_diff_eq!(1e-3 * (1. - x[1]), fx[2], epsilon = 1e-12);
+        assert!(state.is_feasible(1e-6));
+
+        for scales in [vec![1e6], vec![1e6, -1.], vec![f64::INFINITY, 1.]] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .with_constraint_scaling(scales)
+                .iprint(0)
+                .build();
+            let res = Executor::new(UnitsParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run();
+            assert!(res.is_err());
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_equality() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_equality_constraints(vec![EqualityConstraint {
+                index: 1,
+                tol: 1e-6,
+            }])
+            .iprint(0)
+            .build();
+        let res = Executor::new(EqualityProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        // Analytic solution: x0 = -9/11, x1 = -20/11
+        let x = res.state().get_best_param().unwrap();
+        assert_abs_diff_eq!(-9. / 11., x[0], epsilon = 1e-3);
+        assert_abs_diff_eq!(-20. / 11., x[1], epsilon = 1e-3);
+        let h = res.state().get_full_best_cost().unwrap()[1];
+        assert_abs_diff_eq!(0., h, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_paraboloid_equality_bad_index() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .with_equality_constraints(vec![EqualityConstraint {
+                index: 2,
+                tol: 1e-6,
+            }])
+            .build();
+        let res = Executor::new(EqualityProblem, solver).run();
+        assert!(res.is_err());
+    }
+
+    /// Unconstrained paraboloid whose evaluation fails (NaN) for x0 < -0.5
+    struct FailingParaboloid;
+
+    impl CostFunction for FailingParaboloid {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            if x[0] < -0.5 {
+                Ok(vec![f64::NAN])
+            } else {
+                Ok(vec![paraboloid(x, &mut ())])
+            }
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_non_finite_cost() {
+        let run = |policy: NonFinitePolicy| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .non_finite_policy(policy)
+                .iprint(0)
+                .build();
+            Executor::new(FailingParaboloid, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        let res = run(NonFinitePolicy::Abort);
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Err(FailStatus::NonFiniteCost))
+        );
+        let best = res.state().get_best_param().unwrap();
+        assert!(best[0] >= -0.5);
+        assert!(res.state().get_best_cost().is_finite());
+
+        let res = run(NonFinitePolicy::Penalty(1e4));
+        assert!(matches!(res.state().termination_status(), Some(Ok(_))));
+        let best = res.state().get_best_param().unwrap();
+        assert!(best[0] >= -0.5);
+        assert_abs_diff_eq!(-0.5, best[0], epsilon = 1e-2);
+        assert!(res.state().get_best_cost() < 5.);
+    }
+
+    /// Paraboloid problem subject to x0 >= 0.2, the constraint being undefined (NaN)
+    /// for x0 < 0.1
+    struct NanConstraintProblem;
+
+    impl CostFunction for NanConstraintProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            let c = if x[0] < 0.1 { f64::NAN } else { x[0] - 0.2 };
+            Ok(vec![paraboloid(x, &mut ()), c])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_nan_constraint() {
+        let run = |policy: NanConstraintPolicy| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .nan_constraint_policy(policy)
+                .iprint(0)
+                .build();
+            Executor::new(NanConstraintProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+        };
+
+        let res = run(NanConstraintPolicy::NonFinite).unwrap();
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Err(FailStatus::NonFiniteCost))
+        );
+
+        let res = run(NanConstraintPolicy::Violation(10.)).unwrap();
+        let state = res.state();
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        let x = state.get_best_param().unwrap();
+        assert_abs_diff_eq!(0.2, x[0], epsilon = 1e-3);
+        assert_abs_diff_eq!(0., x[1], epsilon = 1e-3);
+        assert_abs_diff_eq!(14.4, state.get_best_cost(), epsilon = 1e-2);
+        assert!(state.is_feasible(1e-6));
+
+        for violation in [0., -1., f64::INFINITY] {
+            assert!(run(NanConstraintPolicy::Violation(violation)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_penalty_value_reached() {
+        use crate::Evaluation;
+
+        struct ContextParaboloidProblem;
+
+        impl CostFunction for ContextParaboloidProblem {
+            type Param = Vec<f64>;
+            type Output = Evaluation<Vec<f64>>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(Evaluation {
+                    objective: paraboloid(x, &mut ()),
+                    constraints: vec![x[0]],
+                    context: x.clone(),
+                })
+            }
+        }
+
+        let run = |policy: NonFinitePolicy| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .non_finite_policy(policy)
+                .iprint(0)
+                .build();
+            Executor::new(ContextParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+        };
+
+        // last evaluated point is the result, its context being kept
+        let res = run(NonFinitePolicy::Abort);
+        let best_cost = res.state().get_best_cost();
+        assert_eq!(res.state().best_context(), res.state().get_best_param());
+
+        // a genuine objective value equal to the penalty is not a penalized one
+        let res = run(NonFinitePolicy::Penalty(best_cost));
+        assert_eq!(res.state().get_best_cost(), best_cost);
+        assert_eq!(res.state().best_context(), res.state().get_best_param());
+    }
+
+    #[test]
+    fn test_paraboloid_output_size_mismatch() {
+        use argmin::core::TerminationReason;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Paraboloid problem forgetting its constraint on the third call
+        struct ForgetfulParaboloid(AtomicUsize);
+
+        impl CostFunction for ForgetfulParaboloid {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                let fx = paraboloid(x, &mut ());
+                if self.0.fetch_add(1, Ordering::Relaxed) == 2 {
+                    Ok(vec![fx])
+                } else {
+                    Ok(vec![fx, x[0]])
+                }
+            }
+        }
+
+        let res = Executor::new(
+            ForgetfulParaboloid(AtomicUsize::new(0)),
+            CobylaSolver::new(vec![1., 1.]),
+        )
+        .configure(|state| state.max_iters(100).iprint(0))
+        .run()
+        .unwrap();
+
+        let state = res.state();
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::InvalidArgs))
+        );
+        assert_eq!(state.func_evals(), 3);
+        assert!(matches!(
+            state.get_termination_reason(),
+            Some(TerminationReason::SolverExit(reason)) if reason.contains("output size (1)")
+        ));
+        assert_eq!(state.get_full_best_cost().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_paraboloid_stop_callback() {
+        let mut calls = 0;
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .stop_callback(move |x, f| {
+                assert_eq!(x.len(), 2);
+                assert!(f.is_finite());
+                calls += 1;
+                calls == 5
+            })
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Err(FailStatus::ForcedStop))
+        );
+        assert_eq!(res.state().func_evals(), 5);
+        assert!(res.state().get_best_cost() < 41.);
+    }
+
+    #[test]
+    fn test_paraboloid_interrupted() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        // Interruption flag set asynchronously, eg by a Ctrl-C handler
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let evals = Arc::new(AtomicUsize::new(0));
+        let (flag, counter) = (interrupted.clone(), evals.clone());
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .keep_evaluations(true)
+            .stop_callback(move |_, _| {
+                if counter.fetch_add(1, Ordering::Relaxed) + 1 == 20 {
+                    flag.store(true, Ordering::Relaxed);
+                }
+                flag.load(Ordering::Relaxed)
+            })
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::ForcedStop))
+        );
+        assert_eq!(state.func_evals(), 20);
+        assert!(interrupted.load(Ordering::Relaxed));
+        assert_eq!(evals.load(Ordering::Relaxed), 20);
+        // Best point so far is one of the evaluated points with its cost function output
+        let x = state.get_best_param().unwrap();
+        let fx = ParaboloidProblem.cost(x).unwrap();
+        assert!(
+            state
+                .evaluations()
+                .iter()
+                .any(|(xe, fe)| xe == x && fe == &fx)
+        );
+        assert_eq!(state.get_best_cost(), fx[0]);
+        assert_eq!(state.best_constraints(), &fx[1..]);
+        assert_eq!(state.best_max_constraint_violation(), 0f64.max(-fx[1]));
+        assert!(state.get_best_cost() < state.evaluations()[0].1[0]);
+    }
+
+    #[test]
+    fn test_paraboloid_progress_callback() {
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = progress.clone();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .on_progress(move |info| {
+                assert_eq!(info.x.len(), 2);
+                recorder.lock().unwrap().push((
+                    info.iter,
+                    info.objective,
+                    info.max_violation,
+                    info.rho,
+                ));
+            })
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let progress = progress.lock().unwrap();
+        assert_eq!(progress.len() as u64, res.state().get_iter());
+        for (k, &(iter, objective, max_violation, rho)) in progress.iter().enumerate() {
+            assert_eq!(iter, k as u64);
+            assert!(objective.is_finite());
+            assert!(max_violation >= 0.);
+            assert!(rho > 0.);
+        }
+        // non improving iterations are reported too
+        assert!(
+            progress
+                .iter()
+                .any(|&(_, f, _, _)| f > res.state().get_best_cost())
+        );
+        assert!(progress.windows(2).all(|w| w[1].3 <= w[0].3));
+    }
+
+    #[test]
+    fn test_paraboloid_last_improvement() {
+        let improvements = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = improvements.clone();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .on_progress(move |info| recorder.lock().unwrap().push(info.improvement))
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let improvements = improvements.lock().unwrap();
+        assert!(improvements.iter().all(|&d| d >= 0. && d.is_finite()));
+        // some iterations do not improve the best point
+        assert!(improvements.contains(&0.));
+        // x0 is feasible with f(x0) = 41: improvements add up to the total decrease
+        let total: f64 = improvements.iter().sum();
+        assert_abs_diff_eq!(total, 41. - 10., epsilon = 1e-2);
+        assert_eq!(
+            res.state().last_improvement(),
+            *improvements.last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_paraboloid_reproducibility() {
+        let run = || {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+        };
+        let (res1, res2) = (run(), run());
+        let (state1, state2) = (res1.state(), res2.state());
+
+        assert_eq!(state1.get_best_param(), state2.get_best_param());
+        assert_eq!(
+            state1.get_best_cost().to_bits(),
+            state2.get_best_cost().to_bits()
+        );
+        assert_eq!(state1.func_evals(), state2.func_evals());
+    }
+
+    #[test]
+    fn test_empty_param() {
+        use argmin::core::TerminationReason;
+
+        let res = Executor::new(ParaboloidProblem, CobylaSolver::new(Vec::<f64>::new()))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+        let state = res.state();
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::InvalidArgs))
+        );
+        assert_eq!(state.func_evals(), 0);
+        assert!(matches!(
+            state.get_termination_reason(),
+            Some(TerminationReason::SolverExit(reason)) if reason.contains("at least one component")
+        ));
+    }
+
+    /// Problem Definition for CobylaSolver : minimize (x - 0.3)^2 subject to x >= 0.5
+    struct OneDimProblem;
+
+    impl CostFunction for OneDimProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![(x[0] - 0.3).powi(2), x[0] - 0.5])
+        }
+    }
+
+    #[test]
+    fn test_one_dim() {
+        for x0 in [2., -1., 0.5] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![x0])
+                .iprint(0)
+                .build();
+            let res = Executor::new(OneDimProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap();
+            let state = res.state();
+            assert_abs_diff_eq!(0.5, state.get_best_param().unwrap()[0], epsilon = 1e-4);
+            assert_abs_diff_eq!(0.04, state.get_best_cost(), epsilon = 1e-4);
+            assert!(state.is_feasible(1e-6));
+            assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        }
+
+        // Degenerate bounds fix the only component
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1.])
+            .with_bounds(vec![1.], vec![1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(OneDimProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        let state = res.state();
+        assert_abs_diff_eq!(1., state.get_best_param().unwrap()[0], epsilon = 1e-6);
+        assert_abs_diff_eq!(0.49, state.get_best_cost(), epsilon = 1e-6);
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+    }
+
+    #[test]
+    fn test_paraboloid_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let solver = CobylaSolver::builder()
+                    .initial_param(vec![1. + i as f64, 1.])
+                    .iprint(0)
+                    .build();
+                std::thread::spawn(move || {
+                    Executor::new(ParaboloidProblem, solver)
+                        .configure(|state| state.max_iters(100))
+                        .run()
+                        .unwrap()
+                        .state
+                })
+            })
+            .collect();
+        for handle in handles {
+            let state = handle.join().unwrap();
+            assert_abs_diff_eq!(0., state.get_best_param().unwrap()[0], epsilon = 1e-2);
+            assert_abs_diff_eq!(0., state.get_best_param().unwrap()[1], epsilon = 1e-2);
+            assert_abs_diff_eq!(10., state.get_best_cost(), epsilon = 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_minimize() {
+        let options = MinimizeOptions {
+            bounds: Some((vec![-10., 0.5], vec![10., 2.])),
+            verbosity: Verbosity::Silent,
+            ..MinimizeOptions::default()
+        };
+        let report = minimize(ParaboloidProblem, vec![1., 1.], options.clone()).unwrap();
+        assert_abs_diff_eq!(0., report.best_param[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(0.5, report.best_param[1], epsilon = 1e-2);
+        assert_abs_diff_eq!(10.25, report.best_objective, epsilon = 1e-2);
+        assert_eq!(report.constraints, vec![report.best_param[0]]);
+        assert_eq!(report.status, Some(Ok(SuccessStatus::Success)));
+        assert!(report.func_evals >= report.iterations);
+
+        let options = MinimizeOptions {
+            max_fun_evals: Some(5),
+            ..options
+        };
+        let report = minimize(ParaboloidProblem, vec![1., 1.], options).unwrap();
+        assert_eq!(report.func_evals, 5);
+        assert_eq!(report.status, Some(Ok(SuccessStatus::MaxEvalReached)));
+    }
+
+    #[test]
+    fn test_paraboloid_stopval() {
+        let run = |stopval: f64| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .stopval(stopval)
+                .iprint(0)
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+                .state
+        };
+
+        let state = run(11.);
+        assert_eq!(
+            state.termination_status(),
+            Some(Ok(SuccessStatus::StopValReached))
+        );
+        assert!(state.get_best_cost() <= 11.);
+        assert!(state.is_feasible(1e-6));
+        let full = run(f64::NEG_INFINITY);
+        assert!(state.func_evals() < full.func_evals());
+
+        // Initial guess reaches the target
+        let state = run(100.);
+        assert_eq!(
+            state.termination_status(),
+            Some(Ok(SuccessStatus::StopValReached))
+        );
+        assert_eq!(state.func_evals(), 1);
+
+        // Target is only reached by infeasible points
+        let state = run(9.5);
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        assert_abs_diff_eq!(10., state.get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_max_fun_evals() {
+        // max_iters is left unbounded: only the evaluation budget stops the optimization
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .max_fun_evals(7)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver).run().unwrap();
+
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::MaxEvalReached))
+        );
+        assert_eq!(res.state().func_evals(), 7);
+        assert!(res.state().get_best_cost() < 41.);
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .max_fun_evals(0)
+            .iprint(0)
+            .build();
+        assert!(Executor::new(ParaboloidProblem, solver).run().is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_summary() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let summary = res.state().summary();
+        assert!(summary.contains("status:          Success"));
+        assert!(summary.contains("max violation:"));
+        assert!(summary.contains(&format!("func evals:      {}", res.state().func_evals())));
+        assert_eq!(summary, res.state().to_string());
+    }
+
+    #[test]
+    fn test_paraboloid_eval_time() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(SlowParaboloidProblem, solver)
+            .configure(|state| state.max_iters(10))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        let evals = state.func_evals() as u32;
+        assert!(state.eval_time() >= evals * std::time::Duration::from_millis(10));
+        assert!(state.solver_time() >= state.eval_time());
+        assert_eq!(state.mean_eval_time(), Some(state.eval_time() / evals));
+        assert!(state.summary().contains("per evaluation"));
+        assert_eq!(crate::CobylaState::<Vec<f64>>::new().mean_eval_time(), None);
+    }
+
+    #[test]
+    fn test_paraboloid_size_mismatch() {
+        use argmin::core::TerminationReason;
+
+        let check = |solver: CobylaSolver, size: &str| {
+            let res = Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap();
+            let state = res.state();
+            assert_eq!(
+                state.termination_status(),
+                Some(Err(FailStatus::InvalidArgs))
+            );
+            assert!(matches!(
+                state.get_termination_reason(),
+                Some(TerminationReason::SolverExit(reason))
+                    if reason.contains(size) && reason.contains("x dimension (2)")
+            ));
+        };
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .rhobeg(RhoBeg::Set(vec![0.5, 0.5, 0.5]))
+            .iprint(0)
+            .build();
+        check(solver, "rhobeg size (3)");
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .stop_tols(StopTols {
+                xtol_abs: vec![1e-3],
+                ..StopTols::default()
+            })
+            .iprint(0)
+            .build();
+        check(solver, "xtol_abs size (1)");
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .stop_tols(StopTols {
+                xtol_rel: XtolRel::set([1e-3, 1e-3, 1e-3]),
+                ..StopTols::default()
+            })
+            .iprint(0)
+            .build();
+        check(solver, "xtol_rel size (3)");
+    }
+
+    /// Paraboloid problem whose cost function fails for x0 < 0.5
+    struct FailingParaboloidProblem;
+
+    impl CostFunction for FailingParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            if x[0] < 0.5 {
+                return Err(Error::msg("degenerate geometry"));
+            }
+            Ok(vec![paraboloid(x, &mut ()), x[0]])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_cost_error() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let err = Executor::new(FailingParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .err()
+            .unwrap();
+
+        let Some(CobylaError::CostEvaluation(err)) = err.downcast_ref::<CobylaError>() else {
+            panic!("cost evaluation error expected");
+        };
+        assert!(err.iter > 0);
+        assert!(err.param[0] < 0.5);
+        assert_eq!(err.source.to_string(), "degenerate geometry");
+        assert!(err.to_string().contains("degenerate geometry"));
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 1.])
+            .iprint(0)
+            .build();
+        let err = Executor::new(FailingParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .err()
+            .unwrap();
+        let Some(CobylaError::CostEvaluation(err)) = err.downcast_ref::<CobylaError>() else {
+            panic!("cost evaluation error expected");
+        };
+        assert_eq!(err.iter, 0);
+        assert_eq!(err.param, vec![0., 1.]);
+    }
+
+    /// Paraboloid problem whose cost function panics for x0 < 0.5
+    struct PanickingParaboloidProblem;
+
+    impl CostFunction for PanickingParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            if x[0] < 0.5 {
+                panic!("degenerate geometry");
+            }
+            Ok(vec![paraboloid(x, &mut ()), x[0]])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_cost_panic() {
+        use argmin::core::TerminationReason;
+
+        let run = |x0: Vec<f64>, catch_panics: bool| {
+            let solver = CobylaSolver::builder()
+                .initial_param(x0)
+                .catch_panics(catch_panics)
+                .iprint(0)
+                .build();
+            Executor::new(PanickingParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap()
+                .state
+        };
+
+        let state = run(vec![1., 1.], true);
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::UnexpectedError))
+        );
+        assert!(matches!(
+            state.get_termination_reason(),
+            Some(TerminationReason::SolverExit(reason)) if reason.contains("degenerate geometry")
+        ));
+        // Best point found so far is kept
+        assert!(state.get_best_param().unwrap()[0] >= 0.5);
+        assert!(state.get_best_cost() < paraboloid(&[1., 1.], &mut ()));
+
+        let state = run(vec![0., 1.], true);
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::UnexpectedError))
+        );
+        assert_eq!(state.func_evals(), 1);
+
+        assert!(std::panic::catch_unwind(|| run(vec![1., 1.], false)).is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_custom_simplex() {
+        let vertices = vec![vec![1., 1.], vec![1.5, 1.2], vec![0.8, 1.6]];
+        let evaluated = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = evaluated.clone();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 0.])
+            .simplex_init(SimplexInit::Custom(vertices.clone()))
+            .stop_callback(move |x, _| {
+                recorder.lock().unwrap().push(x.to_vec());
+                false
+            })
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        assert_eq!(evaluated.lock().unwrap()[..3], vertices[..]);
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::Success))
+        );
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [0., 0.][..],
+            epsilon = 1e-3
+        );
+
+        for vertices in [
+            vec![vec![0., 0.], vec![1., 1.], vec![2., 2.]],
+            vec![vec![0., 0.], vec![1., 0.]],
+            vec![vec![0., 0.], vec![1., 0.], vec![0.]],
+        ] {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![0., 0.])
+                .simplex_init(SimplexInit::Custom(vertices))
+                .iprint(0)
+                .build();
+            assert!(Executor::new(ParaboloidProblem, solver).run().is_err());
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_degenerate_simplex() {
+        use crate::DegenerateSimplex;
+
+        let run = |vertices: Vec<Vec<f64>>, degenerate_simplex: DegenerateSimplex| {
+            let evaluated = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let recorder = evaluated.clone();
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .simplex_init(SimplexInit::Custom(vertices))
+                .degenerate_simplex(degenerate_simplex)
+                .stop_callback(move |x, _| {
+                    recorder.lock().unwrap().push(x.to_vec());
+                    false
+                })
+                .iprint(0)
+                .build();
+            let res = Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run();
+            let evaluated = evaluated.lock().unwrap().clone();
+            (res, evaluated)
+        };
+
+        // two coincident vertices
+        let coincident = vec![vec![1., 1.], vec![1.5, 1.], vec![1.5, 1.]];
+        let (res, _) = run(coincident.clone(), DegenerateSimplex::default());
+        assert!(matches!(
+            res.err().unwrap().downcast_ref::<CobylaError>(),
+            Some(CobylaError::InvalidParameter(text)) if text.contains("vertex 2")
+        ));
+        let (res, evaluated) = run(coincident, DegenerateSimplex::Perturb(1e-8));
+        assert_eq!(evaluated[..3], [vec![1., 1.], vec![1.5, 1.], vec![1., 1.5]]);
+        assert_abs_diff_eq!(
+            res.unwrap().state().get_best_param().unwrap()[..],
+            [0., 0.][..],
+            epsilon = 1e-3
+        );
+
+        // nearly collinear vertices are only rejected with a tolerance
+        let collinear = vec![vec![1., 1.], vec![1.5, 1.], vec![2., 1. + 1e-10]];
+        assert!(
+            run(collinear.clone(), DegenerateSimplex::Error(1e-8))
+                .0
+                .is_err()
+        );
+        assert!(run(collinear, DegenerateSimplex::Error(0.)).0.is_ok());
+    }
+
+    /// Paraboloid problem recording the sizes of batch evaluations
+    #[derive(Default)]
+    struct BatchParaboloidProblem {
+        batches: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl CostFunction for BatchParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            ParaboloidProblem.cost(x)
+        }
+
+        fn bulk_cost<P>(&self, params: &[P]) -> Result<Vec<Self::Output>, Error>
+        where
+            P: std::borrow::Borrow<Self::Param> + argmin::core::SyncAlias,
+            Self::Output: argmin::core::SendAlias,
+            Self: argmin::core::SyncAlias,
+        {
+            self.batches.lock().unwrap().push(params.len());
+            params.iter().map(|x| self.cost(x.borrow())).collect()
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_batch_initial_simplex() {
+        let vertices = vec![vec![1., 1.], vec![1.5, 1.2], vec![0.8, 1.6]];
+        let run = |batch: bool| {
+            let problem = BatchParaboloidProblem::default();
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .simplex_init(SimplexInit::Custom(vertices.clone()))
+                .batch_initial_simplex(batch)
+                .iprint(0)
+                .build();
+            let res = Executor::new(problem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap();
+            let batches = res
+                .problem()
+                .problem
+                .as_ref()
+                .unwrap()
+                .batches
+                .lock()
+                .unwrap()
+                .clone();
+            (
+                res.state().func_evals(),
+                res.state().get_best_param().unwrap().clone(),
+                batches,
+            )
+        };
+        let (evals, best, batches) = run(false);
+        assert!(batches.is_empty());
+        let (batch_evals, batch_best, batches) = run(true);
+        assert_eq!(batches, vec![2]);
+        // all batch evaluations are used: the optimization is unchanged
+        assert_eq!(batch_evals, evals);
+        assert_abs_diff_eq!(batch_best[..], best[..], epsilon = 1e-10);
+
+        // axis aligned vertices depend on the objective at the previous ones
+        let err = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .batch_initial_simplex(true)
+            .validate()
+            .unwrap_err();
+        assert!(err[0].message.contains("batch"));
+
+        /// Paraboloid problem whose batch evaluations fail
+        struct FailingBatchProblem;
+
+        impl CostFunction for FailingBatchProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                ParaboloidProblem.cost(x)
+            }
+
+            fn bulk_cost<P>(&self, _params: &[P]) -> Result<Vec<Self::Output>, Error>
+            where
+                P: std::borrow::Borrow<Self::Param> + argmin::core::SyncAlias,
+                Self::Output: argmin::core::SendAlias,
+                Self: argmin::core::SyncAlias,
+            {
+                Err(Error::msg("batch failure"))
+            }
+        }
+
+        // batch failure is reported as the failure of a single evaluation
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .simplex_init(SimplexInit::Custom(vertices.clone()))
+            .batch_initial_simplex(true)
+            .iprint(0)
+            .build();
+        let err = Executor::new(FailingBatchProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .err()
+            .unwrap();
+        let Some(CobylaError::CostEvaluation(err)) = err.downcast_ref::<CobylaError>() else {
+            panic!("cost evaluation error expected");
+        };
+        assert_eq!(err.iter, 1);
+        assert!(err.param.is_empty());
+        assert_eq!(err.source.to_string(), "batch failure");
+    }
+
+    #[test]
+    fn test_paraboloid_roundoff_limited() {
+        use argmin::core::TerminationReason;
+
+        // Nearly degenerate simplex: its inverse is spoiled by rounding errors
+        let eps = 1e-15;
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .simplex_init(SimplexInit::Custom(vec![
+                vec![1., 1.],
+                vec![1.3, 1.7],
+                vec![1.3 + 0.7 * eps, 1.7 + 0.3 * eps],
+            ]))
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::RoundoffLimited))
+        );
+        let roundoff = state.roundoff().unwrap();
+        assert_eq!(roundoff.rho, state.current_rho());
+        assert!(roundoff.step > 0.);
+        assert!(matches!(
+            state.get_termination_reason(),
+            Some(TerminationReason::SolverExit(reason)) if reason.contains("last step")
+        ));
+
+        let res = Executor::new(ParaboloidProblem, CobylaSolver::new(vec![1., 1.]))
+            .configure(|state| state.max_iters(100).iprint(0))
+            .run()
+            .unwrap();
+        assert!(res.state().roundoff().is_none());
+    }
+
+    #[test]
+    fn test_paraboloid_max_restarts() {
+        use crate::CobylaState;
+        use argmin::core::KV;
+        use argmin::core::observers::{Observe, ObserverMode};
+        use std::sync::{Arc, Mutex};
+
+        /// Records the restart points, which are the last evaluated points when the
+        /// number of restarts increases
+        #[derive(Default, Clone)]
+        struct RestartObserver(Arc<Mutex<(usize, Vec<Vec<f64>>)>>);
+
+        impl Observe<CobylaState> for RestartObserver {
+            fn observe_iter(&mut self, state: &CobylaState, _kv: &KV) -> Result<(), Error> {
+                let mut restarts = self.0.lock().unwrap();
+                if state.restarts() > restarts.0 {
+                    let (x, _) = state.evaluations().last().unwrap();
+                    let previous = &state.evaluations()[..state.evaluations().len() - 1];
+                    // the restart point is moved away from the previously evaluated points
+                    assert!(previous.iter().all(|(xk, _)| xk != x));
+                    assert_eq!(state.get_param(), Some(x));
+                    restarts.0 = state.restarts();
+                    restarts.1.push(x.clone());
+                }
+                Ok(())
+            }
+        }
+
+        let eps = 1e-15;
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .simplex_init(SimplexInit::Custom(vec![
+                vec![1., 1.],
+                vec![1.3, 1.7],
+                vec![1.3 + 0.7 * eps, 1.7 + 0.3 * eps],
+            ]))
+            .max_restarts(2)
+            .keep_evaluations(true)
+            .iprint(0)
+            .build();
+        let observer = RestartObserver::default();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .add_observer(observer.clone(), ObserverMode::Always)
+            .run()
+            .unwrap();
+
+        let state = res.state();
+        assert_eq!(state.restarts(), 1);
+        assert_eq!(observer.0.lock().unwrap().1.len(), 1);
+        assert_eq!(state.termination_status(), Some(Ok(SuccessStatus::Success)));
+        assert!(state.roundoff().is_none());
+        assert_abs_diff_eq!(0., state.get_best_param().unwrap()[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(10., state.get_best_cost(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_paraboloid_restart_bounds_and_budget() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let eps = 1e-15;
+        let builder = || {
+            CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .simplex_init(SimplexInit::Custom(vec![
+                    vec![1., 1.],
+                    vec![1.3, 1.7],
+                    vec![1.3 + 0.7 * eps, 1.7 + 0.3 * eps],
+                ]))
+                .max_restarts(1)
+                .keep_evaluations(true)
+                .iprint(0)
+        };
+        let run = |solver: CobylaSolver| {
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        // best point is moved by -0.05 along x0 and +0.05 along x1 without bounds, x0 is
+        // moved the other way to stay within x0 >= 0
+        let res = run(builder()
+            .with_bounds(vec![0., f64::NEG_INFINITY], vec![f64::INFINITY; 2])
+            .build());
+        let state = res.state();
+        assert_eq!(state.restarts(), 1);
+        let (xb, _) = &state.evaluations()[4];
+        let (xr, _) = &state.evaluations()[5];
+        assert!(xb[0] < 0.05);
+        assert_abs_diff_eq!(xb[0] + 0.05, xr[0], epsilon = 1e-12);
+        assert_abs_diff_eq!(xb[1] + 0.05, xr[1], epsilon = 1e-12);
+
+        // no evaluation is left for the restart point: the degenerate run is not restarted
+        let res = run(builder().max_fun_evals(5).build());
+        let state = res.state();
+        assert_eq!(state.restarts(), 0);
+        assert_eq!(state.func_evals(), 5);
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::RoundoffLimited))
+        );
+
+        /// Paraboloid problem returning an additional constraint from the 6th evaluation
+        struct GrowingProblem(AtomicUsize);
+
+        impl CostFunction for GrowingProblem {
+            type Param = Vec<f64>;
+            type Output = Vec<f64>;
+
+            fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+                let mut fx = ParaboloidProblem.cost(x)?;
+                if self.0.fetch_add(1, Ordering::Relaxed) >= 5 {
+                    fx.push(1.);
+                }
+                Ok(fx)
+            }
+        }
+
+        // output size changes at the restart point: the run stops with the best point
+        let res = Executor::new(GrowingProblem(AtomicUsize::new(0)), builder().build())
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+        let state = res.state();
+        assert_eq!(state.func_evals(), 6);
+        assert_eq!(
+            state.termination_status(),
+            Some(Err(FailStatus::InvalidArgs))
+        );
+        assert_eq!(state.get_best_param(), Some(&state.evaluations()[4].0));
+    }
+
+    #[test]
+    fn test_paraboloid_stagnation() {
+        let run = |solver: CobylaSolver| {
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+        };
+        let full = run(CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build())
+        .unwrap();
+        let res = run(CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .stagnation_window(3, 1e-2)
+            .iprint(0)
+            .build())
+        .unwrap();
+
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::Stagnated))
+        );
+        assert!(res.state().func_evals() < full.state().func_evals());
+        assert_abs_diff_eq!(res.state().get_best_cost(), 10., epsilon = 1e-1);
+
+        let res = run(CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .stagnation_window(0, 1e-2)
+            .iprint(0)
+            .build());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_paraboloid_evaluations() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .keep_evaluations(true)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        let evaluations = res.state().evaluations();
+        assert_eq!(evaluations.len() as u64, res.state().func_evals());
+        assert_eq!(evaluations[0].0, vec![1., 1.]);
+        for (x, fx) in evaluations {
+            assert_eq!(fx, &ParaboloidProblem.cost(x).unwrap());
+        }
+
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .iprint(0)
+            .build();
+        let res = Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+        assert!(res.state().evaluations().is_empty());
+    }
+
+    /// Writer sharing its buffer to capture COBYLA printouts
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_verbosity_output() {
+        let run = |verbosity| {
+            let buffer = SharedBuffer::default();
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .verbosity(verbosity)
+                .output(buffer.clone())
+                .build();
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(100))
+                .run()
+                .unwrap();
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap()
+        };
+
+        assert!(run(Verbosity::Silent).is_empty());
+        let summary = run(Verbosity::Summary);
+        assert!(summary.contains("Normal return from subroutine COBYLA"));
+        let nb_evals = |output: &str| output.matches("NFVALS").count();
+        assert!(nb_evals(&run(Verbosity::Debug)) > nb_evals(&summary));
+
+        // Starting penalty parameter is the one printed
+        let buffer = SharedBuffer::default();
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .initial_penalty(10.)
+            .verbosity(Verbosity::Debug)
+            .output(buffer.clone())
+            .build();
+        Executor::new(ParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("PARMU is set to 10."));
+        assert!(run(Verbosity::Debug).contains("PARMU is set to 0."));
+    }
+
+    /// Unconstrained problem whose minimum lies halfway between the first two evaluated
+    /// points (x0 = [0, 0] with rhobeg = 0.5) which hence give the same objective value
+    struct ShiftedParaboloidProblem;
+
+    impl CostFunction for ShiftedParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![(x[0] - 0.25).powf(2.) + (x[1] - 1.).powf(2.)])
+        }
+    }
+
+    #[test]
+    fn test_ftol_against_best() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![0., 0.])
+            .rhobeg(RhoBeg::All(0.5))
+            .stop_tols(StopTols {
+                ftol_abs: 1e-8,
+                ..StopTols::default()
+            })
+            .keep_history(true)
+            .iprint(0)
+            .build();
+        let res = Executor::new(ShiftedParaboloidProblem, solver)
+            .configure(|state| state.max_iters(200))
+            .run()
+            .unwrap();
+
+        // the second evaluation does not improve the first one: no early termination
+        let history = res.state().history();
+        assert_eq!(history[0].1, history[1].1);
+        assert!(res.state().func_evals() > 2);
+        assert_abs_diff_eq!(
+            res.state().get_best_param().unwrap()[..],
+            [0.25, 1.][..],
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn test_ftol_init() {
+        let run = |stop_tols: StopTols| {
+            let solver = CobylaSolver::builder()
+                .initial_param(vec![0., 0.])
+                .rhobeg(RhoBeg::All(0.5))
+                .stop_tols(stop_tols)
+                .iprint(0)
+                .build();
+            Executor::new(ShiftedParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200))
+                .run()
+                .unwrap()
+        };
+
+        // the objective goes to zero: improvements are small relative to the initial value
+        // long before being small relative to the current value
+        let res = run(StopTols {
+            ftol_init: 1e-3,
+            ..StopTols::default()
+        });
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::FtolReached))
+        );
+        assert!(res.state().get_best_cost() < 1e-2);
+        let res_rel = run(StopTols {
+            ftol_rel: 1e-3,
+            ..StopTols::default()
+        });
+        assert!(res.state().func_evals() < res_rel.state().func_evals());
+    }
+
+    /// Paraboloid problem with a slow cost function
+    struct SlowParaboloidProblem;
+
+    impl CostFunction for SlowParaboloidProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            Ok(vec![paraboloid(x, &mut ()), x[0]])
+        }
+    }
+
+    #[test]
+    fn test_paraboloid_max_time() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .max_time(std::time::Duration::from_millis(50))
+            .iprint(0)
+            .build();
+        let res = Executor::new(SlowParaboloidProblem, solver)
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            res.state().termination_status(),
+            Some(Ok(SuccessStatus::MaxTimeReached))
+        );
+        assert!(res.state().func_evals() < 20);
+        assert!(res.state().get_best_param().is_some());
+        assert!(res.state().get_best_cost() < 41.);
+    }
+
+    /// Double well function with global minimum near x = -1 and local one near x = 1
+    struct DoubleWellProblem;
+
+    impl CostFunction for DoubleWellProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![(x[0] * x[0] - 1.).powi(2) + 0.3 * x[0], x[0] + 2.])
+        }
+    }
+
+    #[test]
+    fn test_double_well_multistart() {
+        use crate::MultiStartCobyla;
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let multistart = MultiStartCobyla::new(CobylaSolver::builder().iprint(0)).max_iters(200);
+        let res = multistart
+            .run(&DoubleWellProblem, vec![vec![2.], vec![-0.5], vec![0.5]])
+            .unwrap();
+        assert_eq!(res.results.len(), 3);
+        assert_eq!(res.best_index, 1);
+        assert_abs_diff_eq!(-1., res.best().get_best_param().unwrap()[0], epsilon = 0.1);
+
+        let run = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            multistart
+                .run_random(&DoubleWellProblem, 4, &[-2.], &[2.], &mut rng)
+                .unwrap()
+        };
+        let (res1, res2) = (run(42), run(42));
+        assert_eq!(res1.best_index, res2.best_index);
+        for (s1, s2) in res1.results.iter().zip(&res2.results) {
+            assert_eq!(s1.get_best_param(), s2.get_best_param());
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_double_well_par_multistart() {
+        use crate::MultiStartCobyla;
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let multistart = MultiStartCobyla::new(CobylaSolver::builder().iprint(0)).max_iters(200);
+        let starts = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..16)
+                .map(|_| vec![rand::Rng::random_range(&mut rng, -2.0..2.0)])
+                .collect::<Vec<_>>()
+        };
+        let res = multistart.run(&DoubleWellProblem, starts(0)).unwrap();
+        let par_res = multistart.par_run(&DoubleWellProblem, starts(0)).unwrap();
+
+        assert_eq!(par_res.best_index, res.best_index);
+        assert_eq!(par_res.results.len(), 16);
+        for (s1, s2) in res.results.iter().zip(&par_res.results) {
+            assert_eq!(s1.get_best_param(), s2.get_best_param());
+            assert_eq!(s1.get_full_best_cost(), s2.get_full_best_cost());
+        }
+    }
+
+    #[test]
+    fn test_multistart_feasibility_first() {
+        use crate::{CobylaState, MultiStartCobyla};
+
+        let state = |cost: Vec<f64>| {
+            let mut state: CobylaState = CobylaState::new();
+            state.best_cost = Some(cost);
+            state
+        };
+        let multistart = MultiStartCobyla::new(CobylaSolver::builder());
+        let res = multistart.select(vec![
+            state(vec![1., -1.]),
+            state(vec![5., 0.5]),
+            state(vec![3., 0.]),
+            state(vec![0., -0.5]),
+        ]);
+        assert_eq!(res.best_index, 2);
+        let res = multistart.select(vec![state(vec![1., -1.]), state(vec![5., -0.5])]);
+        assert_eq!(res.best_index, 1);
+    }
+
+    /// Rosenbrock function, slow enough to converge to test resuming
+    #[cfg(feature = "serde1")]
+    struct RosenbrockProblem;
+
+    #[cfg(feature = "serde1")]
+    impl CostFunction for RosenbrockProblem {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn cost(&self, x: &Self::Param) -> Result<Self::Output, Error> {
+            let fx = 100. * (x[1] - x[0] * x[0]).powi(2) + (1. - x[0]).powi(2);
+            Ok(vec![fx, 2. - x[0] * x[0] - x[1] * x[1]])
+        }
+    }
+
+    /// In-memory checkpoint which keeps the first saved solver and state, saved every
+    /// given number of iterations
+    #[cfg(feature = "serde1")]
+    #[derive(Clone)]
+    struct MemoryCheckpoint(std::rc::Rc<std::cell::RefCell<Option<Vec<u8>>>>, u64);
+
+    #[cfg(feature = "serde1")]
+    impl argmin::core::checkpointing::Checkpoint<CobylaSolver, crate::CobylaState>
+        for MemoryCheckpoint
+    {
+        fn save(&self, solver: &CobylaSolver, state: &crate::CobylaState) -> Result<(), Error> {
+            let mut saved = self.0.borrow_mut();
+            if saved.is_none() {
+                *saved = Some(bincode::serialize(&(solver, state))?);
+            }
+            Ok(())
+        }
+
+        fn load(&self) -> Result<Option<(CobylaSolver, crate::CobylaState)>, Error> {
+            match self.0.borrow().as_ref() {
+                Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+                None => Ok(None),
+            }
+        }
+
+        fn frequency(&self) -> argmin::core::checkpointing::CheckpointingFrequency {
+            argmin::core::checkpointing::CheckpointingFrequency::Every(self.1)
+        }
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_rosenbrock_checkpoint_resume() {
+        use argmin::core::checkpointing::Checkpoint;
+
+        let solver = || {
+            CobylaSolver::builder()
+                .initial_param(vec![-1.2, 1.])
+                .rhoend(1e-10)
+                .iprint(0)
+                .build()
+        };
+
+        // Uninterrupted run saving a checkpoint at iteration 50
+        let checkpoint = MemoryCheckpoint(Default::default(), 50);
+        let full = Executor::new(RosenbrockProblem, solver())
+            .configure(|state| state.max_iters(100))
+            .checkpointing(checkpoint.clone())
+            .run()
+            .unwrap();
+        assert_eq!(full.state().get_iter(), 100);
+        let (_, saved) = checkpoint.load().unwrap().unwrap();
+        assert_eq!(saved.get_iter(), 50);
+        assert!(saved.cobyla_context.is_some());
+
+        // Resume from iteration 50 checkpoint
+        let resumed = Executor::new(RosenbrockProblem, solver())
+            .configure(|state| state.max_iters(100))
+            .checkpointing(checkpoint)
+            .run()
+            .unwrap();
+
+        assert_eq!(resumed.state().get_iter(), 100);
+        assert_eq!(resumed.state().func_evals(), full.state().func_evals());
+        assert_eq!(
+            resumed.state().get_best_param().unwrap(),
+            full.state().get_best_param().unwrap()
+        );
+        assert_eq!(
+            resumed.state().get_full_best_cost().unwrap(),
+            full.state().get_full_best_cost().unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_paraboloid_interior_stop_checkpoint_resume() {
+        use argmin::core::checkpointing::Checkpoint;
+
+        let solver = || {
+            CobylaSolver::builder()
+                .initial_param(vec![1., 1.])
+                .with_bounds(vec![-5., -5.], vec![5., 5.])
+                .rhoend(1e-8)
+                .interior_stop(0.1, 1e-2)
+                .keep_simplex(true)
+                .iprint(0)
+                .build()
+        };
+
+        // Uninterrupted run saving a checkpoint at iteration 30, after trust region steps
+        let checkpoint = MemoryCheckpoint(Default::default(), 30);
+        let full = Executor::new(UnconstrainedParaboloid, solver())
+            .configure(|state| state.max_iters(500))
+            .checkpointing(checkpoint.clone())
+            .run()
+            .unwrap();
+        assert_eq!(
+            full.state().termination_status(),
+            Some(Ok(SuccessStatus::FlatInterior))
+        );
+
+        // Last trust region step of the context survives the round trip
+        let (saved_solver, mut saved) = checkpoint.load().unwrap().unwrap();
+        assert_eq!(saved.get_iter(), 30);
+        assert!(saved.trust_region_step().is_some());
+        let ctx = saved.cobyla_context.as_mut().unwrap().as_mut_ptr();
+        let (steps, prerem) = unsafe { ((*ctx).trial_step, (*ctx).trial_prerem) };
+        assert!(steps > 0);
+        assert!(prerem > 0.);
+        let bytes = bincode::serialize(&(&saved_solver, &saved)).unwrap();
+        assert_eq!(Some(bytes), *checkpoint.0.borrow());
+
+        // Resumed run stops on the flat interior at the same iteration
+        let resumed = Executor::new(UnconstrainedParaboloid, solver())
+            .configure(|state| state.max_iters(500))
+            .checkpointing(checkpoint)
+            .run()
+            .unwrap();
+        assert_eq!(
+            resumed.state().termination_status(),
+            full.state().termination_status()
+        );
+        assert_eq!(resumed.state().get_iter(), full.state().get_iter());
+        assert_eq!(resumed.state().func_evals(), full.state().func_evals());
+        assert_eq!(
+            resumed.state().trust_region_step(),
+            full.state().trust_region_step()
+        );
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_paraboloid_config() {
+        let solver = CobylaSolver::builder()
+            .initial_param(vec![1., 1.])
+            .rhobeg(RhoBeg::All(0.2))
+            .rho_reduction(0.3)
+            .initial_penalty(10.)
+            .sense(Sense::Minimize)
+            .with_cache(100, 1e-12)
+            .catch_panics(false)
+            .iprint(0)
+            .build();
+        let mut config = solver.config();
+        config.seed = Some(42);
+        let bytes = bincode::serialize(&config).unwrap();
+        let config: crate::CobylaConfig = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(config.seed, Some(42));
+        assert_eq!(config.rho_reduction, 0.3);
+        assert_eq!(config.cache, Some((100, 1e-12)));
+        assert!(!config.catch_panics);
+
+        let run = |solver: CobylaSolver| {
+            Executor::new(ParaboloidProblem, solver)
+                .configure(|state| state.max_iters(200).iprint(0))
+                .run()
+                .unwrap()
+        };
+        let reproduced = CobylaSolver::from_config(config);
+        assert_eq!(reproduced.config().seed, Some(42));
+        let (res, reproduced) = (run(solver), run(reproduced));
+        assert_eq!(res.state().func_evals(), reproduced.state().func_evals());
+        assert_eq!(res.state().cache_hits(), reproduced.state().cache_hits());
+        assert_eq!(
+            res.state().get_best_param(),
+            reproduced.state().get_best_param()
+        );
+    }
 }